@@ -0,0 +1,209 @@
+use std::collections::VecDeque;
+use std::io;
+
+use bytes::BytesMut;
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::codec::{self, ChecksumMode, CodecError};
+use crate::framing::{FrameDecoder, FrameError};
+use crate::header::ValidationConfig;
+use crate::packet::Packet;
+
+/// `tokio_util` codec for byteframe packets (`tokio-util` feature).
+///
+/// Decoding goes through [`FrameDecoder`], so fragmented input, budgets,
+/// validation and checksum settings behave exactly as in
+/// [`PacketReader`](crate::reader::PacketReader).
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn run() -> std::io::Result<()> {
+/// use byteframe::async_io::ByteframeCodec;
+/// use tokio::net::TcpStream;
+/// use tokio_util::codec::Framed;
+///
+/// let stream = TcpStream::connect("127.0.0.1:8080").await?;
+/// let framed = Framed::new(stream, ByteframeCodec::new());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct ByteframeCodec {
+    decoder: FrameDecoder,
+    decoded: VecDeque<Packet>,       // Packets decoded but not yet handed out
+    failed: Option<FrameError>,      // First framing error, reported after `decoded` drains
+    encode_buffer: Vec<u8>,
+    checksum_mode: ChecksumMode,     // Applied to outgoing frames
+}
+
+impl ByteframeCodec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a codec around a preconfigured decoder (budget, validation, ...).
+    pub fn with_decoder(decoder: FrameDecoder) -> Self {
+        Self {
+            checksum_mode: decoder.checksum_mode(),
+            decoder,
+            ..Self::default()
+        }
+    }
+
+    /// Choose whether frames carry and verify a payload checksum, in both directions.
+    pub fn set_checksum_mode(&mut self, mode: ChecksumMode) {
+        self.checksum_mode = mode;
+        self.decoder.set_checksum_mode(mode);
+    }
+
+    /// Reject frames whose header breaks `config` before buffering their payload.
+    pub fn set_validation(&mut self, config: ValidationConfig) {
+        self.decoder.set_validation(config);
+    }
+
+    /// The underlying decoder, for its frame size and resync statistics.
+    pub fn decoder(&self) -> &FrameDecoder {
+        &self.decoder
+    }
+
+    /// Hand out the next buffered packet, or the pending error once none are left.
+    fn next_item(&mut self) -> Result<Option<Packet>, ByteframeCodecError> {
+        if let Some(packet) = self.decoded.pop_front() {
+            return Ok(Some(packet));
+        }
+        match self.failed.take() {
+            Some(err) => Err(ByteframeCodecError::Frame(err)),
+            None => Ok(None),
+        }
+    }
+}
+
+impl Decoder for ByteframeCodec {
+    type Item = Packet;
+    type Error = ByteframeCodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Packet>, Self::Error> {
+        if self.decoded.is_empty() && self.failed.is_none() && !src.is_empty() {
+            let input = src.split(); // The decoder keeps partial frames itself
+            let result = self.decoder.decode(&input);
+            self.decoded.extend(result.packets);
+            self.failed = result.errors.into_iter().next();
+        }
+        self.next_item()
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Packet>, Self::Error> {
+        if let Some(packet) = self.decode(src)? {
+            return Ok(Some(packet));
+        }
+        if self.decoder.is_mid_frame() {
+            self.decoder.reset();
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "stream closed in the middle of a frame").into());
+        }
+        Ok(None)
+    }
+}
+
+impl Encoder<Packet> for ByteframeCodec {
+    type Error = ByteframeCodecError;
+
+    fn encode(&mut self, packet: Packet, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        self.encode_buffer.clear();
+        codec::encode_with(&packet, &mut self.encode_buffer, self.checksum_mode).map_err(ByteframeCodecError::Codec)?;
+        dst.extend_from_slice(&self.encode_buffer);
+        Ok(())
+    }
+}
+
+/// Errors produced by [`ByteframeCodec`].
+#[derive(Debug)]
+pub enum ByteframeCodecError {
+    /// The transport failed or closed mid-frame.
+    Io(io::Error),
+    /// An incoming frame could not be decoded.
+    Frame(FrameError),
+    /// An outgoing packet could not be encoded.
+    Codec(CodecError),
+}
+
+impl From<io::Error> for ByteframeCodecError {
+    fn from(err: io::Error) -> Self {
+        ByteframeCodecError::Io(err)
+    }
+}
+
+impl core::fmt::Display for ByteframeCodecError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ByteframeCodecError::Io(err) => write!(f, "i/o error: {err}"),
+            ByteframeCodecError::Frame(err) => write!(f, "framing error: {err:?}"),
+            ByteframeCodecError::Codec(err) => write!(f, "encode error: {err:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ByteframeCodecError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ByteframeCodecError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{SinkExt, StreamExt};
+    use tokio_util::codec::{Framed, FramedRead};
+
+    #[tokio::test]
+    async fn round_trips_through_framed() {
+        let (client, server) = tokio::io::duplex(32);
+        let mut sender = Framed::new(client, ByteframeCodec::new());
+        let mut receiver = Framed::new(server, ByteframeCodec::new());
+
+        let packets = vec![Packet::Ping, Packet::Data(b"hi".to_vec()), Packet::Data(vec![9; 200])];
+        let outgoing = packets.clone();
+        tokio::spawn(async move {
+            for packet in outgoing {
+                sender.send(packet).await.unwrap();
+            }
+        });
+
+        let mut received = Vec::new();
+        while let Some(packet) = receiver.next().await {
+            received.push(packet.unwrap());
+        }
+        assert_eq!(received, packets);
+        assert_eq!(receiver.codec().decoder().frame_sizes().count(), 3);
+    }
+
+    #[tokio::test]
+    async fn reports_packets_before_framing_error() {
+        let mut bytes = Vec::new();
+        codec::encode(&Packet::Ping, &mut bytes).unwrap();
+        let start = bytes.len();
+        codec::encode(&Packet::Data(vec![1, 2, 3]), &mut bytes).unwrap();
+        bytes[start + 9] ^= 0xFF; // Corrupt the payload
+
+        let mut reader = FramedRead::new(&bytes[..], ByteframeCodec::new());
+        assert_eq!(reader.next().await.unwrap().unwrap(), Packet::Ping);
+        assert!(matches!(
+            reader.next().await.unwrap(),
+            Err(ByteframeCodecError::Frame(FrameError::Codec(CodecError::ChecksumMismatch { .. })))
+        ));
+    }
+
+    #[tokio::test]
+    async fn truncated_frame_at_eof_is_an_error() {
+        let mut bytes = Vec::new();
+        codec::encode(&Packet::Data(b"cut short".to_vec()), &mut bytes).unwrap();
+        bytes.truncate(bytes.len() - 2);
+
+        let mut reader = FramedRead::new(&bytes[..], ByteframeCodec::new());
+        let err = reader.next().await.unwrap().unwrap_err();
+        assert!(matches!(err, ByteframeCodecError::Io(ref io) if io.kind() == io::ErrorKind::UnexpectedEof));
+    }
+}
@@ -0,0 +1,98 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use super::{AsyncPacketReader, AsyncPacketWriter};
+
+/// Adapts a `futures_io` stream (async-std, smol, ...) to the Tokio I/O
+/// traits the async packet types are built on (`futures-io` feature).
+///
+/// No Tokio runtime is involved; the adapter only translates the traits.
+#[derive(Debug)]
+pub struct FuturesIo<T>(T);
+
+impl<T> FuturesIo<T> {
+    pub fn new(inner: T) -> Self {
+        Self(inner)
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.0
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: futures_io::AsyncRead + Unpin> AsyncRead for FuturesIo<T> {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let unfilled = buf.initialize_unfilled();
+        match Pin::new(&mut self.get_mut().0).poll_read(cx, unfilled) {
+            Poll::Ready(Ok(n)) => {
+                buf.advance(n);
+                Poll::Ready(Ok(()))
+            }
+            Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<T: futures_io::AsyncWrite + Unpin> AsyncWrite for FuturesIo<T> {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().0).poll_close(cx)
+    }
+}
+
+impl<R: futures_io::AsyncRead + Unpin> AsyncPacketReader<FuturesIo<R>> {
+    /// Create a packet reader over a `futures_io::AsyncRead` source.
+    pub fn from_futures_io(reader: R) -> Self {
+        Self::new(FuturesIo::new(reader))
+    }
+}
+
+impl<W: futures_io::AsyncWrite + Unpin> AsyncPacketWriter<FuturesIo<W>> {
+    /// Create a packet writer over a `futures_io::AsyncWrite` sink.
+    pub fn from_futures_io(writer: W) -> Self {
+        Self::new(FuturesIo::new(writer))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packet::Packet;
+    use futures::executor::block_on;
+    use futures::io::Cursor;
+
+    #[test]
+    fn round_trips_without_tokio_runtime() {
+        block_on(async {
+            let mut writer = AsyncPacketWriter::from_futures_io(Cursor::new(Vec::new()));
+            writer.write_packet(&Packet::Data(b"smol".to_vec())).await.unwrap();
+            writer.write_packet(&Packet::Data(vec![1, 2, 3])).await.unwrap();
+            writer.close().await.unwrap();
+
+            let bytes = writer.into_writer().into_inner().into_inner();
+            let mut reader = AsyncPacketReader::from_futures_io(Cursor::new(bytes));
+            assert_eq!(reader.read_packet().await.unwrap(), Packet::Data(b"smol".to_vec()));
+            assert_eq!(reader.read_packet().await.unwrap(), Packet::Data(vec![1, 2, 3]));
+            assert_eq!(reader.read_packet().await.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        });
+    }
+}
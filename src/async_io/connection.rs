@@ -0,0 +1,142 @@
+use std::io;
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadHalf, WriteHalf};
+
+use super::{AsyncPacketReader, AsyncPacketWriter};
+use crate::keepalive::{KeepaliveConfig, KeepaliveConnection};
+use crate::packet::Packet;
+use crate::reader::ReadOpts;
+use crate::writer::WriteOpts;
+
+/// An async packet connection over a single duplex stream.
+///
+/// Use it directly for request/response traffic, or [`split`](Self::split)
+/// it into a reader and a writer that can live in different tasks; no
+/// `try_clone()` of the socket is needed.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn run() -> std::io::Result<()> {
+/// use byteframe::async_io::Connection;
+/// use byteframe::Packet;
+/// use tokio::net::TcpStream;
+///
+/// let stream = TcpStream::connect("127.0.0.1:8080").await?;
+/// let (mut reader, mut writer) = Connection::new(stream).split();
+///
+/// tokio::spawn(async move {
+///     while let Ok(packet) = reader.read_packet().await {
+///         println!("Received: {:?}", packet);
+///     }
+/// });
+/// writer.write_packet(&Packet::Ping).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Connection<T> {
+    reader: AsyncPacketReader<ReadHalf<T>>,
+    writer: AsyncPacketWriter<WriteHalf<T>>,
+}
+
+impl<T: AsyncRead + AsyncWrite> Connection<T> {
+    pub fn new(stream: T) -> Self {
+        let (read_half, write_half) = tokio::io::split(stream);
+        Self {
+            reader: AsyncPacketReader::new(read_half),
+            writer: AsyncPacketWriter::new(write_half),
+        }
+    }
+
+    /// Rebuild a connection from the halves returned by [`split`](Self::split).
+    pub fn from_parts(reader: AsyncPacketReader<ReadHalf<T>>, writer: AsyncPacketWriter<WriteHalf<T>>) -> Self {
+        Self { reader, writer }
+    }
+
+    /// Read one complete packet; see [`AsyncPacketReader::read_packet`].
+    pub async fn read_packet(&mut self) -> io::Result<Packet> {
+        self.reader.read_packet().await
+    }
+
+    /// Write one packet; see [`AsyncPacketWriter::write_packet`].
+    pub async fn write_packet(&mut self, packet: &Packet) -> io::Result<()> {
+        self.writer.write_packet(packet).await
+    }
+
+    /// Read with per-call options; see [`AsyncPacketReader::read_packet_with`].
+    pub async fn read_packet_with(&mut self, opts: &ReadOpts) -> io::Result<Packet> {
+        self.reader.read_packet_with(opts).await
+    }
+
+    /// Write with per-call options; see [`AsyncPacketWriter::write_packet_with`].
+    pub async fn write_packet_with(&mut self, packet: &Packet, opts: &WriteOpts) -> io::Result<()> {
+        self.writer.write_packet_with(packet, opts).await
+    }
+
+    pub async fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush().await
+    }
+
+    /// Split into independent read and write halves.
+    pub fn split(self) -> (AsyncPacketReader<ReadHalf<T>>, AsyncPacketWriter<WriteHalf<T>>) {
+        (self.reader, self.writer)
+    }
+
+    /// Start pinging the peer; reads fail with `TimedOut` once it stops answering.
+    pub fn keepalive(self, config: KeepaliveConfig) -> KeepaliveConnection<T>
+    where
+        T: Send + 'static,
+    {
+        KeepaliveConnection::new(self.reader, self.writer, config)
+    }
+
+    pub fn reader(&self) -> &AsyncPacketReader<ReadHalf<T>> {
+        &self.reader
+    }
+
+    pub fn writer(&self) -> &AsyncPacketWriter<WriteHalf<T>> {
+        &self.writer
+    }
+
+    /// Unwrap and return the underlying stream, discarding any buffered data.
+    pub fn into_inner(self) -> T
+    where
+        T: Unpin,
+    {
+        self.reader.into_reader().unsplit(self.writer.into_writer())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn halves_work_concurrently() {
+        let (client, server) = tokio::io::duplex(32);
+        let mut server = Connection::new(server);
+        let (mut reader, mut writer) = Connection::new(client).split();
+
+        let echo = tokio::spawn(async move {
+            for _ in 0..3 {
+                let packet = server.read_packet().await.unwrap();
+                server.write_packet(&packet).await.unwrap();
+            }
+        });
+        let send = tokio::spawn(async move {
+            for i in 0..3u8 {
+                writer.write_packet(&Packet::Data(vec![i; 40])).await.unwrap();
+            }
+            writer
+        });
+
+        for i in 0..3u8 {
+            assert_eq!(reader.read_packet().await.unwrap(), Packet::Data(vec![i; 40]));
+        }
+        echo.await.unwrap();
+
+        let connection = Connection::from_parts(reader, send.await.unwrap());
+        assert_eq!(connection.writer().frame_sizes().count(), 3);
+        let _stream: tokio::io::DuplexStream = connection.into_inner();
+    }
+}
@@ -0,0 +1,31 @@
+//! Async counterparts of [`PacketReader`](crate::reader::PacketReader) and
+//! [`PacketWriter`](crate::writer::PacketWriter) for Tokio (`tokio` feature).
+//!
+//! Only Tokio's I/O traits are used, not its runtime. With the `futures-io`
+//! feature, async-std and smol streams plug in through `FuturesIo`.
+
+#[cfg(feature = "tokio-util")]
+mod codec;
+#[cfg(feature = "futures-io")]
+mod compat;
+mod connection;
+mod reader;
+#[cfg(feature = "futures")]
+mod sink;
+#[cfg(feature = "futures")]
+mod stream;
+mod udp;
+mod writer;
+
+#[cfg(feature = "tokio-util")]
+pub use codec::{ByteframeCodec, ByteframeCodecError};
+#[cfg(feature = "futures-io")]
+pub use compat::FuturesIo;
+pub use connection::Connection;
+pub use reader::{AsyncPacketReader, ReaderShutdown};
+#[cfg(feature = "futures")]
+pub use sink::PacketSink;
+#[cfg(feature = "futures")]
+pub use stream::{PacketStream, ReadError};
+pub use udp::UdpPacketSocket;
+pub use writer::AsyncPacketWriter;
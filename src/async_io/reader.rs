@@ -0,0 +1,395 @@
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::sync::watch;
+
+use crate::codec::ChecksumMode;
+use crate::framing::FrameDecoder;
+use crate::header::ValidationConfig;
+use crate::packet::Packet;
+use crate::reader::{framing_error, is_transient, FrameTimeout, ReadOpts};
+use crate::state::ConnectionState;
+use crate::stats::{FrameSizeHistogram, ResyncStats};
+
+/// Stops an [`AsyncPacketReader`] from another task.
+///
+/// A `read_packet().await` in progress resolves with an error of kind
+/// `ConnectionAborted`, as does every later read, so a reader task can
+/// wind down on its own instead of being aborted. Packets already decoded
+/// but not yet returned are discarded.
+#[derive(Debug, Clone)]
+pub struct ReaderShutdown(Arc<watch::Sender<bool>>);
+
+impl ReaderShutdown {
+    pub fn shutdown(&self) {
+        self.0.send_replace(true);
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        *self.0.borrow()
+    }
+}
+
+fn shut_down() -> io::Error {
+    io::Error::new(io::ErrorKind::ConnectionAborted, "reader closed by shutdown handle")
+}
+
+/// Wraps a Tokio `AsyncRead` source and provides packet-level reading.
+///
+/// Behaves like [`PacketReader`](crate::reader::PacketReader): framing
+/// errors that lose frame sync and hard I/O errors poison the reader, EOF
+/// closes it.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn run() -> std::io::Result<()> {
+/// use byteframe::async_io::AsyncPacketReader;
+/// use tokio::net::TcpStream;
+///
+/// let stream = TcpStream::connect("127.0.0.1:8080").await?;
+/// let mut reader = AsyncPacketReader::new(stream);
+///
+/// while let Ok(packet) = reader.read_packet().await {
+///     println!("Received: {:?}", packet);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct AsyncPacketReader<R> {
+    reader: R,
+    decoder: FrameDecoder,
+    read_buffer: Vec<u8>,
+    packet_buffer: Vec<Packet>,
+    deferred_error: Option<io::Error>, // Framing error to report once `packet_buffer` drains
+    state: ConnectionState,
+    shutdown: Option<Arc<watch::Sender<bool>>>,
+    frame_deadline: Option<Duration>,
+    frame_started: Option<tokio::time::Instant>, // When the incomplete frame's first bytes arrived
+}
+
+impl<R: AsyncRead + Unpin> AsyncPacketReader<R> {
+    /// Create a new packet reader wrapping the given `AsyncRead` source.
+    pub fn new(reader: R) -> Self {
+        Self::with_capacity(reader, 4096)
+    }
+
+    /// Create a new packet reader with a specific read buffer size.
+    pub fn with_capacity(reader: R, capacity: usize) -> Self {
+        Self {
+            reader,
+            decoder: FrameDecoder::new(),
+            read_buffer: vec![0u8; capacity.max(1)],
+            packet_buffer: Vec::new(),
+            deferred_error: None,
+            state: ConnectionState::Healthy,
+            shutdown: None,
+            frame_deadline: None,
+            frame_started: None,
+        }
+    }
+
+    /// Read one complete packet from the stream.
+    ///
+    /// Cancel safe: if the future is dropped before completing, no bytes are
+    /// lost and the next call picks up where this one left off.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`PacketReader::read_packet`](crate::reader::PacketReader::read_packet),
+    /// plus `ConnectionAborted` once a [`ReaderShutdown`] has been triggered.
+    pub async fn read_packet(&mut self) -> io::Result<Packet> {
+        let Some(shutdown) = &self.shutdown else {
+            return self.read_next().await;
+        };
+        let mut stop = shutdown.subscribe();
+        if *stop.borrow_and_update() {
+            return Err(shut_down());
+        }
+        tokio::select! {
+            result = self.read_next() => result,
+            _ = stop.changed() => {
+                self.state = ConnectionState::Closed;
+                Err(shut_down())
+            }
+        }
+    }
+
+    /// Fail with [`FrameTimeout`] once a frame has been incomplete for longer than `limit`.
+    ///
+    /// Unlike the blocking reader, a pending read is cut off at the deadline
+    /// even if the peer sends nothing more. Measured with Tokio's clock.
+    pub fn set_frame_deadline(&mut self, limit: Duration) {
+        self.frame_deadline = Some(limit);
+    }
+
+    /// A handle that makes pending and future reads fail with `ConnectionAborted`.
+    ///
+    /// Handles from repeated calls control the same reader.
+    pub fn shutdown_handle(&mut self) -> ReaderShutdown {
+        let sender = self.shutdown.get_or_insert_with(|| Arc::new(watch::channel(false).0));
+        ReaderShutdown(Arc::clone(sender))
+    }
+
+    async fn read_next(&mut self) -> io::Result<Packet> {
+        loop {
+            if !self.packet_buffer.is_empty() {
+                return Ok(self.packet_buffer.remove(0));
+            }
+            if let Some(err) = self.deferred_error.take() {
+                return Err(err);
+            }
+
+            match &self.state {
+                ConnectionState::Healthy => {}
+                ConnectionState::Poisoned(reason) => {
+                    return Err(io::Error::new(io::ErrorKind::Other, format!("reader poisoned: {reason}")));
+                }
+                ConnectionState::Closed => {
+                    return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "reader is closed"));
+                }
+            }
+
+            let read = self.reader.read(&mut self.read_buffer);
+            let result = match (self.frame_deadline, self.frame_started) {
+                (Some(limit), Some(started)) => match tokio::time::timeout_at(started + limit, read).await {
+                    Ok(result) => result,
+                    Err(_) => {
+                        let timeout = FrameTimeout { elapsed: started.elapsed(), limit };
+                        self.state = ConnectionState::Poisoned(timeout.to_string());
+                        return Err(timeout.into());
+                    }
+                },
+                _ => read.await,
+            };
+            let bytes_read = match result {
+                Ok(n) => n,
+                Err(err) => {
+                    if !is_transient(&err) {
+                        self.state = ConnectionState::Poisoned(err.to_string());
+                    }
+                    return Err(err);
+                }
+            };
+
+            if bytes_read == 0 {
+                self.state = ConnectionState::Closed;
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "Stream closed before complete packet received",
+                ));
+            }
+
+            let decode_result = self.decoder.decode(&self.read_buffer[..bytes_read]);
+            if let Some((err, desync)) = framing_error(&decode_result.errors) {
+                if desync {
+                    self.state = ConnectionState::Poisoned(err.to_string());
+                }
+                self.deferred_error = Some(err);
+            }
+            if self.frame_deadline.is_some() {
+                // A frame ending here means any partial frame left over is a new one
+                self.frame_started = match self.frame_started {
+                    _ if !self.decoder.is_mid_frame() => None,
+                    Some(started) if decode_result.packets.is_empty() => Some(started),
+                    _ => Some(tokio::time::Instant::now()),
+                };
+            }
+            self.packet_buffer.extend(decode_result.packets);
+        }
+    }
+
+    /// Like [`read_packet`](Self::read_packet), but give up after `timeout`.
+    ///
+    /// On expiry returns an error of kind `TimedOut`. The reader is not
+    /// poisoned and keeps any partially received frame, so the next read
+    /// continues where this one stopped.
+    pub async fn read_packet_timeout(&mut self, timeout: Duration) -> io::Result<Packet> {
+        self.read_packet_with(&ReadOpts::new().timeout(timeout)).await
+    }
+
+    /// [`read_packet`](Self::read_packet) with per-call options.
+    ///
+    /// A [`timeout`](ReadOpts::timeout) covers the whole call, including
+    /// any skipped keepalives. On expiry the reader is left as
+    /// [`read_packet_timeout`](Self::read_packet_timeout) describes.
+    pub async fn read_packet_with(&mut self, opts: &ReadOpts) -> io::Result<Packet> {
+        let skip_keepalive = opts.skip_keepalive;
+        let read = async {
+            loop {
+                let packet = self.read_packet().await?;
+                if !(skip_keepalive && matches!(packet, Packet::Ping | Packet::Pong)) {
+                    return Ok(packet);
+                }
+            }
+        };
+        match opts.timeout {
+            None => read.await,
+            Some(timeout) => match tokio::time::timeout(timeout, read).await {
+                Ok(result) => result,
+                Err(_) => Err(io::Error::new(io::ErrorKind::TimedOut, "no complete packet before the deadline")),
+            },
+        }
+    }
+
+    /// Reject frames whose header breaks `config` before buffering their payload.
+    pub fn set_validation(&mut self, config: ValidationConfig) {
+        self.decoder.set_validation(config);
+    }
+
+    /// Choose whether incoming payload checksums are verified.
+    pub fn set_checksum_mode(&mut self, mode: ChecksumMode) {
+        self.decoder.set_checksum_mode(mode);
+    }
+
+    pub fn checksum_mode(&self) -> ChecksumMode {
+        self.decoder.checksum_mode()
+    }
+
+    /// Sizes of the frames received on this connection.
+    pub fn frame_sizes(&self) -> &FrameSizeHistogram {
+        self.decoder.frame_sizes()
+    }
+
+    /// Corruption and resync counters for this connection.
+    pub fn resync_stats(&self) -> &ResyncStats {
+        self.decoder.resync_stats()
+    }
+
+    /// Current health of the reader.
+    pub fn state(&self) -> &ConnectionState {
+        &self.state
+    }
+
+    /// Access the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Access the underlying reader mutably.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Unwrap and return the underlying reader, discarding any buffered data.
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec;
+    use tokio::io::AsyncWriteExt;
+
+    fn encode(packet: &Packet) -> Vec<u8> {
+        let mut buf = Vec::new();
+        codec::encode(packet, &mut buf).unwrap();
+        buf
+    }
+
+    #[tokio::test]
+    async fn reads_packets_split_across_writes() {
+        let (mut client, server) = tokio::io::duplex(64);
+        let mut reader = AsyncPacketReader::with_capacity(server, 5);
+
+        tokio::spawn(async move {
+            let mut bytes = encode(&Packet::Data(b"hello".to_vec()));
+            bytes.extend_from_slice(&encode(&Packet::Ping));
+            for chunk in bytes.chunks(3) {
+                client.write_all(chunk).await.unwrap();
+            }
+        });
+
+        assert_eq!(reader.read_packet().await.unwrap(), Packet::Data(b"hello".to_vec()));
+        assert_eq!(reader.read_packet().await.unwrap(), Packet::Ping);
+
+        let err = reader.read_packet().await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert!(reader.state().is_closed());
+    }
+
+    #[tokio::test]
+    async fn timeout_keeps_partial_frame() {
+        let (mut client, server) = tokio::io::duplex(64);
+        let mut reader = AsyncPacketReader::new(server);
+        let bytes = encode(&Packet::Data(vec![1, 2, 3, 4]));
+
+        client.write_all(&bytes[..6]).await.unwrap();
+        let err = reader.read_packet_timeout(Duration::from_millis(20)).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert!(reader.state().is_healthy());
+
+        client.write_all(&bytes[6..]).await.unwrap();
+        let packet = reader.read_packet_timeout(Duration::from_secs(5)).await.unwrap();
+        assert_eq!(packet, Packet::Data(vec![1, 2, 3, 4]));
+    }
+
+    #[tokio::test]
+    async fn only_desync_errors_poison_reader() {
+        let mut bytes = encode(&Packet::Data(vec![1, 2, 3]));
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xFF;
+        let mut reader = AsyncPacketReader::new(&bytes[..]);
+        let err = reader.read_packet().await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(reader.state().is_healthy());
+
+        let mut bytes = encode(&Packet::Data(vec![1, 2, 3]));
+        bytes[0] ^= 0xFF;
+        let mut reader = AsyncPacketReader::new(&bytes[..]);
+        let err = reader.read_packet().await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(reader.state().is_poisoned());
+    }
+
+    #[tokio::test]
+    async fn shutdown_ends_pending_read() {
+        let (_client, server) = tokio::io::duplex(64);
+        let mut reader = AsyncPacketReader::new(server);
+        let shutdown = reader.shutdown_handle();
+
+        let task = tokio::spawn(async move {
+            let err = reader.read_packet().await.unwrap_err();
+            (err.kind(), reader.read_packet().await.unwrap_err().kind(), reader.state().is_closed())
+        });
+        tokio::task::yield_now().await;
+        shutdown.shutdown();
+
+        let (first, again, closed) = task.await.unwrap();
+        assert_eq!(first, io::ErrorKind::ConnectionAborted);
+        assert_eq!(again, io::ErrorKind::ConnectionAborted);
+        assert!(closed);
+        assert!(shutdown.is_shutdown());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn frame_deadline_cuts_off_trickled_frame() {
+        let (mut client, server) = tokio::io::duplex(64);
+        let mut reader = AsyncPacketReader::new(server);
+        reader.set_frame_deadline(Duration::from_secs(2));
+        let bytes = encode(&Packet::Data(vec![1, 2, 3, 4]));
+
+        client.write_all(&bytes).await.unwrap();
+        assert_eq!(reader.read_packet().await.unwrap(), Packet::Data(vec![1, 2, 3, 4]));
+
+        let trickle = tokio::spawn(async move {
+            for byte in bytes {
+                client.write_all(&[byte]).await.unwrap();
+                tokio::time::sleep(Duration::from_millis(500)).await;
+            }
+            client
+        });
+        let started = tokio::time::Instant::now();
+        let err = reader.read_packet().await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        let timeout = err.get_ref().and_then(|inner| inner.downcast_ref::<FrameTimeout>()).unwrap();
+        assert_eq!(timeout.limit, Duration::from_secs(2));
+        assert_eq!(started.elapsed(), Duration::from_secs(2));
+        assert!(reader.state().is_poisoned());
+        trickle.abort();
+    }
+}
@@ -0,0 +1,189 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_sink::Sink;
+use tokio::io::AsyncWrite;
+
+use crate::codec::{self, ChecksumMode};
+use crate::packet::Packet;
+use crate::stats::FrameSizeHistogram;
+use crate::writer::codec_to_io_error;
+
+/// A [`Sink`] of packets over any Tokio `AsyncWrite` (`futures` feature).
+///
+/// Sent packets are encoded into an internal buffer; `poll_ready` only
+/// waits for the sink once more than `high_water` bytes are buffered, so
+/// small packets are written together. `poll_flush` drains the buffer and
+/// flushes the writer, and `poll_close` also shuts the writer down.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn run() -> std::io::Result<()> {
+/// use byteframe::async_io::{PacketSink, PacketStream};
+/// use futures::{SinkExt, StreamExt, TryStreamExt};
+/// use tokio::net::TcpStream;
+///
+/// let upstream = TcpStream::connect("127.0.0.1:8080").await?;
+/// let downstream = TcpStream::connect("127.0.0.1:9090").await?;
+///
+/// let mut sink = PacketSink::new(downstream);
+/// sink.send(byteframe::Packet::Ping).await?;
+/// PacketStream::new(upstream).err_into().forward(sink).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct PacketSink<W> {
+    writer: W,
+    buffer: Vec<u8>, // Encoded frames not yet accepted by the writer
+    written: usize,  // Bytes at the front of `buffer` already written
+    high_water: usize,
+    frame_sizes: FrameSizeHistogram,
+    checksum_mode: ChecksumMode,
+}
+
+impl<W: AsyncWrite + Unpin> PacketSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self::with_high_water(writer, 8 * 1024)
+    }
+
+    /// Create a sink that applies backpressure once `high_water` bytes are buffered.
+    pub fn with_high_water(writer: W, high_water: usize) -> Self {
+        Self {
+            writer,
+            buffer: Vec::new(),
+            written: 0,
+            high_water,
+            frame_sizes: FrameSizeHistogram::new(),
+            checksum_mode: ChecksumMode::Enabled,
+        }
+    }
+
+    /// Choose whether outgoing frames carry a payload checksum.
+    pub fn set_checksum_mode(&mut self, mode: ChecksumMode) {
+        self.checksum_mode = mode;
+    }
+
+    /// Sizes of the frames accepted by this sink.
+    pub fn frame_sizes(&self) -> &FrameSizeHistogram {
+        &self.frame_sizes
+    }
+
+    /// Bytes encoded but not yet written.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len() - self.written
+    }
+
+    /// Unwrap and return the underlying writer, discarding any buffered frames.
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+
+    /// Write buffered bytes until the buffer is empty or the writer is not ready.
+    fn poll_write_buffer(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while self.written < self.buffer.len() {
+            match Pin::new(&mut self.writer).poll_write(cx, &self.buffer[self.written..]) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Ok(0)) => {
+                    return Poll::Ready(Err(io::Error::new(io::ErrorKind::WriteZero, "failed to write frame bytes")));
+                }
+                Poll::Ready(Ok(n)) => self.written += n,
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+            }
+        }
+        self.buffer.clear();
+        self.written = 0;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W: AsyncWrite + Unpin> Sink<Packet> for PacketSink<W> {
+    type Error = io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if this.buffered() < this.high_water {
+            return Poll::Ready(Ok(()));
+        }
+        this.poll_write_buffer(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, packet: Packet) -> io::Result<()> {
+        let this = self.get_mut();
+        let start = this.buffer.len();
+        codec::encode_with(&packet, &mut this.buffer, this.checksum_mode).map_err(codec_to_io_error)?;
+        this.frame_sizes.record(this.buffer.len() - start);
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        match this.poll_write_buffer(cx) {
+            Poll::Ready(Ok(())) => Pin::new(&mut this.writer).poll_flush(cx),
+            other => other,
+        }
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.as_mut().poll_flush(cx) {
+            Poll::Ready(Ok(())) => Pin::new(&mut self.get_mut().writer).poll_shutdown(cx),
+            other => other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::async_io::PacketStream;
+    use futures::{SinkExt, StreamExt, TryStreamExt};
+
+    #[tokio::test]
+    async fn sends_and_closes() {
+        let (client, server) = tokio::io::duplex(16);
+        let mut sink = PacketSink::with_high_water(client, 32);
+
+        let send = tokio::spawn(async move {
+            sink.send(Packet::Ping).await.unwrap();
+            sink.feed(Packet::Data(vec![5; 100])).await.unwrap();
+            sink.feed(Packet::Data(b"last".to_vec())).await.unwrap();
+            sink.close().await.unwrap();
+            sink.frame_sizes().count()
+        });
+
+        let received: Vec<_> = PacketStream::new(server).try_collect().await.unwrap();
+        assert_eq!(
+            received,
+            vec![Packet::Ping, Packet::Data(vec![5; 100]), Packet::Data(b"last".to_vec())]
+        );
+        assert_eq!(send.await.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn forwards_between_connections() {
+        let mut upstream = Vec::new();
+        for packet in [Packet::Ping, Packet::Data(b"relay".to_vec()), Packet::Pong] {
+            codec::encode(&packet, &mut upstream).unwrap();
+        }
+
+        let (relay_out, downstream) = tokio::io::duplex(64);
+        let relay = tokio::spawn(async move {
+            PacketStream::new(&upstream[..]).err_into().forward(PacketSink::new(relay_out)).await
+        });
+
+        let received: Vec<_> = PacketStream::new(downstream).collect().await;
+        relay.await.unwrap().unwrap();
+        assert_eq!(received.len(), 3);
+        assert_eq!(*received[1].as_ref().unwrap(), Packet::Data(b"relay".to_vec()));
+    }
+
+    #[tokio::test]
+    async fn oversized_packet_is_rejected() {
+        let mut sink = PacketSink::new(Vec::new());
+        let err = sink.send(Packet::Data(vec![0; 70_000])).await.unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(sink.buffered(), 0);
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::VecDeque;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+use tokio::io::{AsyncRead, ReadBuf};
+
+use crate::framing::{FrameDecoder, FrameError};
+use crate::packet::Packet;
+
+/// A [`Stream`] of packets decoded from any Tokio `AsyncRead` source
+/// (`futures` feature).
+///
+/// The stream ends (`None`) when the source reaches EOF on a frame
+/// boundary. A framing error, an I/O error or EOF in the middle of a frame
+/// is yielded once as `Err`, after which the stream ends.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn run() -> std::io::Result<()> {
+/// use byteframe::async_io::PacketStream;
+/// use byteframe::Packet;
+/// use futures::StreamExt;
+/// use tokio::net::TcpStream;
+///
+/// let stream = TcpStream::connect("127.0.0.1:8080").await?;
+/// PacketStream::new(stream)
+///     .filter_map(|result| async move { result.ok() })
+///     .filter(|packet| std::future::ready(matches!(packet, Packet::Data(_))))
+///     .for_each(|packet| async move { println!("{:?}", packet) })
+///     .await;
+/// # Ok(())
+/// # }
+/// ```
+pub struct PacketStream<R> {
+    reader: R,
+    decoder: FrameDecoder,
+    read_buffer: Vec<u8>,
+    decoded: VecDeque<Packet>,  // Packets decoded but not yet yielded
+    failed: Option<ReadError>,  // Error to yield once `decoded` drains
+    done: bool,                 // No further reads; yield `None` once drained
+}
+
+impl<R: AsyncRead + Unpin> PacketStream<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_decoder(reader, FrameDecoder::new())
+    }
+
+    /// Create a stream around a preconfigured decoder (budget, validation, ...).
+    pub fn with_decoder(reader: R, decoder: FrameDecoder) -> Self {
+        Self {
+            reader,
+            decoder,
+            read_buffer: vec![0u8; 4096],
+            decoded: VecDeque::new(),
+            failed: None,
+            done: false,
+        }
+    }
+
+    /// The underlying decoder, for its frame size and resync statistics.
+    pub fn decoder(&self) -> &FrameDecoder {
+        &self.decoder
+    }
+
+    /// Unwrap and return the underlying reader, discarding any buffered data.
+    pub fn into_reader(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: AsyncRead + Unpin> Stream for PacketStream<R> {
+    type Item = Result<Packet, ReadError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(packet) = this.decoded.pop_front() {
+                return Poll::Ready(Some(Ok(packet)));
+            }
+            if let Some(err) = this.failed.take() {
+                this.done = true;
+                return Poll::Ready(Some(Err(err)));
+            }
+            if this.done {
+                return Poll::Ready(None);
+            }
+
+            let mut buf = ReadBuf::new(&mut this.read_buffer);
+            match Pin::new(&mut this.reader).poll_read(cx, &mut buf) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Err(err)) => this.failed = Some(ReadError::Io(err)),
+                Poll::Ready(Ok(())) if buf.filled().is_empty() => {
+                    this.done = true;
+                    if this.decoder.is_mid_frame() {
+                        this.failed = Some(ReadError::Io(io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            "stream closed in the middle of a frame",
+                        )));
+                    }
+                }
+                Poll::Ready(Ok(())) => {
+                    let result = this.decoder.decode(buf.filled());
+                    this.decoded.extend(result.packets);
+                    this.failed = result.errors.into_iter().next().map(ReadError::Frame);
+                }
+            }
+        }
+    }
+}
+
+/// Errors yielded by [`PacketStream`].
+#[derive(Debug)]
+pub enum ReadError {
+    /// The source failed, or closed in the middle of a frame.
+    Io(io::Error),
+    /// An incoming frame could not be decoded.
+    Frame(FrameError),
+}
+
+impl core::fmt::Display for ReadError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ReadError::Io(err) => write!(f, "i/o error: {err}"),
+            ReadError::Frame(err) => write!(f, "framing error: {err:?}"),
+        }
+    }
+}
+
+impl From<ReadError> for io::Error {
+    /// Lets a [`PacketStream`] be forwarded into a [`PacketSink`](super::PacketSink).
+    fn from(err: ReadError) -> Self {
+        match err {
+            ReadError::Io(err) => err,
+            ReadError::Frame(err) => io::Error::new(io::ErrorKind::InvalidData, format!("framing error: {err:?}")),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReadError::Io(err) => Some(err),
+            ReadError::Frame(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::{self, CodecError};
+    use futures::StreamExt;
+
+    fn encode_all(packets: &[Packet]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for packet in packets {
+            codec::encode(packet, &mut bytes).unwrap();
+        }
+        bytes
+    }
+
+    #[tokio::test]
+    async fn composes_with_stream_combinators() {
+        let bytes = encode_all(&[
+            Packet::Ping,
+            Packet::Data(b"one".to_vec()),
+            Packet::Pong,
+            Packet::Data(b"two".to_vec()),
+        ]);
+
+        let payloads: Vec<Packet> = PacketStream::new(&bytes[..])
+            .filter_map(|result| async move { result.ok() })
+            .filter(|packet| std::future::ready(matches!(packet, Packet::Data(_))))
+            .collect()
+            .await;
+
+        assert_eq!(payloads, vec![Packet::Data(b"one".to_vec()), Packet::Data(b"two".to_vec())]);
+    }
+
+    #[tokio::test]
+    async fn yields_error_once_then_ends() {
+        let mut bytes = encode_all(&[Packet::Ping, Packet::Data(vec![1, 2, 3])]);
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xFF;
+
+        let items: Vec<_> = PacketStream::new(&bytes[..]).collect().await;
+        assert_eq!(items.len(), 2);
+        assert_eq!(*items[0].as_ref().unwrap(), Packet::Ping);
+        assert!(matches!(
+            items[1],
+            Err(ReadError::Frame(FrameError::Codec(CodecError::ChecksumMismatch { .. })))
+        ));
+    }
+
+    #[tokio::test]
+    async fn eof_mid_frame_is_an_error() {
+        let mut bytes = encode_all(&[Packet::Data(b"truncated".to_vec())]);
+        bytes.truncate(bytes.len() - 3);
+
+        let items: Vec<_> = PacketStream::new(&bytes[..]).collect().await;
+        assert!(matches!(&items[..], [Err(ReadError::Io(err))] if err.kind() == io::ErrorKind::UnexpectedEof));
+    }
+}
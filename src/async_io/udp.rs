@@ -0,0 +1,162 @@
+use std::io;
+use std::net::SocketAddr;
+
+use tokio::net::{ToSocketAddrs, UdpSocket};
+
+use crate::codec::{self, ChecksumMode, CodecError};
+use crate::header::{Header, HeaderError, ValidationConfig, MAX_HEADER_LEN};
+use crate::packet::Packet;
+use crate::writer::codec_to_io_error;
+
+/// Largest frame a datagram can carry: a header plus a full `u16` payload.
+const MAX_DATAGRAM: usize = MAX_HEADER_LEN + u16::MAX as usize;
+
+/// A UDP socket that carries exactly one frame per datagram.
+///
+/// There is no stream to resynchronize, so a datagram that is truncated,
+/// has trailing bytes, a bad magic, a bad checksum, or a header that breaks
+/// the socket's [`ValidationConfig`] is rejected with `InvalidData` and the
+/// socket stays usable. Frames larger than the path
+/// MTU are fragmented by IP; IPv4 caps a datagram at 65507 bytes.
+pub struct UdpPacketSocket {
+    socket: UdpSocket,
+    recv_buffer: Vec<u8>,
+    encode_buffer: Vec<u8>,
+    checksum_mode: ChecksumMode,
+    validation: ValidationConfig,
+}
+
+impl UdpPacketSocket {
+    pub async fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        Ok(Self::new(UdpSocket::bind(addr).await?))
+    }
+
+    pub fn new(socket: UdpSocket) -> Self {
+        Self {
+            socket,
+            recv_buffer: vec![0; MAX_DATAGRAM],
+            encode_buffer: Vec::new(),
+            checksum_mode: ChecksumMode::Enabled,
+            validation: ValidationConfig::default(),
+        }
+    }
+
+    /// Set whether checksums are written and verified; both peers must agree.
+    pub fn set_checksum_mode(&mut self, mode: ChecksumMode) {
+        self.checksum_mode = mode;
+    }
+
+    /// Reject datagrams whose header breaks `config`; the default accepts
+    /// known opcodes of [`PROTOCOL_VERSION`](crate::header::PROTOCOL_VERSION).
+    pub fn set_validation(&mut self, config: ValidationConfig) {
+        self.validation = config;
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Encode `packet` and send it to `addr` as a single datagram.
+    pub async fn send_packet(&mut self, packet: &Packet, addr: SocketAddr) -> io::Result<()> {
+        self.encode_buffer.clear();
+        codec::encode_with(packet, &mut self.encode_buffer, self.checksum_mode).map_err(codec_to_io_error)?;
+        let sent = self.socket.send_to(&self.encode_buffer, addr).await?;
+        if sent != self.encode_buffer.len() {
+            return Err(io::Error::new(io::ErrorKind::WriteZero, "datagram truncated on send"));
+        }
+        Ok(())
+    }
+
+    /// Receive one datagram and decode it as a single frame.
+    pub async fn recv_packet(&mut self) -> io::Result<(Packet, SocketAddr)> {
+        let (len, peer) = self.socket.recv_from(&mut self.recv_buffer).await?;
+        let packet = decode_datagram(&self.recv_buffer[..len], self.checksum_mode, &self.validation).map_err(codec_to_io_error)?;
+        Ok((packet, peer))
+    }
+
+    pub fn get_ref(&self) -> &UdpSocket {
+        &self.socket
+    }
+
+    pub fn into_inner(self) -> UdpSocket {
+        self.socket
+    }
+}
+
+fn decode_datagram(datagram: &[u8], mode: ChecksumMode, validation: &ValidationConfig) -> Result<Packet, CodecError> {
+    let header = match Header::from_bytes(datagram) {
+        Err(HeaderError::ShortBuffer(len)) => return Err(CodecError::FrameTooShort(len)),
+        other => other?,
+    };
+    header.validate(validation)?;
+    // The payload must fill the rest of the datagram exactly
+    codec::decode_frame(&header, &datagram[header.wire_len()..], mode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::HEADER_LEN;
+
+    async fn pair() -> (UdpPacketSocket, UdpPacketSocket) {
+        let a = UdpPacketSocket::bind("127.0.0.1:0").await.unwrap();
+        let b = UdpPacketSocket::bind("127.0.0.1:0").await.unwrap();
+        (a, b)
+    }
+
+    #[tokio::test]
+    async fn round_trips_one_packet_per_datagram() {
+        let (mut a, mut b) = pair().await;
+        let to = b.local_addr().unwrap();
+        a.send_packet(&Packet::Ping, to).await.unwrap();
+        a.send_packet(&Packet::Data(vec![7; 1000]), to).await.unwrap();
+
+        assert_eq!(b.recv_packet().await.unwrap(), (Packet::Ping, a.local_addr().unwrap()));
+        assert_eq!(b.recv_packet().await.unwrap().0, Packet::Data(vec![7; 1000]));
+    }
+
+    #[tokio::test]
+    async fn rejects_malformed_datagrams_and_keeps_receiving() {
+        let (a, mut b) = pair().await;
+        let to = b.local_addr().unwrap();
+        let mut frame = Vec::new();
+        codec::encode(&Packet::Data(vec![1, 2, 3]), &mut frame).unwrap();
+
+        let mut corrupted = frame.clone();
+        corrupted[HEADER_LEN] ^= 0xFF;
+        let mut trailing = frame.clone();
+        trailing.push(0);
+        let mut bad_magic = frame.clone();
+        bad_magic[0] = 0;
+
+        for datagram in [&corrupted, &trailing, &bad_magic, &frame[..HEADER_LEN + 1]] {
+            a.get_ref().send_to(datagram, to).await.unwrap();
+            let err = b.recv_packet().await.unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+
+        a.get_ref().send_to(&frame, to).await.unwrap();
+        assert_eq!(b.recv_packet().await.unwrap().0, Packet::Data(vec![1, 2, 3]));
+    }
+
+    #[tokio::test]
+    async fn validates_headers_before_decoding() {
+        let (mut a, mut b) = pair().await;
+        let to = b.local_addr().unwrap();
+        let payload = [1, 2, 3];
+        let mut newer = Header::new(crate::packet::OPCODE_DATA, 3, crate::checksum::fnv1a32(&payload))
+            .with_version(1)
+            .to_bytes()
+            .to_vec();
+        newer.extend_from_slice(&payload);
+
+        a.get_ref().send_to(&newer, to).await.unwrap();
+        assert_eq!(b.recv_packet().await.unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+        b.set_validation(ValidationConfig { max_length: 2, max_version: 1, ..ValidationConfig::default() });
+        a.send_packet(&Packet::Data(payload.to_vec()), to).await.unwrap();
+        assert_eq!(b.recv_packet().await.unwrap_err().kind(), io::ErrorKind::InvalidData);
+        a.send_packet(&Packet::Data(vec![1, 2]), to).await.unwrap();
+        assert_eq!(b.recv_packet().await.unwrap().0, Packet::Data(vec![1, 2]));
+    }
+}
@@ -0,0 +1,266 @@
+use std::io;
+
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::codec::{self, ChecksumMode, FrameFormat};
+use crate::header::Flags;
+use crate::packet::Packet;
+use crate::state::ConnectionState;
+use crate::stats::FrameSizeHistogram;
+use crate::writer::{codec_to_io_error, WriteOpts};
+
+/// Wraps a Tokio `AsyncWrite` sink and provides packet-level writing.
+///
+/// The frame being written is kept inside the writer until the sink has
+/// accepted all of it, so dropping a `write_packet` future part-way never
+/// leaves half a frame on the wire: the next call finishes it first.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn run() -> std::io::Result<()> {
+/// use byteframe::async_io::AsyncPacketWriter;
+/// use byteframe::Packet;
+/// use tokio::net::TcpStream;
+///
+/// let stream = TcpStream::connect("127.0.0.1:8080").await?;
+/// let mut writer = AsyncPacketWriter::new(stream);
+///
+/// writer.write_packet(&Packet::Ping).await?;
+/// writer.flush().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct AsyncPacketWriter<W> {
+    writer: W,
+    pending: Vec<u8>, // Encoded frame the sink has not fully accepted yet
+    written: usize,   // Bytes of `pending` already on the wire
+    state: ConnectionState, // Poisoned when a hard error left a frame half-written
+    frame_sizes: FrameSizeHistogram,
+    checksum_mode: ChecksumMode,
+    format: FrameFormat,
+    header_crc: bool,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncPacketWriter<W> {
+    /// Create a new packet writer wrapping the given `AsyncWrite` sink.
+    pub fn new(writer: W) -> Self {
+        Self::with_capacity(writer, 1024)
+    }
+
+    /// Create a new packet writer with a specific encode buffer capacity.
+    pub fn with_capacity(writer: W, capacity: usize) -> Self {
+        Self {
+            writer,
+            pending: Vec::with_capacity(capacity),
+            written: 0,
+            state: ConnectionState::Healthy,
+            frame_sizes: FrameSizeHistogram::new(),
+            checksum_mode: ChecksumMode::Enabled,
+            format: FrameFormat::V1,
+            header_crc: false,
+        }
+    }
+
+    /// Write a single packet to the stream.
+    ///
+    /// Any frame left unfinished by an earlier cancelled call is completed
+    /// before this packet is encoded.
+    ///
+    /// # Errors
+    ///
+    /// Returns `io::Error` if:
+    /// - The packet payload exceeds the maximum size of the
+    ///   [frame format](Self::set_frame_format) (65535 bytes by default)
+    /// - The underlying write operation fails
+    /// - The writer is poisoned by an earlier partial frame, or closed
+    pub async fn write_packet(&mut self, packet: &Packet) -> io::Result<()> {
+        self.write_packet_with(packet, &WriteOpts::default()).await
+    }
+
+    /// [`write_packet`](Self::write_packet) with per-call options.
+    pub async fn write_packet_with(&mut self, packet: &Packet, opts: &WriteOpts) -> io::Result<()> {
+        self.ensure_usable()?;
+        self.flush_pending().await?;
+
+        codec::encode_format(packet, self.flags(opts), self.format, &mut self.pending, self.checksum_mode)
+            .map_err(codec_to_io_error)?;
+        self.frame_sizes.record(self.pending.len());
+        self.flush_pending().await?;
+        if opts.flush {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Finish writing the frame left by a cancelled or failed `write_packet`.
+    ///
+    /// Completing a partially written frame clears the poisoned state.
+    pub async fn flush_pending(&mut self) -> io::Result<()> {
+        if self.state.is_closed() {
+            return Err(closed_error());
+        }
+        while self.written < self.pending.len() {
+            match self.writer.write(&self.pending[self.written..]).await {
+                Ok(0) => return Err(self.poison(io::Error::new(io::ErrorKind::WriteZero, "failed to write frame bytes"))),
+                Ok(n) => self.written += n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(self.poison(err)),
+            }
+        }
+        self.pending.clear();
+        self.written = 0;
+        if self.state.is_poisoned() {
+            self.state = ConnectionState::Healthy; // Back on a frame boundary
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if a frame is waiting to be (fully) written.
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Flush the pending frame and the underlying writer.
+    pub async fn flush(&mut self) -> io::Result<()> {
+        self.flush_pending().await?;
+        self.writer.flush().await
+    }
+
+    /// Flush everything, shut the sink down and mark the writer closed.
+    pub async fn close(&mut self) -> io::Result<()> {
+        self.ensure_usable()?;
+        self.flush().await?;
+        self.writer.shutdown().await?;
+        self.state = ConnectionState::Closed;
+        Ok(())
+    }
+
+    /// Choose whether outgoing frames carry a payload checksum.
+    pub fn set_checksum_mode(&mut self, mode: ChecksumMode) {
+        self.checksum_mode = mode;
+    }
+
+    pub fn checksum_mode(&self) -> ChecksumMode {
+        self.checksum_mode
+    }
+
+    /// Choose the header layouts this connection sends: [`FrameFormat::V2`]
+    /// allows payloads over 65535 bytes, [`FrameFormat::Varint`] uses LEB128
+    /// lengths throughout. The peer must understand the chosen headers.
+    pub fn set_frame_format(&mut self, format: FrameFormat) {
+        self.format = format;
+    }
+
+    pub fn frame_format(&self) -> FrameFormat {
+        self.format
+    }
+
+    /// End every header with a CRC-8 so the peer rejects a corrupted header
+    /// at once; see [`Flags::HEADER_CRC`]. Headers switch to the flagged
+    /// layout, which only flag-aware peers accept.
+    pub fn set_header_crc(&mut self, enabled: bool) {
+        self.header_crc = enabled;
+    }
+
+    pub fn header_crc(&self) -> bool {
+        self.header_crc
+    }
+
+    fn flags(&self, opts: &WriteOpts) -> Flags {
+        if self.header_crc {
+            opts.flags() | Flags::HEADER_CRC
+        } else {
+            opts.flags()
+        }
+    }
+
+    /// Sizes of the frames sent on this connection.
+    pub fn frame_sizes(&self) -> &FrameSizeHistogram {
+        &self.frame_sizes
+    }
+
+    /// Current health of the writer.
+    pub fn state(&self) -> &ConnectionState {
+        &self.state
+    }
+
+    /// Fail fast if the writer is poisoned or closed.
+    fn ensure_usable(&self) -> io::Result<()> {
+        match &self.state {
+            ConnectionState::Healthy => Ok(()),
+            ConnectionState::Poisoned(reason) => Err(io::Error::new(io::ErrorKind::Other, format!("writer poisoned: {reason}"))),
+            ConnectionState::Closed => Err(closed_error()),
+        }
+    }
+
+    /// Poison the writer if `err` struck in the middle of a frame; a frame
+    /// that never reached the sink is dropped instead.
+    fn poison(&mut self, err: io::Error) -> io::Error {
+        if self.written > 0 {
+            self.state = ConnectionState::Poisoned(err.to_string());
+        } else {
+            self.pending.clear();
+        }
+        err
+    }
+
+    /// Access the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Access the underlying writer mutably.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Unwrap and return the underlying writer.
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+}
+
+fn closed_error() -> io::Error {
+    io::Error::new(io::ErrorKind::BrokenPipe, "writer is closed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::async_io::AsyncPacketReader;
+
+    #[tokio::test]
+    async fn round_trips_through_duplex() {
+        let (client, server) = tokio::io::duplex(16);
+        let mut writer = AsyncPacketWriter::new(client);
+        let mut reader = AsyncPacketReader::new(server);
+
+        let sent = vec![Packet::Ping, Packet::Data(vec![7; 100]), Packet::Data(b"done".to_vec())];
+        let expected = sent.clone();
+        let send = tokio::spawn(async move {
+            for packet in &sent {
+                writer.write_packet(packet).await.unwrap();
+            }
+            writer.close().await.unwrap();
+            writer
+        });
+
+        for packet in expected {
+            assert_eq!(reader.read_packet().await.unwrap(), packet);
+        }
+        let writer = send.await.unwrap();
+        assert!(writer.state().is_closed());
+        assert_eq!(writer.frame_sizes().count(), 3);
+    }
+
+    #[tokio::test]
+    async fn oversized_packet_is_rejected() {
+        let mut writer = AsyncPacketWriter::new(Vec::new());
+        let err = writer.write_packet(&Packet::Data(vec![0; 70_000])).await.unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(writer.get_ref().is_empty());
+        assert!(!writer.has_pending());
+    }
+}
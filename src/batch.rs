@@ -0,0 +1,187 @@
+//! Encoder-side coalescing of small packets into `Batch` super-frames.
+//!
+//! A `Batch` frame carries several packets behind one 9-byte header and one
+//! checksum, each entry costing only [`BATCH_ENTRY_OVERHEAD`] bytes. The
+//! [`FrameDecoder`](crate::framing::FrameDecoder) unpacks batches back into
+//! individual packets, so receivers never see them.
+
+use std::time::{Duration, Instant};
+
+use crate::clock::{Clock, SystemClock};
+use crate::codec::{BATCH_ENTRY_OVERHEAD, HELLO_LEN};
+use crate::packet::Packet;
+
+/// When a [`Batcher`] releases the packets it has collected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchConfig {
+    /// Flush once the batch payload would exceed this many bytes.
+    pub max_bytes: usize,
+    /// Flush once this many packets are collected.
+    pub max_packets: usize,
+    /// Flush once the oldest collected packet has waited this long.
+    pub max_delay: Duration,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            max_bytes: 1400, // Fits a typical Ethernet MTU with the outer header
+            max_packets: 64,
+            max_delay: Duration::from_millis(5),
+        }
+    }
+}
+
+/// Collects packets and emits them as `Batch` frames on size, count or time thresholds.
+///
+/// ```
+/// use byteframe::batch::{BatchConfig, Batcher};
+/// use byteframe::Packet;
+///
+/// let mut batcher = Batcher::new(BatchConfig { max_packets: 2, ..BatchConfig::default() });
+/// assert!(batcher.push(Packet::Ping).is_none());
+/// let ready = batcher.push(Packet::Pong).unwrap();
+/// assert_eq!(ready, Packet::Batch(vec![Packet::Ping, Packet::Pong]));
+/// ```
+pub struct Batcher {
+    config: BatchConfig,
+    clock: Box<dyn Clock>,
+    pending: Vec<Packet>,
+    pending_bytes: usize,
+    oldest: Option<Instant>,
+}
+
+impl Batcher {
+    pub fn new(config: BatchConfig) -> Self {
+        Self::with_clock(config, SystemClock)
+    }
+
+    /// Create a batcher that measures `max_delay` against `clock`.
+    pub fn with_clock(config: BatchConfig, clock: impl Clock + 'static) -> Self {
+        Self {
+            config,
+            clock: Box::new(clock),
+            pending: Vec::new(),
+            pending_bytes: 0,
+            oldest: None,
+        }
+    }
+
+    /// Add `packet`, returning a frame to send if a threshold was reached.
+    ///
+    /// If `packet` would push the batch over `max_bytes`, the packets
+    /// collected so far are returned and `packet` starts the next batch.
+    /// Packets too large to share a batch, and nested batches, are passed
+    /// through on their own.
+    pub fn push(&mut self, packet: Packet) -> Option<Packet> {
+        let cost = entry_len(&packet);
+        if cost > self.config.max_bytes || matches!(packet, Packet::Batch(_)) {
+            return match self.flush() {
+                Some(ready) => {
+                    self.start(packet, cost); // keep order: hand back the batch, queue the big one
+                    Some(ready)
+                }
+                None => Some(packet),
+            };
+        }
+
+        let mut ready = None;
+        if self.pending_bytes + cost > self.config.max_bytes {
+            ready = self.flush();
+        }
+        self.start(packet, cost);
+        if ready.is_none() && self.pending.len() >= self.config.max_packets.max(1) {
+            ready = self.flush();
+        }
+        ready
+    }
+
+    /// Return the collected packets if the oldest has waited at least `max_delay`.
+    pub fn poll(&mut self) -> Option<Packet> {
+        let oldest = self.oldest?;
+        if self.clock.now() - oldest >= self.config.max_delay {
+            return self.flush();
+        }
+        None
+    }
+
+    /// Release whatever is collected: nothing, a single packet, or a `Batch`.
+    pub fn flush(&mut self) -> Option<Packet> {
+        self.pending_bytes = 0;
+        self.oldest = None;
+        match self.pending.len() {
+            0 => None,
+            1 => self.pending.pop(),
+            _ => Some(Packet::Batch(std::mem::take(&mut self.pending))),
+        }
+    }
+
+    /// Number of packets waiting to be released.
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    fn start(&mut self, packet: Packet, cost: usize) {
+        self.oldest.get_or_insert_with(|| self.clock.now());
+        self.pending_bytes += cost;
+        self.pending.push(packet);
+    }
+}
+
+fn entry_len(packet: &Packet) -> usize {
+    BATCH_ENTRY_OVERHEAD
+        + match packet {
+            Packet::Ping | Packet::Pong => 0,
+            #[cfg(feature = "text")]
+            Packet::Message(text) => text.len(),
+            Packet::Data(bytes) => bytes.len(),
+            Packet::Batch(packets) => packets.iter().map(entry_len).sum(),
+            Packet::Hello { .. } => HELLO_LEN,
+        }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+
+    #[test]
+    fn flushes_on_byte_threshold_preserving_order() {
+        let config = BatchConfig { max_bytes: 10, ..BatchConfig::default() };
+        let mut batcher = Batcher::new(config);
+
+        assert!(batcher.push(Packet::Data(vec![1; 4])).is_none());
+        let ready = batcher.push(Packet::Data(vec![2; 4])).unwrap();
+        assert_eq!(ready, Packet::Data(vec![1; 4]));
+        assert_eq!(batcher.len(), 1);
+    }
+
+    #[test]
+    fn flushes_after_max_delay() {
+        let clock = MockClock::new();
+        let config = BatchConfig { max_delay: Duration::from_millis(5), ..BatchConfig::default() };
+        let mut batcher = Batcher::with_clock(config, clock.clone());
+
+        batcher.push(Packet::Ping);
+        batcher.push(Packet::Pong);
+        assert!(batcher.poll().is_none());
+
+        clock.advance(Duration::from_millis(5));
+        assert_eq!(batcher.poll(), Some(Packet::Batch(vec![Packet::Ping, Packet::Pong])));
+        assert!(batcher.is_empty());
+    }
+
+    #[test]
+    fn oversized_packet_passes_through() {
+        let config = BatchConfig { max_bytes: 8, ..BatchConfig::default() };
+        let mut batcher = Batcher::new(config);
+
+        let big = Packet::Data(vec![0; 32]);
+        assert_eq!(batcher.push(big.clone()), Some(big));
+        assert!(batcher.is_empty());
+    }
+}
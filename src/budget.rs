@@ -0,0 +1,77 @@
+//! Process-wide accounting of payload memory held by decoders.
+
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// Shared cap on the payload bytes all registered decoders may buffer at once.
+///
+/// Cloning the handle shares the same counter, so one budget can be handed
+/// to every [`FrameDecoder`](crate::framing::FrameDecoder) or
+/// [`PacketReader`](crate::reader::PacketReader) in a process. A decoder
+/// reserves a frame's declared payload length when its header arrives and
+/// releases it once the frame completes or the decoder is dropped.
+#[derive(Debug, Clone)]
+pub struct MemoryBudget {
+    inner: Arc<BudgetInner>,
+}
+
+#[derive(Debug)]
+struct BudgetInner {
+    limit: usize,
+    used: AtomicUsize,
+}
+
+impl MemoryBudget {
+    /// Create a budget allowing at most `limit` bytes to be buffered in total.
+    pub fn new(limit: usize) -> Self {
+        Self {
+            inner: Arc::new(BudgetInner {
+                limit,
+                used: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.inner.limit
+    }
+
+    /// Bytes currently reserved by all holders of this budget.
+    pub fn used(&self) -> usize {
+        self.inner.used.load(Ordering::Acquire)
+    }
+
+    /// Reserve `bytes`, returning `false` without reserving if that would exceed the limit.
+    pub fn try_reserve(&self, bytes: usize) -> bool {
+        self.inner
+            .used
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |used| {
+                used.checked_add(bytes).filter(|&total| total <= self.inner.limit)
+            })
+            .is_ok()
+    }
+
+    /// Return `bytes` previously obtained from [`try_reserve`](Self::try_reserve).
+    pub fn release(&self, bytes: usize) {
+        self.inner.used.fetch_sub(bytes, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enforces_shared_limit() {
+        let budget = MemoryBudget::new(100);
+        let other = budget.clone();
+
+        assert!(budget.try_reserve(60));
+        assert!(!other.try_reserve(41));
+        assert!(other.try_reserve(40));
+        assert_eq!(budget.used(), 100);
+
+        budget.release(60);
+        assert_eq!(other.used(), 40);
+    }
+}
@@ -1,20 +1,41 @@
-//! Pure Rust implementation of the 32-bit FNV-1a checksum.
+//! Pure Rust implementations of the 32-bit FNV-1a payload checksum and the
+//! CRC-8 guarding headers.
 
 pub const FNV_OFFSET_BASIS: u32 = 0x811C9DC5;
 pub const FNV_PRIME: u32 = 0x01000193;
 
-pub fn fnv1a32(data: &[u8]) -> u32 {
+/// Usable in `const` items, e.g. to checksum a payload known at compile time.
+pub const fn fnv1a32(data: &[u8]) -> u32 {
     let mut hash = FNV_OFFSET_BASIS;
-    for &byte in data {
-        hash ^= byte as u32;
+    let mut i = 0;
+    while i < data.len() { // `for` is not allowed in a const fn
+        hash ^= data[i] as u32;
         hash = hash.wrapping_mul(FNV_PRIME);
+        i += 1;
     }
     hash
 }
 
+/// CRC-8 with polynomial 0x07 and no reflection (CRC-8/SMBUS), used for
+/// [`Flags::HEADER_CRC`](crate::header::Flags::HEADER_CRC).
+pub const fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    let mut i = 0;
+    while i < data.len() {
+        crc ^= data[i];
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x80 != 0 { crc << 1 ^ 0x07 } else { crc << 1 };
+            bit += 1;
+        }
+        i += 1;
+    }
+    crc
+}
+
 #[cfg(test)]
 mod tests {
-    use super::fnv1a32;
+    use super::{crc8, fnv1a32};
 
     #[test]
     fn matches_known_vectors() {
@@ -22,4 +43,10 @@ mod tests {
         assert_eq!(fnv1a32(b"a"), 0xE40C292C);
         assert_eq!(fnv1a32(b"hello"), 0x4F9F2CAB);
     }
+
+    #[test]
+    fn crc8_matches_check_value() {
+        assert_eq!(crc8(b""), 0);
+        assert_eq!(crc8(b"123456789"), 0xF4);
+    }
 }
@@ -0,0 +1,442 @@
+//! Async client that survives dropped connections (`tokio` feature).
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::async_io::Connection;
+use crate::events::{CloseCode, ConnectionEvents, NoEvents};
+use crate::packet::Packet;
+use crate::reader::is_transient;
+
+type ConnectFuture<S> = Pin<Box<dyn Future<Output = io::Result<S>> + Send>>;
+type Connector<S> = Box<dyn Fn() -> ConnectFuture<S> + Send + Sync>;
+type PeerOf<S> = fn(&S) -> Option<SocketAddr>;
+
+/// Backoff and replay settings for [`ReconnectingClient`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReconnectConfig {
+    /// Delay before the first retry.
+    pub initial_backoff: Duration,
+    /// Upper bound for the delay between retries.
+    pub max_backoff: Duration,
+    /// Factor applied to the delay after each failed attempt.
+    pub multiplier: u32,
+    /// Give up after this many consecutive failed attempts (`None` = retry
+    /// forever). A connection that drops before a packet got through counts
+    /// as a failed attempt.
+    pub max_attempts: Option<u32>,
+    /// Resend unacknowledged packets after reconnecting.
+    pub replay: bool,
+    /// Most packets kept for replay; older ones are dropped.
+    pub max_replay: usize,
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2,
+            max_attempts: None,
+            replay: false,
+            max_replay: 1024,
+        }
+    }
+}
+
+/// A packet connection that reconnects with exponential backoff when the
+/// stream fails or the peer closes it.
+///
+/// byteframe has no acknowledgements of its own, so "unacknowledged" means
+/// every packet written since the last [`acknowledge`](Self::acknowledge)
+/// call; call it when your application protocol confirms receipt. With
+/// [`ReconnectConfig::replay`] those packets are resent, in order, on every
+/// new connection.
+///
+/// The backoff carries over between reconnects until a packet is read or
+/// written on the new connection, so a peer that accepts and at once hangs
+/// up is retried with growing delays, and counts toward
+/// [`max_attempts`](ReconnectConfig::max_attempts), like one that refuses.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn run() -> std::io::Result<()> {
+/// use byteframe::client::{ReconnectConfig, ReconnectingClient};
+/// use byteframe::Packet;
+///
+/// let mut client = ReconnectingClient::tcp("127.0.0.1:8080", ReconnectConfig::default()).await?;
+/// client.write_packet(&Packet::Ping).await?;
+/// let reply = client.read_packet().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ReconnectingClient<S> {
+    connector: Connector<S>,
+    config: ReconnectConfig,
+    connection: Connection<S>,
+    unacknowledged: VecDeque<Packet>,
+    reconnects: u64,
+    events: Arc<dyn ConnectionEvents>,
+    peer_of: PeerOf<S>,
+    peer: Option<SocketAddr>,
+    delay: Duration, // Sleep before the next connect attempt
+    failures: u32,   // Consecutive attempts that did not yield a working connection
+    healthy: bool,   // A packet got through on the current connection
+}
+
+impl ReconnectingClient<tokio::net::TcpStream> {
+    /// Connect to `addr` over TCP (with `TCP_NODELAY`), reconnecting to the same address.
+    pub async fn tcp(addr: impl Into<String>, config: ReconnectConfig) -> io::Result<Self> {
+        Self::tcp_with_events(addr, config, NoEvents).await
+    }
+
+    /// Like [`tcp`](Self::tcp), reporting the lifecycle of every connection to `events`.
+    pub async fn tcp_with_events(
+        addr: impl Into<String>,
+        config: ReconnectConfig,
+        events: impl ConnectionEvents,
+    ) -> io::Result<Self> {
+        let addr = addr.into();
+        let connector: Connector<tokio::net::TcpStream> = Box::new(move || {
+            let addr = addr.clone();
+            Box::pin(async move {
+                let stream = tokio::net::TcpStream::connect(addr).await?;
+                stream.set_nodelay(true)?;
+                Ok(stream)
+            })
+        });
+        Self::start(connector, config, Arc::new(events), |stream| stream.peer_addr().ok()).await
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin + Send + 'static> ReconnectingClient<S> {
+    /// Establish the first connection with `connect`, retrying per `config`.
+    pub async fn connect<F, Fut>(connect: F, config: ReconnectConfig) -> io::Result<Self>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = io::Result<S>> + Send + 'static,
+    {
+        Self::connect_with_events(connect, config, NoEvents).await
+    }
+
+    /// Like [`connect`](Self::connect), reporting the lifecycle of every
+    /// connection to `events`. The peer address is reported as `None`.
+    pub async fn connect_with_events<F, Fut>(connect: F, config: ReconnectConfig, events: impl ConnectionEvents) -> io::Result<Self>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = io::Result<S>> + Send + 'static,
+    {
+        let connector: Connector<S> = Box::new(move || Box::pin(connect()));
+        Self::start(connector, config, Arc::new(events), |_| None).await
+    }
+
+    async fn start(
+        connector: Connector<S>,
+        config: ReconnectConfig,
+        events: Arc<dyn ConnectionEvents>,
+        peer_of: PeerOf<S>,
+    ) -> io::Result<Self> {
+        let mut failures = 0;
+        let mut delay = config.initial_backoff;
+        let stream = establish(&connector, &config, &mut failures, &mut delay)
+            .await
+            .map_err(|err| gave_up(&*events, None, err))?;
+        let peer = peer_of(&stream);
+        events.on_connect(peer);
+        events.on_handshake_complete(peer);
+        Ok(Self {
+            connector,
+            config,
+            connection: Connection::new(stream),
+            unacknowledged: VecDeque::new(),
+            reconnects: 0,
+            events,
+            peer_of,
+            peer,
+            delay,
+            failures,
+            healthy: false,
+        })
+    }
+
+    /// Read the next packet, reconnecting (and replaying) if the connection drops.
+    ///
+    /// `TimedOut`, `WouldBlock` and `Interrupted` errors are returned as is.
+    pub async fn read_packet(&mut self) -> io::Result<Packet> {
+        loop {
+            match self.connection.read_packet().await {
+                Ok(packet) => {
+                    self.mark_healthy();
+                    return Ok(packet);
+                }
+                Err(err) if is_transient(&err) => return Err(err),
+                Err(err) => {
+                    self.events.on_error(self.peer, &err);
+                    self.reconnect(err).await?
+                }
+            }
+        }
+    }
+
+    /// Write a packet, reconnecting and retrying if the connection is broken.
+    ///
+    /// Oversized packets fail with `InvalidInput` without reconnecting.
+    pub async fn write_packet(&mut self, packet: &Packet) -> io::Result<()> {
+        if self.config.replay {
+            if self.unacknowledged.len() == self.config.max_replay.max(1) {
+                self.unacknowledged.pop_front();
+            }
+            self.unacknowledged.push_back(packet.clone());
+        }
+        loop {
+            match self.connection.write_packet(packet).await {
+                Ok(()) => {
+                    self.mark_healthy();
+                    return Ok(());
+                }
+                Err(err) if err.kind() == io::ErrorKind::InvalidInput => {
+                    if self.config.replay {
+                        self.unacknowledged.pop_back();
+                    }
+                    return Err(err);
+                }
+                Err(err) => {
+                    self.events.on_error(self.peer, &err);
+                    if self.config.replay {
+                        return self.reconnect(err).await; // Replay resends `packet`
+                    }
+                    self.reconnect(err).await?
+                }
+            }
+        }
+    }
+
+    /// Mark every packet written so far as received by the peer.
+    pub fn acknowledge(&mut self) {
+        self.unacknowledged.clear();
+    }
+
+    /// Packets that would be replayed on the next reconnect.
+    pub fn unacknowledged(&self) -> usize {
+        self.unacknowledged.len()
+    }
+
+    /// Number of times the connection was re-established.
+    pub fn reconnects(&self) -> u64 {
+        self.reconnects
+    }
+
+    /// The current connection carries traffic, so the next outage starts
+    /// its backoff afresh.
+    fn mark_healthy(&mut self) {
+        self.healthy = true;
+        self.failures = 0;
+        self.delay = self.config.initial_backoff;
+    }
+
+    /// Drop the current connection after it failed with `err`, connect
+    /// again and replay if configured.
+    async fn reconnect(&mut self, mut err: io::Error) -> io::Result<()> {
+        loop {
+            if !std::mem::take(&mut self.healthy) {
+                self.failures += 1; // Dropped before any packet got through
+                if self.config.max_attempts.is_some_and(|max| self.failures >= max) {
+                    return Err(gave_up(&*self.events, self.peer, err));
+                }
+            }
+            let stream = establish(&self.connector, &self.config, &mut self.failures, &mut self.delay)
+                .await
+                .map_err(|err| gave_up(&*self.events, self.peer, err))?;
+            self.peer = (self.peer_of)(&stream);
+            self.events.on_connect(self.peer);
+            self.connection = Connection::new(stream);
+            self.reconnects += 1;
+            match self.replay().await {
+                Ok(()) => {
+                    self.events.on_handshake_complete(self.peer);
+                    return Ok(());
+                }
+                Err(replay_err) => {
+                    self.events.on_error(self.peer, &replay_err);
+                    err = replay_err;
+                }
+            }
+        }
+    }
+
+    async fn replay(&mut self) -> io::Result<()> {
+        if !self.config.replay {
+            return Ok(());
+        }
+        for packet in &self.unacknowledged {
+            self.connection.write_packet(packet).await?;
+        }
+        self.connection.flush().await
+    }
+}
+
+impl<S> Drop for ReconnectingClient<S> {
+    fn drop(&mut self) {
+        self.events.on_close(self.peer, CloseCode::Shutdown, "client dropped");
+    }
+}
+
+fn gave_up(events: &dyn ConnectionEvents, peer: Option<SocketAddr>, err: io::Error) -> io::Error {
+    events.on_close(peer, CloseCode::GaveUp, &err.to_string());
+    err
+}
+
+/// Connect with exponential backoff until success or `max_attempts` failures.
+///
+/// `failures` and `delay` carry the backoff of earlier attempts; any
+/// failure so far means sleeping before the next connect.
+async fn establish<S>(
+    connector: &Connector<S>,
+    config: &ReconnectConfig,
+    failures: &mut u32,
+    delay: &mut Duration,
+) -> io::Result<S> {
+    loop {
+        if *failures > 0 {
+            tokio::time::sleep(*delay).await;
+            *delay = next_backoff(*delay, config);
+        }
+        match connector().await {
+            Ok(stream) => return Ok(stream),
+            Err(err) => {
+                *failures += 1;
+                if config.max_attempts.is_some_and(|max| *failures >= max) {
+                    return Err(err);
+                }
+            }
+        }
+    }
+}
+
+fn next_backoff(delay: Duration, config: &ReconnectConfig) -> Duration {
+    delay.saturating_mul(config.multiplier.max(1)).min(config.max_backoff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::async_io::{AsyncPacketReader, AsyncPacketWriter};
+    use crate::events::tests::Recorder;
+    use tokio::net::TcpListener;
+
+    fn fast_retries() -> ReconnectConfig {
+        ReconnectConfig {
+            initial_backoff: Duration::from_millis(5),
+            max_backoff: Duration::from_millis(20),
+            ..ReconnectConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn reconnects_and_replays_after_drop() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            // First connection: take one packet, then hang up without answering
+            let (stream, _) = listener.accept().await.unwrap();
+            AsyncPacketReader::new(stream).read_packet().await.unwrap();
+
+            // Second connection: echo everything
+            let (stream, _) = listener.accept().await.unwrap();
+            let (read_half, write_half) = stream.into_split();
+            let mut reader = AsyncPacketReader::new(read_half);
+            let mut writer = AsyncPacketWriter::new(write_half);
+            while let Ok(packet) = reader.read_packet().await {
+                writer.write_packet(&packet).await.unwrap();
+            }
+        });
+
+        let config = ReconnectConfig { replay: true, ..fast_retries() };
+        let mut client = ReconnectingClient::tcp(addr.to_string(), config).await.unwrap();
+        client.write_packet(&Packet::Data(vec![1])).await.unwrap();
+
+        assert_eq!(client.read_packet().await.unwrap(), Packet::Data(vec![1]));
+        assert_eq!(client.reconnects(), 1);
+        assert_eq!(client.unacknowledged(), 1);
+        client.acknowledge();
+        assert_eq!(client.unacknowledged(), 0);
+    }
+
+    #[tokio::test]
+    async fn reports_reconnects_as_events() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            drop(listener.accept().await.unwrap()); // Hang up immediately
+            let (stream, _) = listener.accept().await.unwrap();
+            AsyncPacketWriter::new(stream).write_packet(&Packet::Pong).await.unwrap();
+        });
+
+        let recorder = Arc::new(Recorder::default());
+        let mut client = ReconnectingClient::tcp_with_events(addr.to_string(), fast_retries(), Arc::clone(&recorder))
+            .await
+            .unwrap();
+        assert_eq!(client.read_packet().await.unwrap(), Packet::Pong);
+        drop(client);
+
+        assert_eq!(
+            recorder.events(),
+            ["connect", "ready", "error UnexpectedEof", "connect", "ready", "close Shutdown"]
+        );
+    }
+
+    #[tokio::test]
+    async fn backs_off_from_a_peer_that_hangs_up_at_once() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                drop(listener.accept().await.unwrap()); // Accept, then hang up
+            }
+        });
+
+        let config = ReconnectConfig { max_attempts: Some(4), ..fast_retries() };
+        let recorder = Arc::new(Recorder::default());
+        let mut client = ReconnectingClient::tcp_with_events(addr.to_string(), config, Arc::clone(&recorder))
+            .await
+            .unwrap();
+        let started = std::time::Instant::now();
+        assert!(client.read_packet().await.is_err());
+        assert!(started.elapsed() >= Duration::from_millis(5 + 10 + 20));
+        assert_eq!(client.reconnects(), 3);
+        assert_eq!(recorder.events().last().unwrap(), "close GaveUp");
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        drop(listener);
+
+        let config = ReconnectConfig { max_attempts: Some(3), ..fast_retries() };
+        let recorder = Arc::new(Recorder::default());
+        let err = ReconnectingClient::tcp_with_events(addr, config, Arc::clone(&recorder)).await.err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::ConnectionRefused);
+        assert_eq!(recorder.events(), ["close GaveUp"]);
+    }
+
+    #[test]
+    fn backoff_grows_to_cap() {
+        let config = fast_retries();
+        let mut delay = config.initial_backoff;
+        let mut seen = Vec::new();
+        for _ in 0..4 {
+            seen.push(delay);
+            delay = next_backoff(delay, &config);
+        }
+        assert_eq!(seen, [5, 10, 20, 20].map(Duration::from_millis));
+    }
+}
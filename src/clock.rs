@@ -0,0 +1,94 @@
+//! Time source abstraction so time-dependent code can be tested without sleeps.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Source of the current instant.
+///
+/// The blocking layers take a `Clock` wherever they measure elapsed time
+/// (frame deadlines, read timeouts, time-budgeted decoding, batching,
+/// scheduling traces, scenarios and soak runs), defaulting to
+/// [`SystemClock`]. Tests substitute a [`MockClock`] and move time forward
+/// explicitly.
+///
+/// The tokio layers (keepalive, the server's accept throttle, async frame
+/// deadlines and timeouts) do not: they sleep on tokio timers, which a
+/// `Clock` cannot drive, and read `tokio::time::Instant` instead. Test them
+/// with `#[tokio::test(start_paused = true)]`, which pauses and
+/// auto-advances tokio's clock.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+/// The real monotonic clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Manually driven clock for deterministic tests.
+///
+/// Time only moves when [`advance`](Self::advance) is called. Clones share
+/// the same time, so a test can keep one handle and give another to the
+/// code under test.
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    start: Instant,
+    elapsed: Arc<Mutex<Duration>>,
+}
+
+impl MockClock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            elapsed: Arc::new(Mutex::new(Duration::ZERO)),
+        }
+    }
+
+    /// Move time forward by `by`.
+    pub fn advance(&self, by: Duration) {
+        *self.lock() += by;
+    }
+
+    /// Total time advanced since the clock was created.
+    pub fn elapsed(&self) -> Duration {
+        *self.lock()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Duration> {
+        self.elapsed.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mock_clock_moves_only_when_advanced() {
+        let clock = MockClock::new();
+        let handle = clock.clone();
+        let before = clock.now();
+        assert_eq!(clock.now(), before);
+
+        handle.advance(Duration::from_secs(5));
+        assert_eq!(clock.now() - before, Duration::from_secs(5));
+        assert_eq!(clock.elapsed(), Duration::from_secs(5));
+    }
+}
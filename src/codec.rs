@@ -1,20 +1,66 @@
 //! Encoding and decoding helpers for wire packets.
 
-use std::borrow::Cow;
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+#[cfg(feature = "text")]
+use alloc::string::String;
 
 use crate::checksum::fnv1a32;
-use crate::header::{Header, HeaderError, HEADER_LEN};
-use crate::packet::{Packet, OPCODE_DATA, OPCODE_MESSAGE, OPCODE_PING, OPCODE_PONG};
+use crate::header::{frame_overhead, Flags, Header, HeaderError, HEADER_LEN};
+#[cfg(feature = "text")]
+use crate::packet::OPCODE_MESSAGE;
+use crate::packet::{BatchRef, Packet, PacketRef, OPCODE_BATCH, OPCODE_DATA, OPCODE_HELLO, OPCODE_PING, OPCODE_PONG};
+
+/// Bytes of framing per entry inside a `Batch` payload (opcode + u16 length).
+pub const BATCH_ENTRY_OVERHEAD: usize = 3;
+/// Bytes of a `Hello` payload: version (u8) then features (u32 BE).
+pub const HELLO_LEN: usize = 5;
 
 #[derive(Debug)]
 pub enum CodecError {
     Header(HeaderError),
     FrameTooShort(usize),
     PayloadTooLarge(usize),
-    PayloadLengthMismatch { declared: u16, actual: usize },
+    PayloadLengthMismatch { declared: u32, actual: usize },
     InvalidOpcode(u8),
-    InvalidUtf8(std::string::FromUtf8Error),
+    #[cfg(feature = "text")]
+    InvalidUtf8(alloc::string::FromUtf8Error),
     ChecksumMismatch { expected: u32, actual: u32 },
+    /// A `Batch` payload was truncated or contained another batch.
+    MalformedBatch,
+    /// [`encode_into`] was given a buffer shorter than the frame.
+    BufferTooSmall { needed: usize, available: usize },
+    /// The frame carries flags beyond priority, so its payload is not a
+    /// plain packet; read it with [`decode_flagged`] instead.
+    UnsupportedFlags(Flags),
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for CodecError {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        match self {
+            CodecError::Header(err) => defmt::write!(f, "Header({})", err),
+            CodecError::FrameTooShort(len) => defmt::write!(f, "FrameTooShort({})", len),
+            CodecError::PayloadTooLarge(len) => defmt::write!(f, "PayloadTooLarge({})", len),
+            CodecError::PayloadLengthMismatch { declared, actual } => {
+                defmt::write!(f, "PayloadLengthMismatch {{ declared: {}, actual: {} }}", declared, actual)
+            }
+            CodecError::InvalidOpcode(opcode) => defmt::write!(f, "InvalidOpcode({=u8:#x})", opcode),
+            // `FromUtf8Error` has no defmt impl; the offset is what matters on a probe log
+            #[cfg(feature = "text")]
+            CodecError::InvalidUtf8(err) => {
+                defmt::write!(f, "InvalidUtf8 {{ valid_up_to: {} }}", err.utf8_error().valid_up_to())
+            }
+            CodecError::ChecksumMismatch { expected, actual } => {
+                defmt::write!(f, "ChecksumMismatch {{ expected: {=u32:#x}, actual: {=u32:#x} }}", expected, actual)
+            }
+            CodecError::MalformedBatch => defmt::write!(f, "MalformedBatch"),
+            CodecError::BufferTooSmall { needed, available } => {
+                defmt::write!(f, "BufferTooSmall {{ needed: {}, available: {} }}", needed, available)
+            }
+            CodecError::UnsupportedFlags(flags) => defmt::write!(f, "UnsupportedFlags({})", flags),
+        }
+    }
 }
 
 impl From<HeaderError> for CodecError {
@@ -23,39 +69,278 @@ impl From<HeaderError> for CodecError {
     }
 }
 
+/// Whether frames carry and verify an FNV-1a payload checksum.
+///
+/// `Disabled` is meant for transports that already guarantee integrity
+/// (TLS, QUIC): the encoder writes a zero checksum and the decoder skips
+/// verification. Both peers must agree on the mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ChecksumMode {
+    #[default]
+    Enabled,
+    Disabled,
+}
+
+/// Which length fields the encoder may use.
+///
+/// Decoders accept both without configuration, since each layout has its
+/// own magic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FrameFormat {
+    /// 16-bit lengths only; larger payloads fail with `PayloadTooLarge`.
+    #[default]
+    V1,
+    /// Payloads over 65535 bytes get the v2 header with a 32-bit length.
+    /// Smaller ones are framed exactly as in `V1`, so only peers sent a
+    /// large payload need to understand v2.
+    V2,
+    /// Every frame gets the varint header: one length byte up to 127
+    /// bytes of payload, five at most. Both peers must understand it.
+    Varint,
+}
+
+impl FrameFormat {
+    /// Largest payload this format can frame.
+    pub const fn max_payload(self) -> usize {
+        match self {
+            FrameFormat::V1 => u16::MAX as usize,
+            FrameFormat::V2 | FrameFormat::Varint => u32::MAX as usize,
+        }
+    }
+
+    /// Header this format gives a frame, before flags are applied.
+    pub(crate) const fn header(self, opcode: u8, length: u32, checksum: u32) -> Header {
+        match self {
+            FrameFormat::Varint => Header::new(opcode, length, checksum).with_varint_length(),
+            FrameFormat::V1 | FrameFormat::V2 => Header::new(opcode, length, checksum),
+        }
+    }
+}
+
 pub fn encode(packet: &Packet, buf: &mut Vec<u8>) -> Result<(), CodecError> {
-    let payload = extract_payload(packet);
-    if payload.len() > u16::MAX as usize {
+    encode_with(packet, buf, ChecksumMode::Enabled)
+}
+
+/// Encode `packet`, computing the checksum only if `mode` is `Enabled`.
+pub fn encode_with(packet: &Packet, buf: &mut Vec<u8>, mode: ChecksumMode) -> Result<(), CodecError> {
+    encode_flagged(packet, Flags::empty(), buf, mode)
+}
+
+/// Encode `packet` with `flags` in its header.
+///
+/// Empty flags produce the same bytes as [`encode_with`]; anything else
+/// uses the 10-byte flagged layout, which only flag-aware peers accept.
+/// Transform flags (`COMPRESSED`, `ENCRYPTED`, `FRAGMENTED`) describe the
+/// payload; the caller must already have applied the transform.
+pub fn encode_flagged(packet: &Packet, flags: Flags, buf: &mut Vec<u8>, mode: ChecksumMode) -> Result<(), CodecError> {
+    encode_format(packet, flags, FrameFormat::V1, buf, mode)
+}
+
+/// [`encode_flagged`], allowing the length fields of `format`.
+pub fn encode_format(
+    packet: &Packet,
+    flags: Flags,
+    format: FrameFormat,
+    buf: &mut Vec<u8>,
+    mode: ChecksumMode,
+) -> Result<(), CodecError> {
+    let payload = extract_payload(packet)?;
+    if payload.len() > format.max_payload() {
         return Err(CodecError::PayloadTooLarge(payload.len()));
     }
 
-    let length = payload.len() as u16;
-    let checksum = fnv1a32(&payload);
-    let header = Header::new(packet.opcode(), length, checksum);
-
-    buf.extend_from_slice(&header.to_bytes());
+    let length = payload.len() as u32;
+    let checksum = match mode {
+        ChecksumMode::Enabled => fnv1a32(&payload),
+        ChecksumMode::Disabled => 0,
+    };
+    format.header(packet.opcode(), length, checksum).with_flags(flags).write_to(buf);
     buf.extend_from_slice(&payload);
     Ok(())
 }
 
+/// Bytes [`encode_format`] produces for `packet` with `flags` in `format`:
+/// the header plus its payload.
+pub fn encoded_len(packet: &Packet, format: FrameFormat, flags: Flags) -> usize {
+    let length = payload_len(packet);
+    frame_overhead(format, flags, length) + length
+}
+
+/// Encode `packet` into the front of `buf` without allocating.
+///
+/// Returns the frame length. Meant for DMA and stack buffers; the bytes
+/// match [`encode`].
+///
+/// ```
+/// use byteframe::codec::{encode_into, encoded_len, FrameFormat};
+/// use byteframe::header::Flags;
+/// use byteframe::Packet;
+///
+/// let packet = Packet::Data(vec![1, 2, 3]);
+/// let mut buf = [0u8; 32];
+/// let len = encode_into(&packet, &mut buf).unwrap();
+/// assert_eq!(len, encoded_len(&packet, FrameFormat::V1, Flags::empty()));
+/// assert_eq!(byteframe::decode(&buf[..len]).unwrap(), packet);
+/// ```
+pub fn encode_into(packet: &Packet, buf: &mut [u8]) -> Result<usize, CodecError> {
+    encode_into_with(packet, buf, ChecksumMode::Enabled)
+}
+
+/// [`encode_into`], computing the checksum only if `mode` is `Enabled`.
+pub fn encode_into_with(packet: &Packet, buf: &mut [u8], mode: ChecksumMode) -> Result<usize, CodecError> {
+    let length = payload_len(packet);
+    if length > u16::MAX as usize {
+        return Err(CodecError::PayloadTooLarge(length));
+    }
+    let needed = HEADER_LEN + length;
+    let Some(frame) = buf.get_mut(..needed) else {
+        return Err(CodecError::BufferTooSmall { needed, available: buf.len() });
+    };
+
+    let (header_bytes, payload) = frame.split_at_mut(HEADER_LEN);
+    write_payload(packet, payload)?;
+    let checksum = match mode {
+        ChecksumMode::Enabled => fnv1a32(payload),
+        ChecksumMode::Disabled => 0,
+    };
+    header_bytes.copy_from_slice(&Header::new(packet.opcode(), length as u32, checksum).to_bytes());
+    Ok(needed)
+}
+
+fn payload_len(packet: &Packet) -> usize {
+    match packet {
+        Packet::Ping | Packet::Pong => 0,
+        #[cfg(feature = "text")]
+        Packet::Message(text) => text.len(),
+        Packet::Data(bytes) => bytes.len(),
+        Packet::Batch(packets) => packets.iter().map(|entry| BATCH_ENTRY_OVERHEAD + payload_len(entry)).sum(),
+        Packet::Hello { .. } => HELLO_LEN,
+    }
+}
+
+/// Write the payload of `packet` into `out`, which is exactly `payload_len` long.
+fn write_payload(packet: &Packet, out: &mut [u8]) -> Result<(), CodecError> {
+    match packet {
+        Packet::Ping | Packet::Pong => {}
+        #[cfg(feature = "text")]
+        Packet::Message(text) => out.copy_from_slice(text.as_bytes()),
+        Packet::Data(bytes) => out.copy_from_slice(bytes),
+        Packet::Hello { version, features } => out.copy_from_slice(&hello_payload(*version, *features)),
+        Packet::Batch(packets) => {
+            let mut rest = out;
+            for entry in packets {
+                if matches!(entry, Packet::Batch(_)) {
+                    return Err(CodecError::MalformedBatch);
+                }
+                let len = payload_len(entry);
+                if len > u16::MAX as usize {
+                    return Err(CodecError::PayloadTooLarge(len));
+                }
+                let (head, tail) = core::mem::take(&mut rest).split_at_mut(BATCH_ENTRY_OVERHEAD + len);
+                head[0] = entry.opcode();
+                head[1..BATCH_ENTRY_OVERHEAD].copy_from_slice(&(len as u16).to_be_bytes());
+                write_payload(entry, &mut head[BATCH_ENTRY_OVERHEAD..])?;
+                rest = tail;
+            }
+        }
+    }
+    Ok(())
+}
+
 pub fn decode(bytes: &[u8]) -> Result<Packet, CodecError> {
+    decode_with(bytes, ChecksumMode::Enabled)
+}
+
+/// Decode one frame, verifying its checksum only if `mode` is `Enabled`.
+///
+/// Frames whose flags go beyond priority fail with
+/// [`CodecError::UnsupportedFlags`].
+pub fn decode_with(bytes: &[u8], mode: ChecksumMode) -> Result<Packet, CodecError> {
+    let (header, payload) = split_frame(bytes)?;
+    decode_frame(&header, payload, mode)
+}
+
+/// Decode one frame of any header layout and return its flags alongside.
+///
+/// The packet is built from the payload as it is on the wire, so payloads
+/// under a transform flag should travel as `Data` and be undone by the
+/// caller.
+pub fn decode_flagged(bytes: &[u8], mode: ChecksumMode) -> Result<(Packet, Flags), CodecError> {
+    let (header, payload) = split_frame(bytes)?;
+    Ok((decode_payload(&header, payload, mode)?, header.flags))
+}
+
+/// Parse the header, whatever its layout, and slice out its payload.
+fn split_frame(bytes: &[u8]) -> Result<(Header, &[u8]), CodecError> {
     if bytes.len() < HEADER_LEN {
         return Err(CodecError::FrameTooShort(bytes.len()));
     }
 
-    let header = Header::from_bytes(&bytes[0..HEADER_LEN])?; // Step 1: Parse ONLY the header (first 9 bytes)
-    let payload_len = header.length as usize; // Step 2: Use the header to find where the payload is
-    if bytes.len() < HEADER_LEN + payload_len {
-        return Err(CodecError::FrameTooShort(bytes.len()));
+    let header = Header::from_bytes(bytes).map_err(|err| match err {
+        HeaderError::ShortBuffer(len) => CodecError::FrameTooShort(len), // Longer layout cut short
+        other => CodecError::Header(other),
+    })?;
+    let end = header
+        .wire_len()
+        .checked_add(header.length as usize)
+        .ok_or(CodecError::PayloadTooLarge(header.length as usize))?; // Only on targets with a 32-bit usize
+    let payload = bytes.get(header.wire_len()..end).ok_or(CodecError::FrameTooShort(bytes.len()))?;
+    Ok((header, payload))
+}
+
+/// Decode one frame without copying its payload.
+pub fn decode_ref(bytes: &[u8]) -> Result<PacketRef<'_>, CodecError> {
+    decode_ref_with(bytes, ChecksumMode::Enabled)
+}
+
+/// [`decode_ref`], verifying the checksum only if `mode` is `Enabled`.
+pub fn decode_ref_with(bytes: &[u8], mode: ChecksumMode) -> Result<PacketRef<'_>, CodecError> {
+    let (header, payload) = split_frame(bytes)?;
+    if !header.flags.is_plain() {
+        return Err(CodecError::UnsupportedFlags(header.flags));
     }
-    let payload = &bytes[HEADER_LEN..][..payload_len]; // Step 3: Now extract the payload (bytes after the header)
-    
-    decode_frame(&header, payload)
+    if mode == ChecksumMode::Enabled {
+        let actual = fnv1a32(payload);
+        if actual != header.checksum {
+            return Err(CodecError::ChecksumMismatch { expected: header.checksum, actual });
+        }
+    }
+    packet_ref_from_opcode(header.opcode, payload)
 }
 
-    
+/// Encode a borrowed packet; the bytes match [`encode`] of the owned form.
+pub fn encode_ref(packet: &PacketRef<'_>, buf: &mut Vec<u8>) -> Result<(), CodecError> {
+    let hello;
+    let payload: &[u8] = match *packet {
+        PacketRef::Ping | PacketRef::Pong => &[],
+        PacketRef::Hello { version, features } => {
+            hello = hello_payload(version, features);
+            &hello
+        }
+        #[cfg(feature = "text")]
+        PacketRef::Message(text) => text.as_bytes(),
+        PacketRef::Data(bytes) => bytes,
+        PacketRef::Batch(batch) => batch.payload(),
+    };
+    if payload.len() > u16::MAX as usize {
+        return Err(CodecError::PayloadTooLarge(payload.len()));
+    }
+    let header = Header::new(packet.opcode(), payload.len() as u32, fnv1a32(payload));
+    buf.extend_from_slice(&header.to_bytes());
+    buf.extend_from_slice(payload);
+    Ok(())
+}
 
-pub(crate) fn decode_frame(header: &Header, payload: &[u8]) -> Result<Packet, CodecError> {
+pub(crate) fn decode_frame(header: &Header, payload: &[u8], mode: ChecksumMode) -> Result<Packet, CodecError> {
+    if !header.flags.is_plain() {
+        return Err(CodecError::UnsupportedFlags(header.flags));
+    }
+    decode_payload(header, payload, mode)
+}
+
+fn decode_payload(header: &Header, payload: &[u8], mode: ChecksumMode) -> Result<Packet, CodecError> {
     if payload.len() != header.length as usize {
         return Err(CodecError::PayloadLengthMismatch {
             declared: header.length,
@@ -63,25 +348,114 @@ pub(crate) fn decode_frame(header: &Header, payload: &[u8]) -> Result<Packet, Co
         });
     }
 
-    let actual = fnv1a32(payload);
-    if actual != header.checksum {
-        return Err(CodecError::ChecksumMismatch {
-            expected: header.checksum,
-            actual,
-        });
+    if mode == ChecksumMode::Enabled {
+        let actual = fnv1a32(payload);
+        if actual != header.checksum {
+            return Err(CodecError::ChecksumMismatch {
+                expected: header.checksum,
+                actual,
+            });
+        }
     }
 
     packet_from_opcode(header.opcode, payload)
 }
 
-fn extract_payload(packet: &Packet) -> Cow<'_, [u8]> {
-    match packet {
+fn extract_payload(packet: &Packet) -> Result<Cow<'_, [u8]>, CodecError> {
+    Ok(match packet {
         Packet::Ping | Packet::Pong => Cow::Borrowed(&[]),
+        #[cfg(feature = "text")]
         Packet::Message(text) => Cow::Owned(text.as_bytes().to_vec()),
         Packet::Data(bytes) => Cow::Borrowed(bytes.as_slice()),
+        Packet::Batch(packets) => Cow::Owned(batch_payload(packets)?),
+        Packet::Hello { version, features } => Cow::Owned(hello_payload(*version, *features).to_vec()),
+    })
+}
+
+fn hello_payload(version: u8, features: u32) -> [u8; HELLO_LEN] {
+    let [a, b, c, d] = features.to_be_bytes();
+    [version, a, b, c, d]
+}
+
+fn parse_hello(payload: &[u8]) -> Result<(u8, u32), CodecError> {
+    match *payload {
+        [version, a, b, c, d] => Ok((version, u32::from_be_bytes([a, b, c, d]))),
+        _ => Err(CodecError::PayloadLengthMismatch { declared: HELLO_LEN as u32, actual: payload.len() }),
     }
 }
 
+/// Batch payload: each entry is `opcode (u8) | length (u16 BE) | payload`.
+/// The outer frame's checksum covers every entry.
+fn batch_payload(packets: &[Packet]) -> Result<Vec<u8>, CodecError> {
+    let mut payload = Vec::new();
+    for packet in packets {
+        if matches!(packet, Packet::Batch(_)) {
+            return Err(CodecError::MalformedBatch);
+        }
+        let entry = extract_payload(packet)?;
+        if entry.len() > u16::MAX as usize {
+            return Err(CodecError::PayloadTooLarge(entry.len()));
+        }
+        payload.push(packet.opcode());
+        payload.extend_from_slice(&(entry.len() as u16).to_be_bytes());
+        payload.extend_from_slice(&entry);
+    }
+    Ok(payload)
+}
+
+fn packets_from_batch(mut payload: &[u8]) -> Result<Vec<Packet>, CodecError> {
+    let mut packets = Vec::new();
+    while let Some((entry, rest)) = next_batch_entry(payload)? {
+        packets.push(entry.to_packet());
+        payload = rest;
+    }
+    Ok(packets)
+}
+
+fn packet_ref_from_opcode(opcode: u8, payload: &[u8]) -> Result<PacketRef<'_>, CodecError> {
+    match opcode {
+        OPCODE_PING | OPCODE_PONG if !payload.is_empty() => {
+            Err(CodecError::PayloadLengthMismatch { declared: 0, actual: payload.len() })
+        }
+        OPCODE_PING => Ok(PacketRef::Ping),
+        OPCODE_PONG => Ok(PacketRef::Pong),
+        #[cfg(feature = "text")]
+        OPCODE_MESSAGE => match core::str::from_utf8(payload) {
+            Ok(text) => Ok(PacketRef::Message(text)),
+            // `CodecError` carries the owned error type; only this path allocates
+            Err(_) => Err(CodecError::InvalidUtf8(String::from_utf8(payload.to_vec()).unwrap_err())),
+        },
+        OPCODE_DATA => Ok(PacketRef::Data(payload)),
+        OPCODE_BATCH => {
+            let mut rest = payload;
+            while let Some((_, after)) = next_batch_entry(rest)? {
+                rest = after;
+            }
+            Ok(PacketRef::Batch(BatchRef { payload }))
+        }
+        OPCODE_HELLO => parse_hello(payload).map(|(version, features)| PacketRef::Hello { version, features }),
+        other => Err(CodecError::InvalidOpcode(other)),
+    }
+}
+
+/// Split the first entry off a batch payload; `None` once it is empty.
+pub(crate) fn next_batch_entry(payload: &[u8]) -> Result<Option<(PacketRef<'_>, &[u8])>, CodecError> {
+    if payload.is_empty() {
+        return Ok(None);
+    }
+    if payload.len() < BATCH_ENTRY_OVERHEAD {
+        return Err(CodecError::MalformedBatch);
+    }
+    let opcode = payload[0];
+    let len = u16::from_be_bytes([payload[1], payload[2]]) as usize;
+    let rest = &payload[BATCH_ENTRY_OVERHEAD..];
+    if opcode == OPCODE_BATCH || rest.len() < len {
+        return Err(CodecError::MalformedBatch);
+    }
+    let entry = packet_ref_from_opcode(opcode, &rest[..len])?;
+    Ok(Some((entry, &rest[len..])))
+}
+
 fn packet_from_opcode(opcode: u8, payload: &[u8]) -> Result<Packet, CodecError> {
     match opcode {
         OPCODE_PING => {
@@ -96,11 +470,14 @@ fn packet_from_opcode(opcode: u8, payload: &[u8]) -> Result<Packet, CodecError>
             }
             Ok(Packet::Pong)
         }
+        #[cfg(feature = "text")]
         OPCODE_MESSAGE => {
             let text = String::from_utf8(payload.to_vec()).map_err(CodecError::InvalidUtf8)?;
             Ok(Packet::Message(text))
         }
         OPCODE_DATA => Ok(Packet::Data(payload.to_vec())),
+        OPCODE_BATCH => packets_from_batch(payload).map(Packet::Batch),
+        OPCODE_HELLO => parse_hello(payload).map(|(version, features)| Packet::Hello { version, features }),
         other => Err(CodecError::InvalidOpcode(other)),
     }
 }
@@ -117,6 +494,30 @@ mod tests {
         assert_eq!(decoded, Packet::Ping);
     }
 
+    #[test]
+    fn hello_round_trips_owned_and_borrowed() {
+        let packet = Packet::Hello { version: 2, features: 0x0102_0304 };
+        let mut buf = Vec::new();
+        encode(&packet, &mut buf).unwrap();
+        assert_eq!(&buf[HEADER_LEN..], &[2, 1, 2, 3, 4]);
+        assert_eq!(decode(&buf).unwrap(), packet);
+        assert_eq!(decode_ref(&buf).unwrap(), PacketRef::Hello { version: 2, features: 0x0102_0304 });
+
+        let mut short = Header::new(OPCODE_HELLO, 1, fnv1a32(&[2])).to_bytes().to_vec();
+        short.push(2);
+        assert!(matches!(decode(&short), Err(CodecError::PayloadLengthMismatch { declared: 5, actual: 1 })));
+    }
+
+    #[cfg(not(feature = "text"))]
+    #[test]
+    fn message_opcode_is_unknown_without_text() {
+        let payload = b"hello";
+        let mut frame = Header::new(crate::packet::OPCODE_MESSAGE, 5, fnv1a32(payload)).to_bytes().to_vec();
+        frame.extend_from_slice(payload);
+        assert!(matches!(decode(&frame), Err(CodecError::InvalidOpcode(0x03))));
+    }
+
+    #[cfg(feature = "text")]
     #[test]
     fn encode_decode_message_round_trip() {
         let packet = Packet::Message("hello".into());
@@ -126,6 +527,7 @@ mod tests {
         assert_eq!(decoded, packet);
     }
 
+    #[cfg(feature = "text")]
     #[test]
     fn rejects_bad_checksum() {
         let packet = Packet::Message("hello".into());
@@ -137,12 +539,156 @@ mod tests {
         assert!(matches!(err, CodecError::ChecksumMismatch { .. }));
     }
 
+    #[cfg(feature = "text")]
+    #[test]
+    fn disabled_mode_writes_zero_and_skips_verification() {
+        let packet = Packet::Message("trusted".into());
+        let mut buf = Vec::new();
+        encode_with(&packet, &mut buf, ChecksumMode::Disabled).unwrap();
+        assert_eq!(&buf[5..9], &[0, 0, 0, 0]);
+
+        assert!(matches!(decode(&buf), Err(CodecError::ChecksumMismatch { .. })));
+        assert_eq!(decode_with(&buf, ChecksumMode::Disabled).unwrap(), packet);
+    }
+
+    #[cfg(feature = "text")]
+    #[test]
+    fn encode_decode_batch_round_trip() {
+        let packet = Packet::Batch(vec![
+            Packet::Ping,
+            Packet::Message("hi".into()),
+            Packet::Data(vec![1, 2, 3]),
+        ]);
+        let mut buf = Vec::new();
+        encode(&packet, &mut buf).unwrap();
+        assert_eq!(buf.len(), HEADER_LEN + 3 * BATCH_ENTRY_OVERHEAD + 2 + 3);
+        assert_eq!(decode(&buf).unwrap(), packet);
+    }
+
+    #[test]
+    fn encode_into_matches_encode() {
+        let packets = [
+            Packet::Pong,
+            Packet::Data(vec![9; 40]),
+            Packet::Batch(vec![Packet::Ping, Packet::Data(vec![1, 2])]),
+        ];
+        for packet in &packets {
+            let mut expected = Vec::new();
+            encode(packet, &mut expected).unwrap();
+            let mut buf = [0xFFu8; 64];
+            assert_eq!(encode_into(packet, &mut buf).unwrap(), encoded_len(packet, FrameFormat::V1, Flags::empty()));
+            assert_eq!(&buf[..expected.len()], &expected[..]);
+        }
+    }
+
+    #[test]
+    fn encoded_len_follows_format_and_flags() {
+        let packets = [Packet::Ping, Packet::Data(vec![3; 200]), Packet::Data(vec![4; 70000])];
+        let flag_sets = [Flags::empty(), Flags::COMPRESSED, Flags::HEADER_CRC];
+        for packet in &packets {
+            for format in [FrameFormat::V1, FrameFormat::V2, FrameFormat::Varint] {
+                for flags in flag_sets {
+                    let mut buf = Vec::new();
+                    if encode_format(packet, flags, format, &mut buf, ChecksumMode::Enabled).is_ok() {
+                        assert_eq!(encoded_len(packet, format, flags), buf.len(), "{format:?} {flags:?}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn encode_into_rejects_short_buffers_and_nested_batches() {
+        let mut buf = [0u8; 10];
+        assert!(matches!(
+            encode_into(&Packet::Data(vec![0; 2]), &mut buf),
+            Err(CodecError::BufferTooSmall { needed: 11, available: 10 })
+        ));
+        let nested = Packet::Batch(vec![Packet::Batch(vec![])]);
+        assert!(matches!(encode_into(&nested, &mut [0u8; 32]), Err(CodecError::MalformedBatch)));
+    }
+
+    #[test]
+    fn rejects_nested_batch() {
+        let packet = Packet::Batch(vec![Packet::Batch(vec![])]);
+        let err = encode(&packet, &mut Vec::new()).unwrap_err();
+        assert!(matches!(err, CodecError::MalformedBatch));
+    }
+
     #[test]
     fn errors_on_invalid_opcode() {
         let mut buf = Vec::new();
-        let header = Header::new(0xFF, 0, fnv1a32(&[]));
+        let header = Header::new(0x0F, 0, fnv1a32(&[]));
         buf.extend_from_slice(&header.to_bytes());
         let err = decode(&buf).unwrap_err();
-        assert!(matches!(err, CodecError::InvalidOpcode(0xFF)));
+        assert!(matches!(err, CodecError::InvalidOpcode(0x0F)));
+    }
+
+    #[cfg(feature = "text")]
+    #[test]
+    fn decode_ref_borrows_from_frame() {
+        let packet = Packet::Batch(vec![Packet::Message("hi".into()), Packet::Data(vec![1, 2])]);
+        let mut buf = Vec::new();
+        encode(&packet, &mut buf).unwrap();
+
+        let borrowed = decode_ref(&buf).unwrap();
+        let PacketRef::Batch(batch) = borrowed else { panic!("expected batch, got {:?}", borrowed) };
+        let entries: Vec<_> = batch.iter().collect();
+        assert_eq!(entries, [PacketRef::Message("hi"), PacketRef::Data(&[1, 2])]);
+        assert!(matches!(entries[0], PacketRef::Message(text) if text.as_ptr() == buf[HEADER_LEN + 3..].as_ptr()));
+        assert_eq!(borrowed.to_packet(), packet);
+
+        let mut reencoded = Vec::new();
+        encode_ref(&borrowed, &mut reencoded).unwrap();
+        assert_eq!(reencoded, buf);
+    }
+
+    #[test]
+    fn decode_ref_rejects_what_decode_rejects() {
+        let mut buf = Vec::new();
+        encode(&Packet::Data(vec![1, 2, 3]), &mut buf).unwrap();
+        let last = buf.len() - 1;
+        buf[last] ^= 0xFF;
+        assert!(matches!(decode_ref(&buf), Err(CodecError::ChecksumMismatch { .. })));
+        assert_eq!(decode_ref_with(&buf, ChecksumMode::Disabled).unwrap(), PacketRef::Data(&[1, 2, 0xFC]));
+
+        let truncated_batch = [OPCODE_DATA, 0, 5, 1];
+        let mut frame = Header::new(OPCODE_BATCH, 4, fnv1a32(&truncated_batch)).to_bytes().to_vec();
+        frame.extend_from_slice(&truncated_batch);
+        assert!(matches!(decode_ref(&frame), Err(CodecError::MalformedBatch)));
+    }
+
+    #[test]
+    fn flags_round_trip_and_plain_decode_rejects_transforms() {
+        let packet = Packet::Data(vec![7; 4]);
+        let mut buf = Vec::new();
+        encode_flagged(&packet, Flags::COMPRESSED | Flags::empty().with_priority(2), &mut buf, ChecksumMode::Enabled).unwrap();
+        assert_eq!(buf.len(), crate::header::FLAGGED_HEADER_LEN + 4);
+
+        let (decoded, flags) = decode_flagged(&buf, ChecksumMode::Enabled).unwrap();
+        assert_eq!(decoded, packet);
+        assert!(flags.contains(Flags::COMPRESSED));
+        assert_eq!(flags.priority(), 2);
+        assert!(matches!(decode(&buf), Err(CodecError::UnsupportedFlags(_))));
+
+        // Priority alone changes nothing about the payload, so plain decode accepts it
+        buf.clear();
+        encode_flagged(&packet, Flags::empty().with_priority(3), &mut buf, ChecksumMode::Enabled).unwrap();
+        assert_eq!(decode(&buf).unwrap(), packet);
+
+        // Empty flags keep the v1 layout byte for byte
+        let mut plain = Vec::new();
+        encode_flagged(&packet, Flags::empty(), &mut plain, ChecksumMode::Enabled).unwrap();
+        let mut v1 = Vec::new();
+        encode(&packet, &mut v1).unwrap();
+        assert_eq!(plain, v1);
+
+        let mut stream = v1.clone();
+        stream.extend_from_slice(&buf);
+        stream.extend_from_slice(&v1);
+        let mut decoder = crate::framing::FrameDecoder::new();
+        let result = decoder.decode(&stream);
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        assert_eq!(result.packets, [packet.clone(), packet.clone(), packet]);
     }
 }
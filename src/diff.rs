@@ -0,0 +1,221 @@
+//! Compare two captures of the same traffic frame by frame.
+//!
+//! Typical use is diffing what a peer sent against what arrived through a
+//! proxy or middlebox. Frames are matched by their raw bytes, so the diff
+//! works on traffic that no longer decodes.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::header::{Header, HEADER_LEN, HEADER_MAGIC};
+
+/// One frame cut out of a capture.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedFrame {
+    /// Byte offset of the header within the capture.
+    pub offset: usize,
+    /// Header and payload bytes; shorter than declared if the capture ends early.
+    pub bytes: Vec<u8>,
+}
+
+/// A difference between the expected and the actual capture.
+///
+/// Indexes refer to positions in the frame lists returned by
+/// [`split_frames`]; offsets are byte offsets in the captures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrameDiff {
+    /// An expected frame never arrived.
+    Missing { index: usize, offset: usize },
+    /// An expected frame arrived after a frame that was sent later.
+    Reordered { expected_index: usize, actual_index: usize, actual_offset: usize },
+    /// A frame arrived again after an identical copy was already matched.
+    Duplicated { expected_index: usize, actual_index: usize, actual_offset: usize },
+    /// A frame arrived with the right length but different bytes.
+    Corrupted {
+        expected_index: usize,
+        actual_index: usize,
+        actual_offset: usize,
+        /// Offset of the first differing byte within the frame.
+        first_difference: usize,
+    },
+    /// A frame arrived that matches nothing in the expected capture.
+    Unexpected { index: usize, offset: usize },
+}
+
+/// Result of [`diff`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiffReport {
+    /// Differences in the order they were detected: reordering and
+    /// duplicates first, then corruption, then unexpected and missing frames.
+    pub differences: Vec<FrameDiff>,
+    /// Frames that arrived intact and in order.
+    pub matched: usize,
+}
+
+impl DiffReport {
+    /// Returns `true` if the actual capture carries exactly the expected frames.
+    pub fn is_clean(&self) -> bool {
+        self.differences.is_empty()
+    }
+}
+
+/// Cut a capture into frames using each header's declared length.
+///
+/// Bytes that do not start a header are skipped until the next magic.
+/// Checksums are not verified.
+pub fn split_frames(capture: &[u8]) -> Vec<CapturedFrame> {
+    let magic = HEADER_MAGIC.to_be_bytes();
+    let mut frames = Vec::new();
+    let mut offset = 0;
+    while offset + HEADER_LEN <= capture.len() {
+        let Ok(header) = Header::from_bytes(&capture[offset..]) else {
+            offset += 1;
+            while offset < capture.len() && capture[offset] != magic[0] {
+                offset += 1;
+            }
+            continue;
+        };
+        let end = offset.saturating_add(header.wire_len()).saturating_add(header.length as usize).min(capture.len());
+        frames.push(CapturedFrame { offset, bytes: capture[offset..end].to_vec() });
+        offset = end;
+    }
+    frames
+}
+
+/// Align the frames of two captures and report how `actual` deviates from `expected`.
+pub fn diff(expected: &[u8], actual: &[u8]) -> DiffReport {
+    diff_frames(&split_frames(expected), &split_frames(actual))
+}
+
+/// Like [`diff`], for frame lists that were already split.
+pub fn diff_frames(expected: &[CapturedFrame], actual: &[CapturedFrame]) -> DiffReport {
+    let mut report = DiffReport::default();
+    let mut expected_used = vec![false; expected.len()];
+    let mut actual_used = vec![false; actual.len()];
+    let mut furthest: Option<usize> = None; // Highest expected index matched so far
+
+    // Pass 1: exact matches, in arrival order
+    for (actual_index, frame) in actual.iter().enumerate() {
+        let unmatched = (0..expected.len()).find(|&i| !expected_used[i] && expected[i].bytes == frame.bytes);
+        if let Some(expected_index) = unmatched {
+            expected_used[expected_index] = true;
+            actual_used[actual_index] = true;
+            if furthest.is_some_and(|max| expected_index < max) {
+                report.differences.push(FrameDiff::Reordered { expected_index, actual_index, actual_offset: frame.offset });
+            } else {
+                furthest = Some(expected_index);
+                report.matched += 1;
+            }
+        } else if let Some(expected_index) = expected.iter().position(|e| e.bytes == frame.bytes) {
+            actual_used[actual_index] = true;
+            report.differences.push(FrameDiff::Duplicated { expected_index, actual_index, actual_offset: frame.offset });
+        }
+    }
+
+    // Pass 2: pair leftovers of equal length as corrupted copies
+    for (actual_index, frame) in actual.iter().enumerate() {
+        if actual_used[actual_index] {
+            continue;
+        }
+        let candidate = (0..expected.len()).find(|&i| !expected_used[i] && expected[i].bytes.len() == frame.bytes.len());
+        if let Some(expected_index) = candidate {
+            expected_used[expected_index] = true;
+            actual_used[actual_index] = true;
+            let first_difference = expected[expected_index]
+                .bytes
+                .iter()
+                .zip(&frame.bytes)
+                .position(|(a, b)| a != b)
+                .unwrap_or(0);
+            report.differences.push(FrameDiff::Corrupted {
+                expected_index,
+                actual_index,
+                actual_offset: frame.offset,
+                first_difference,
+            });
+        }
+    }
+
+    for (index, frame) in actual.iter().enumerate().filter(|(i, _)| !actual_used[*i]) {
+        report.differences.push(FrameDiff::Unexpected { index, offset: frame.offset });
+    }
+    for (index, frame) in expected.iter().enumerate().filter(|(i, _)| !expected_used[*i]) {
+        report.differences.push(FrameDiff::Missing { index, offset: frame.offset });
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec;
+    use crate::packet::Packet;
+
+    fn frame(packet: &Packet) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        codec::encode(packet, &mut bytes).unwrap();
+        bytes
+    }
+
+    fn capture(frames: &[&Vec<u8>]) -> Vec<u8> {
+        frames.iter().flat_map(|f| f.iter().copied()).collect()
+    }
+
+    #[test]
+    fn identical_captures_are_clean() {
+        let a = frame(&Packet::Ping);
+        let b = frame(&Packet::Data(vec![1, 2, 3]));
+        let report = diff(&capture(&[&a, &b]), &capture(&[&a, &b]));
+
+        assert!(report.is_clean());
+        assert_eq!(report.matched, 2);
+    }
+
+    #[test]
+    fn splits_around_garbage_and_truncation() {
+        let a = frame(&Packet::Data(vec![7; 4]));
+        let mut bytes = vec![0x00, 0x13];
+        bytes.extend_from_slice(&a);
+        bytes.extend_from_slice(&a[..HEADER_LEN + 1]);
+
+        let frames = split_frames(&bytes);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0], CapturedFrame { offset: 2, bytes: a.clone() });
+        assert_eq!(frames[1].offset, 2 + a.len());
+        assert_eq!(frames[1].bytes.len(), HEADER_LEN + 1);
+    }
+
+    #[test]
+    fn reports_each_kind_of_difference() {
+        let a = frame(&Packet::Data(vec![1; 4]));
+        let b = frame(&Packet::Data(vec![2; 4]));
+        let c = frame(&Packet::Data(vec![3; 8]));
+        let d = frame(&Packet::Ping);
+        let e = frame(&Packet::Pong);
+        let mut corrupted = c.clone();
+        corrupted[HEADER_LEN + 5] ^= 0x01;
+        let stray = frame(&Packet::Data(vec![9; 20]));
+
+        let expected = capture(&[&a, &b, &c, &d, &e]);
+        let actual = capture(&[&b, &a, &a, &corrupted, &stray, &d]);
+        let report = diff(&expected, &actual);
+        let offset = |frames: &[&Vec<u8>]| frames.iter().map(|f| f.len()).sum::<usize>();
+
+        assert_eq!(report.matched, 2);
+        assert_eq!(
+            report.differences,
+            vec![
+                FrameDiff::Reordered { expected_index: 0, actual_index: 1, actual_offset: offset(&[&b]) },
+                FrameDiff::Duplicated { expected_index: 0, actual_index: 2, actual_offset: offset(&[&b, &a]) },
+                FrameDiff::Corrupted {
+                    expected_index: 2,
+                    actual_index: 3,
+                    actual_offset: offset(&[&b, &a, &a]),
+                    first_difference: HEADER_LEN + 5,
+                },
+                FrameDiff::Unexpected { index: 4, offset: offset(&[&b, &a, &a, &corrupted]) },
+                FrameDiff::Missing { index: 4, offset: offset(&[&a, &b, &c, &d]) },
+            ]
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! Hand-off between a circular DMA receive buffer and the main loop.
+//!
+//! UART DMA on STM32/nRF parts typically runs in circular mode over one
+//! buffer and raises an interrupt as each half fills. The interrupt calls
+//! [`DmaHalves::complete`]; the main loop polls [`DmaHalves::take`] and feeds
+//! the finished half to [`StaticFrameDecoder::feed`](crate::framing::StaticFrameDecoder::feed)
+//! while the DMA engine fills the other one. Nothing here allocates.
+//!
+//! ```
+//! use byteframe::dma::{DmaHalves, Half};
+//! use byteframe::framing::StaticFrameDecoder;
+//! use byteframe::{codec, Packet};
+//!
+//! static HALVES: DmaHalves = DmaHalves::new();
+//!
+//! let mut dma_buf = [0u8; 18];
+//! codec::encode_into(&Packet::Ping, &mut dma_buf[..9]).unwrap();
+//! codec::encode_into(&Packet::Pong, &mut dma_buf[9..]).unwrap();
+//! HALVES.complete(Half::First); // From the half-transfer interrupt
+//! HALVES.complete(Half::Second); // From the transfer-complete interrupt
+//!
+//! let mut decoder = StaticFrameDecoder::<32>::new();
+//! let mut opcodes = Vec::new();
+//! while let Some(half) = HALVES.take() {
+//!     decoder.feed(&dma_buf[half.range(dma_buf.len())], |frame| opcodes.push(frame.unwrap().opcode()));
+//! }
+//! assert_eq!(opcodes, [0x01, 0x02]);
+//! ```
+
+use core::ops::Range;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// One half of a circular DMA buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Half {
+    First,
+    Second,
+}
+
+impl Half {
+    /// The byte range this half covers in a buffer of `len` bytes.
+    pub fn range(self, len: usize) -> Range<usize> {
+        match self {
+            Half::First => 0..len / 2,
+            Half::Second => len / 2..len,
+        }
+    }
+
+    fn other(self) -> Half {
+        match self {
+            Half::First => Half::Second,
+            Half::Second => Half::First,
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Completion flags shared between the DMA interrupt and the main loop.
+///
+/// Only atomic loads and stores are used, so it also works on cores without
+/// compare-and-swap such as Cortex-M0. It is meant to live in a `static`
+/// with exactly one interrupt calling [`complete`](Self::complete) and one
+/// main loop calling [`take`](Self::take).
+#[derive(Debug)]
+pub struct DmaHalves {
+    ready: [AtomicBool; 2],
+    next_second: AtomicBool,
+    overrun: AtomicBool,
+}
+
+impl DmaHalves {
+    pub const fn new() -> Self {
+        Self {
+            ready: [AtomicBool::new(false), AtomicBool::new(false)],
+            next_second: AtomicBool::new(false),
+            overrun: AtomicBool::new(false),
+        }
+    }
+
+    /// Mark `half` as filled; call from the DMA interrupt.
+    ///
+    /// If `half` was still waiting to be taken, the DMA engine has already
+    /// overwritten it and [`take_overrun`](Self::take_overrun) will report it.
+    pub fn complete(&self, half: Half) {
+        if self.ready[half.index()].load(Ordering::Acquire) {
+            self.overrun.store(true, Ordering::Release);
+        }
+        self.ready[half.index()].store(true, Ordering::Release);
+    }
+
+    /// The next filled half in buffer order, clearing its flag; call from the main loop.
+    ///
+    /// The half must be decoded before the DMA engine wraps back round to it.
+    pub fn take(&self) -> Option<Half> {
+        let half = if self.next_second.load(Ordering::Relaxed) { Half::Second } else { Half::First };
+        if !self.ready[half.index()].load(Ordering::Acquire) {
+            return None;
+        }
+        self.ready[half.index()].store(false, Ordering::Release);
+        self.next_second.store(half.other() == Half::Second, Ordering::Relaxed);
+        Some(half)
+    }
+
+    /// Whether a half was overwritten before it was taken since the last call.
+    ///
+    /// After an overrun the decoder may hold part of a lost frame; call
+    /// [`StaticFrameDecoder::reset`](crate::framing::StaticFrameDecoder::reset)
+    /// or let it resync on the next header.
+    pub fn take_overrun(&self) -> bool {
+        let overrun = self.overrun.load(Ordering::Acquire);
+        if overrun {
+            self.overrun.store(false, Ordering::Release);
+        }
+        overrun
+    }
+}
+
+impl Default for DmaHalves {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn takes_halves_in_order_and_reports_overruns() {
+        let halves = DmaHalves::new();
+        assert_eq!(halves.take(), None);
+
+        halves.complete(Half::First);
+        halves.complete(Half::Second);
+        halves.complete(Half::First); // Main loop fell behind
+        assert!(halves.take_overrun());
+        assert!(!halves.take_overrun());
+
+        assert_eq!(halves.take(), Some(Half::First));
+        assert_eq!(halves.take(), Some(Half::Second));
+        assert_eq!(halves.take(), None);
+        assert_eq!(Half::Second.range(10), 5..10);
+    }
+}
@@ -0,0 +1,231 @@
+//! Packet reader and writer over `embedded_io` traits.
+//!
+//! These mirror [`crate::reader::PacketReader`] and [`crate::writer::PacketWriter`]
+//! for targets without `std::io`, such as UART or SPI drivers on bare metal.
+
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::fmt;
+
+use embedded_io::{ErrorType, Read, Write};
+
+use crate::codec::{self, ChecksumMode, CodecError};
+use crate::framing::{FrameDecoder, FrameError};
+use crate::packet::Packet;
+
+/// Errors from [`EmbeddedPacketReader`] and [`EmbeddedPacketWriter`].
+#[derive(Debug)]
+pub enum EmbeddedError<E> {
+    /// The underlying driver failed.
+    Io(E),
+    /// The source returned end-of-file before a complete packet.
+    Eof,
+    /// The byte stream could not be framed; the reader is poisoned.
+    Frame(FrameError),
+    /// The packet could not be encoded.
+    Codec(CodecError),
+    /// An earlier error left the stream in an unknown state.
+    Poisoned,
+}
+
+impl<E: fmt::Debug> fmt::Display for EmbeddedError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EmbeddedError::Io(err) => write!(f, "I/O error: {err:?}"),
+            EmbeddedError::Eof => write!(f, "stream closed before complete packet received"),
+            EmbeddedError::Frame(err) => write!(f, "framing error: {err:?}"),
+            EmbeddedError::Codec(err) => write!(f, "encode error: {err:?}"),
+            EmbeddedError::Poisoned => write!(f, "stream poisoned by an earlier error"),
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<E: defmt::Format> defmt::Format for EmbeddedError<E> {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        match self {
+            EmbeddedError::Io(err) => defmt::write!(f, "Io({})", err),
+            EmbeddedError::Eof => defmt::write!(f, "Eof"),
+            EmbeddedError::Frame(err) => defmt::write!(f, "Frame({})", err),
+            EmbeddedError::Codec(err) => defmt::write!(f, "Codec({})", err),
+            EmbeddedError::Poisoned => defmt::write!(f, "Poisoned"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: fmt::Debug> std::error::Error for EmbeddedError<E> {}
+
+/// Wraps an `embedded_io::Read` source and provides packet-level reading.
+pub struct EmbeddedPacketReader<R> {
+    reader: R,
+    decoder: FrameDecoder,
+    read_buffer: Vec<u8>,
+    packets: VecDeque<Packet>,
+    poisoned: bool,
+}
+
+impl<R: Read> EmbeddedPacketReader<R> {
+    /// Create a new reader with a 256-byte read buffer.
+    pub fn new(reader: R) -> Self {
+        Self::with_capacity(reader, 256)
+    }
+
+    /// Create a new reader with a specific read buffer size.
+    pub fn with_capacity(reader: R, capacity: usize) -> Self {
+        Self {
+            reader,
+            decoder: FrameDecoder::new(),
+            read_buffer: alloc::vec![0; capacity.max(1)],
+            packets: VecDeque::new(),
+            poisoned: false,
+        }
+    }
+
+    /// Set whether payload checksums are verified.
+    pub fn set_checksum_mode(&mut self, mode: ChecksumMode) {
+        self.decoder.set_checksum_mode(mode);
+    }
+
+    /// Read the next complete packet, blocking on the driver as needed.
+    pub fn read_packet(&mut self) -> Result<Packet, EmbeddedError<<R as ErrorType>::Error>> {
+        loop {
+            if let Some(packet) = self.packets.pop_front() {
+                return Ok(packet);
+            }
+            if self.poisoned {
+                return Err(EmbeddedError::Poisoned);
+            }
+
+            let n = self.reader.read(&mut self.read_buffer).map_err(EmbeddedError::Io)?;
+            if n == 0 {
+                return Err(EmbeddedError::Eof);
+            }
+
+            let mut result = self.decoder.decode(&self.read_buffer[..n]);
+            if !result.errors.is_empty() {
+                self.poisoned = true;
+                return Err(EmbeddedError::Frame(result.errors.swap_remove(0)));
+            }
+            self.packets.extend(result.packets);
+        }
+    }
+
+    /// Whether a framing error has stopped this reader.
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned
+    }
+
+    /// Get a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Get a mutable reference to the underlying reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Consume the reader, returning the underlying source.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+/// Wraps an `embedded_io::Write` sink and provides packet-level writing.
+pub struct EmbeddedPacketWriter<W> {
+    writer: W,
+    encode_buffer: Vec<u8>,
+    checksum_mode: ChecksumMode,
+    poisoned: bool, // Set when a driver error may have left a frame half-written
+}
+
+impl<W: Write> EmbeddedPacketWriter<W> {
+    /// Create a new writer wrapping the given sink.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            encode_buffer: Vec::new(),
+            checksum_mode: ChecksumMode::Enabled,
+            poisoned: false,
+        }
+    }
+
+    /// Set whether payload checksums are computed.
+    pub fn set_checksum_mode(&mut self, mode: ChecksumMode) {
+        self.checksum_mode = mode;
+    }
+
+    /// Encode a packet and write the whole frame to the sink.
+    pub fn write_packet(&mut self, packet: &Packet) -> Result<(), EmbeddedError<<W as ErrorType>::Error>> {
+        if self.poisoned {
+            return Err(EmbeddedError::Poisoned);
+        }
+        self.encode_buffer.clear();
+        codec::encode_with(packet, &mut self.encode_buffer, self.checksum_mode).map_err(EmbeddedError::Codec)?;
+        self.writer.write_all(&self.encode_buffer).map_err(|err| {
+            self.poisoned = true;
+            EmbeddedError::Io(err)
+        })
+    }
+
+    /// Flush the underlying sink.
+    pub fn flush(&mut self) -> Result<(), EmbeddedError<<W as ErrorType>::Error>> {
+        self.writer.flush().map_err(EmbeddedError::Io)
+    }
+
+    /// Get a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Get a mutable reference to the underlying writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Consume the writer, returning the underlying sink.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_slices() {
+        let mut wire = [0u8; 64];
+        let written = {
+            let mut writer = EmbeddedPacketWriter::new(&mut wire[..]);
+            writer.write_packet(&Packet::Ping).unwrap();
+            writer.write_packet(&Packet::Data(alloc::vec![1, 2, 3])).unwrap();
+            writer.flush().unwrap();
+            64 - writer.into_inner().len()
+        };
+
+        // A one-byte buffer forces the decoder to assemble frames across reads
+        let mut reader = EmbeddedPacketReader::with_capacity(&wire[..written], 1);
+        assert_eq!(reader.read_packet().unwrap(), Packet::Ping);
+        assert_eq!(reader.read_packet().unwrap(), Packet::Data(alloc::vec![1, 2, 3]));
+        assert!(matches!(reader.read_packet(), Err(EmbeddedError::Eof)));
+    }
+
+    #[test]
+    fn short_sink_poisons_writer() {
+        let mut wire = [0u8; 4];
+        let mut writer = EmbeddedPacketWriter::new(&mut wire[..]);
+        assert!(matches!(writer.write_packet(&Packet::Ping), Err(EmbeddedError::Io(_))));
+        assert!(matches!(writer.write_packet(&Packet::Ping), Err(EmbeddedError::Poisoned)));
+    }
+
+    #[test]
+    fn framing_error_poisons_reader() {
+        let garbage = [0u8; 16];
+        let mut reader = EmbeddedPacketReader::new(&garbage[..]);
+        assert!(matches!(reader.read_packet(), Err(EmbeddedError::Frame(_))));
+        assert!(reader.is_poisoned());
+        assert!(matches!(reader.read_packet(), Err(EmbeddedError::Poisoned)));
+    }
+}
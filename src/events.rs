@@ -0,0 +1,109 @@
+//! Lifecycle hooks invoked by the async server and client (`tokio` feature).
+
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+/// Why a connection ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloseCode {
+    /// The peer closed the stream.
+    PeerClosed,
+    /// The local side shut down (server shutdown, client dropped).
+    Shutdown,
+    /// A read or write failed.
+    Error,
+    /// The connection could not be (re)established.
+    GaveUp,
+    /// The server turned the connection away at accept time (connection limits).
+    Rejected,
+}
+
+/// Callbacks for connection lifecycle events.
+///
+/// Every method has an empty default, so implement only what you need.
+/// `peer` is `None` when the transport has no socket address, as with a
+/// custom connector passed to
+/// [`ReconnectingClient::connect`](crate::client::ReconnectingClient::connect).
+/// Callbacks run inline on the connection's task; keep them short.
+///
+/// ```
+/// use std::net::SocketAddr;
+/// use byteframe::events::{CloseCode, ConnectionEvents};
+///
+/// struct AuditLog;
+///
+/// impl ConnectionEvents for AuditLog {
+///     fn on_close(&self, peer: Option<SocketAddr>, code: CloseCode, reason: &str) {
+///         eprintln!("{:?} closed: {:?} ({})", peer, code, reason);
+///     }
+/// }
+/// ```
+pub trait ConnectionEvents: Send + Sync + 'static {
+    /// A stream to `peer` was opened or accepted.
+    fn on_connect(&self, _peer: Option<SocketAddr>) {}
+
+    /// The connection is ready to carry packets.
+    ///
+    /// byteframe has no handshake of its own, so this follows `on_connect`
+    /// once the reader and writer are set up (and, for a reconnecting
+    /// client, after unacknowledged packets have been replayed).
+    fn on_handshake_complete(&self, _peer: Option<SocketAddr>) {}
+
+    /// A read or write failed; `on_close` follows unless the client reconnects.
+    fn on_error(&self, _peer: Option<SocketAddr>, _error: &io::Error) {}
+
+    fn on_close(&self, _peer: Option<SocketAddr>, _code: CloseCode, _reason: &str) {}
+
+    /// Nothing was received from `peer` for `idle_for`; only the server
+    /// reports this, see [`PacketServer::idle_timeout`](crate::server::PacketServer::idle_timeout).
+    fn on_idle(&self, _peer: Option<SocketAddr>, _idle_for: Duration) {}
+}
+
+/// Ignores every event; the default for servers and clients.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoEvents;
+
+impl ConnectionEvents for NoEvents {}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Records events as short strings for assertions.
+    #[derive(Default)]
+    pub(crate) struct Recorder(pub(crate) Mutex<Vec<String>>);
+
+    impl Recorder {
+        pub(crate) fn events(&self) -> Vec<String> {
+            self.0.lock().unwrap().clone()
+        }
+
+        fn push(&self, event: String) {
+            self.0.lock().unwrap().push(event);
+        }
+    }
+
+    impl ConnectionEvents for std::sync::Arc<Recorder> {
+        fn on_connect(&self, _peer: Option<SocketAddr>) {
+            self.push("connect".into());
+        }
+
+        fn on_handshake_complete(&self, _peer: Option<SocketAddr>) {
+            self.push("ready".into());
+        }
+
+        fn on_error(&self, _peer: Option<SocketAddr>, error: &io::Error) {
+            self.push(format!("error {:?}", error.kind()));
+        }
+
+        fn on_close(&self, _peer: Option<SocketAddr>, code: CloseCode, _reason: &str) {
+            self.push(format!("close {:?}", code));
+        }
+
+        fn on_idle(&self, _peer: Option<SocketAddr>, _idle_for: Duration) {
+            self.push("idle".into());
+        }
+    }
+}
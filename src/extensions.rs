@@ -0,0 +1,110 @@
+//! A map holding one value per type, for state attached to a connection.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Values keyed by their type, at most one of each.
+///
+/// Lets separate layers keep their own per-connection state (an
+/// authenticated identity, a counter) without agreeing on a struct or
+/// keeping a side table keyed by peer address. Give each piece of state its
+/// own type so layers cannot collide:
+///
+/// ```
+/// use byteframe::extensions::Extensions;
+///
+/// struct User(String);
+///
+/// let mut extensions = Extensions::new();
+/// extensions.insert(User("ada".into()));
+/// *extensions.get_or_insert_default::<u32>() += 1;
+///
+/// assert_eq!(extensions.get::<User>().map(|user| user.0.as_str()), Some("ada"));
+/// assert_eq!(extensions.get::<u32>(), Some(&1));
+/// ```
+#[derive(Default)]
+pub struct Extensions {
+    map: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+}
+
+impl Extensions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store `value`, returning the previous value of the same type.
+    pub fn insert<T: Send + Sync + 'static>(&mut self, value: T) -> Option<T> {
+        self.map.insert(TypeId::of::<T>(), Box::new(value)).and_then(downcast)
+    }
+
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.map.get(&TypeId::of::<T>()).and_then(|value| value.downcast_ref())
+    }
+
+    pub fn get_mut<T: Send + Sync + 'static>(&mut self) -> Option<&mut T> {
+        self.map.get_mut(&TypeId::of::<T>()).and_then(|value| value.downcast_mut())
+    }
+
+    /// The value of type `T`, inserting `T::default()` first if there is none.
+    pub fn get_or_insert_default<T: Default + Send + Sync + 'static>(&mut self) -> &mut T {
+        self.map
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::<T>::default())
+            .downcast_mut()
+            .expect("entry is keyed by its own type")
+    }
+
+    pub fn remove<T: Send + Sync + 'static>(&mut self) -> Option<T> {
+        self.map.remove(&TypeId::of::<T>()).and_then(downcast)
+    }
+
+    pub fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        self.map.contains_key(&TypeId::of::<T>())
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+}
+
+impl fmt::Debug for Extensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Extensions").field("len", &self.map.len()).finish()
+    }
+}
+
+fn downcast<T: 'static>(value: Box<dyn Any + Send + Sync>) -> Option<T> {
+    value.downcast().ok().map(|value| *value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_value_per_type() {
+        #[derive(Debug, PartialEq)]
+        struct Identity(&'static str);
+
+        let mut extensions = Extensions::new();
+        assert_eq!(extensions.insert(Identity("a")), None);
+        assert_eq!(extensions.insert(Identity("b")), Some(Identity("a")));
+        extensions.insert(7u32);
+        *extensions.get_mut::<u32>().unwrap() += 1;
+
+        assert_eq!(extensions.len(), 2);
+        assert_eq!(extensions.get::<u32>(), Some(&8));
+        assert!(extensions.get::<u64>().is_none());
+        assert_eq!(extensions.remove::<Identity>(), Some(Identity("b")));
+        assert!(!extensions.contains::<Identity>());
+    }
+}
@@ -1,14 +1,109 @@
 //! Streaming framing state machine that turns arbitrary byte streams into packets.
 
-use crate::codec::{self, CodecError};
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use core::time::Duration;
+
+use crate::budget::MemoryBudget;
+#[cfg(feature = "std")]
+use crate::clock::{Clock, SystemClock};
+use crate::codec::{self, ChecksumMode, CodecError, FrameFormat};
 use crate::header;
 use crate::packet;
+use crate::stats::{FrameSizeHistogram, ResyncStats};
 
 #[derive(Debug, Default)]
 pub struct FrameDecoder {
     header_buf: Vec<u8>,            // Collecting header bytes
     current_header: Option<header::Header>, // Parsed header, now collecting payload
     payload_buf: Vec<u8>,           // Collecting payload bytes
+    budget: Option<MemoryBudget>,   // Shared cap on buffered payload bytes
+    reserved: usize,                // Bytes of `budget` held for the current payload
+    skip_remaining: usize,          // Payload bytes to discard after a rejected header
+    frame_sizes: FrameSizeHistogram, // Sizes of every complete frame seen
+    checksum_mode: ChecksumMode,    // Whether payload checksums are verified
+    validation: Option<header::ValidationConfig>, // Header rules checked before buffering
+    resync: ResyncStats,            // Corruption and recovery counters
+    corrupted: bool,                // Set between a corruption and the next good frame
+    framing: Framing,               // How frame boundaries are marked on the wire
+    slip_escaped: bool,             // Last SLIP byte was ESC
+    slip_discarding: bool,          // Dropping a bad SLIP frame up to the next END
+}
+
+/// How frame boundaries are marked on the wire.
+///
+/// `Slip` wraps each ordinary frame in SLIP byte stuffing (RFC 1055): frames
+/// end with `END` (0xC0), and `END`/`ESC` bytes inside are escaped. The
+/// header is still carried for its opcode and checksum, but resync after
+/// corruption only needs the next `END` instead of a magic scan, which
+/// recovers faster on lossy serial links.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Framing {
+    /// Magic and length header only (the default).
+    #[default]
+    Length,
+    /// SLIP-delimited frames.
+    Slip,
+}
+
+/// SLIP frame delimiter.
+pub const SLIP_END: u8 = 0xC0;
+/// SLIP escape byte.
+pub const SLIP_ESC: u8 = 0xDB;
+const SLIP_ESC_END: u8 = 0xDC;
+const SLIP_ESC_ESC: u8 = 0xDD;
+/// Budget bytes reserved at a time while a SLIP frame of unknown length accumulates.
+const SLIP_RESERVE_STEP: usize = 256;
+
+impl Framing {
+    /// Encode `packet` into `buf` using this framing.
+    pub fn encode_with(self, packet: &packet::Packet, buf: &mut Vec<u8>, mode: ChecksumMode) -> Result<(), CodecError> {
+        self.encode_flagged(packet, header::Flags::empty(), buf, mode)
+    }
+
+    /// [`encode_with`](Self::encode_with), carrying `flags` in the header.
+    pub fn encode_flagged(
+        self,
+        packet: &packet::Packet,
+        flags: header::Flags,
+        buf: &mut Vec<u8>,
+        mode: ChecksumMode,
+    ) -> Result<(), CodecError> {
+        self.encode_format(packet, flags, FrameFormat::V1, buf, mode)
+    }
+
+    /// [`encode_flagged`](Self::encode_flagged), allowing the length fields of `format`.
+    ///
+    /// SLIP decoders drop anything longer than a v1 frame while hunting
+    /// for a lost `END`, so pair `Slip` with `V1`.
+    pub fn encode_format(
+        self,
+        packet: &packet::Packet,
+        flags: header::Flags,
+        format: FrameFormat,
+        buf: &mut Vec<u8>,
+        mode: ChecksumMode,
+    ) -> Result<(), CodecError> {
+        match self {
+            Framing::Length => codec::encode_format(packet, flags, format, buf, mode),
+            Framing::Slip => {
+                let mut frame = Vec::new();
+                codec::encode_format(packet, flags, format, &mut frame, mode)?;
+                buf.reserve(frame.len() + 2);
+                buf.push(SLIP_END); // Flushes any line noise ahead of the frame
+                for byte in frame {
+                    match byte {
+                        SLIP_END => buf.extend_from_slice(&[SLIP_ESC, SLIP_ESC_END]),
+                        SLIP_ESC => buf.extend_from_slice(&[SLIP_ESC, SLIP_ESC_ESC]),
+                        other => buf.push(other),
+                    }
+                }
+                buf.push(SLIP_END);
+                Ok(())
+            }
+        }
+    }
 }
 
 #[derive(Debug, Default)]
@@ -18,9 +113,46 @@ pub struct DecodeResult {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum FrameError {
     InvalidMagic(u16),
     Codec(CodecError),
+    /// The shared memory budget could not cover this frame's payload; it was skipped.
+    BudgetExceeded { requested: usize },
+    /// The header failed [`header::Header::validate`]; its payload was skipped.
+    Header(header::HeaderError),
+    /// A SLIP `ESC` was followed by this byte instead of an escape code; the frame was dropped.
+    InvalidEscape(u8),
+    /// The decoder hit a state its invariants should rule out and dropped
+    /// the bytes involved instead of panicking. Please report these.
+    InternalState(&'static str),
+}
+
+impl FrameError {
+    /// Whether the decoder lost track of frame boundaries to get here.
+    ///
+    /// The other errors reject one frame whose extent was known (a bad
+    /// checksum or payload, a validation or budget reject), and the stream
+    /// carries on at the next frame.
+    pub fn is_desync(&self) -> bool {
+        matches!(
+            self,
+            FrameError::InvalidMagic(_)
+                | FrameError::InvalidEscape(_)
+                | FrameError::InternalState(_)
+                | FrameError::Header(header::HeaderError::InvalidVarint | header::HeaderError::HeaderCrcMismatch)
+        )
+    }
+}
+
+/// Limit on the work done by one [`FrameDecoder::decode_budgeted`] call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeBudget {
+    /// Stop after this many frames, good or bad. A `Batch` counts as one.
+    Frames(usize),
+    /// Stop at the first frame boundary after this much time has passed.
+    #[cfg(feature = "std")]
+    Time(Duration),
 }
 
 impl FrameDecoder {
@@ -28,68 +160,642 @@ impl FrameDecoder {
         Self::default()
     }
 
+    /// Create a decoder that reserves payload memory from a shared budget.
+    pub fn with_budget(budget: MemoryBudget) -> Self {
+        let mut decoder = Self::default();
+        decoder.budget = Some(budget);
+        decoder
+    }
+
+    /// Choose whether payload checksums are verified; see [`ChecksumMode`].
+    pub fn set_checksum_mode(&mut self, mode: ChecksumMode) {
+        self.checksum_mode = mode;
+    }
+
+    pub fn checksum_mode(&self) -> ChecksumMode {
+        self.checksum_mode
+    }
+
+    /// Choose how frame boundaries are found; both peers must agree.
+    ///
+    /// Any partially decoded frame is dropped.
+    pub fn set_framing(&mut self, framing: Framing) {
+        let (frame_sizes, resync) = (core::mem::take(&mut self.frame_sizes), core::mem::take(&mut self.resync));
+        self.reset();
+        self.frame_sizes = frame_sizes;
+        self.resync = resync;
+        self.framing = framing;
+    }
+
+    pub fn framing(&self) -> Framing {
+        self.framing
+    }
+
+    /// Reject headers breaking `config` before their payload is buffered.
+    ///
+    /// Without a config, frames newer than [`header::PROTOCOL_VERSION`] are
+    /// still rejected with `UnsupportedVersion`, and payloads over
+    /// [`header::DEFAULT_MAX_LENGTH`] with `LengthExceeded`. Set
+    /// [`max_version`](header::ValidationConfig::max_version) to accept a
+    /// version negotiated through `Hello`, and raise
+    /// [`max_length`](header::ValidationConfig::max_length) to accept large
+    /// v2 or varint frames.
+    pub fn set_validation(&mut self, config: header::ValidationConfig) {
+        self.validation = Some(config);
+    }
+
+    /// Sizes (header + payload) of the frames this decoder has completed.
+    pub fn frame_sizes(&self) -> &FrameSizeHistogram {
+        &self.frame_sizes
+    }
+
+    /// Counters describing corruption seen on this stream and how the decoder recovered.
+    pub fn resync_stats(&self) -> &ResyncStats {
+        &self.resync
+    }
+
+    /// Returns `true` if bytes of an unfinished frame are buffered or being skipped.
+    pub fn is_mid_frame(&self) -> bool {
+        !self.header_buf.is_empty()
+            || self.current_header.is_some()
+            || self.skip_remaining > 0
+            || (self.framing == Framing::Slip && (!self.payload_buf.is_empty() || self.slip_discarding))
+    }
+
+    /// Drop any partially decoded frame and clear the statistics, keeping
+    /// buffer capacity for reuse.
+    pub fn reset(&mut self) {
+        self.frame_sizes = FrameSizeHistogram::new();
+        self.resync = ResyncStats::default();
+        self.corrupted = false;
+        self.header_buf.clear();
+        self.current_header = None;
+        self.payload_buf.clear();
+        self.skip_remaining = 0;
+        self.slip_escaped = false;
+        self.slip_discarding = false;
+        self.release_reserved();
+    }
+
+    /// [`reset`](Self::reset), and also put checksum mode, validation and
+    /// framing back to their defaults. The budget is kept.
+    pub(crate) fn reset_config(&mut self) {
+        self.reset();
+        self.checksum_mode = ChecksumMode::Enabled;
+        self.validation = None;
+        self.framing = Framing::Length;
+    }
+
     pub fn decode(&mut self, input: &[u8]) -> DecodeResult {
         let mut result = DecodeResult::default();
-
         for &byte in input {
-            if self.current_header.is_none() { // State 1 - building header until we find a payload
-                self.header_buf.push(byte); // Accumulate header bytes
-                if let Some(parsed_header) = self.try_extract_header(&mut result) {
-                    if parsed_header.length == 0 { // Zero-length payload (Ping/Pong)
-                        self.finish_frame(parsed_header, Vec::new(), &mut result);
-                    } else { // Need to read `header.length` more bytes
-                        self.payload_buf.clear();
-                        self.current_header = Some(parsed_header); 
-                    }
+            self.decode_byte(byte, &mut result);
+        }
+        result
+    }
+
+    /// Feed a single byte, returning the packet or error it completes.
+    ///
+    /// Meant for interrupt handlers that receive one byte at a time: no
+    /// slice or [`DecodeResult`] is needed. A `Batch` is returned whole
+    /// rather than unpacked as [`decode`](Self::decode) does. In the rare
+    /// case that one byte both costs a resync and completes a frame, the
+    /// error is returned and that frame is lost.
+    pub fn push_byte(&mut self, byte: u8) -> Option<Result<packet::Packet, FrameError>> {
+        let mut outcome = None;
+        self.decode_byte(byte, &mut outcome);
+        outcome
+    }
+
+    /// Decode until `budget` is used up and return the unconsumed input.
+    ///
+    /// Meant for cooperative schedulers and game loops that must bound the
+    /// time spent per tick on a flooded connection: feed the returned tail
+    /// back in on the next tick. The budget is checked only when a frame
+    /// ends, so a partial frame never stops the call early.
+    pub fn decode_budgeted<'a>(&mut self, input: &'a [u8], budget: DecodeBudget) -> (DecodeResult, &'a [u8]) {
+        #[cfg(feature = "std")]
+        return self.decode_budgeted_with_clock(input, budget, &SystemClock);
+        #[cfg(not(feature = "std"))]
+        {
+            let DecodeBudget::Frames(max) = budget;
+            self.decode_until(input, |frames| frames >= max)
+        }
+    }
+
+    /// [`decode_budgeted`](Self::decode_budgeted) measuring time with `clock`.
+    #[cfg(feature = "std")]
+    pub fn decode_budgeted_with_clock<'a>(
+        &mut self,
+        input: &'a [u8],
+        budget: DecodeBudget,
+        clock: &dyn Clock,
+    ) -> (DecodeResult, &'a [u8]) {
+        let started = clock.now();
+        self.decode_until(input, |frames| match budget {
+            DecodeBudget::Frames(max) => frames >= max,
+            DecodeBudget::Time(max) => clock.now().duration_since(started) >= max,
+        })
+    }
+
+    /// Decode until `exhausted(frames_so_far)` holds at a frame boundary.
+    fn decode_until<'a>(&mut self, input: &'a [u8], mut exhausted: impl FnMut(usize) -> bool) -> (DecodeResult, &'a [u8]) {
+        let mut result = DecodeResult::default();
+        let mut frames = 0;
+        for (index, &byte) in input.iter().enumerate() {
+            if self.decode_byte(byte, &mut result) {
+                frames += 1;
+                if exhausted(frames) {
+                    return (result, &input[index + 1..]);
                 }
-            } else { // State 2 - after finding payload
-                let expected_len = self.current_header
-                    .as_ref()
-                    .expect("Failed to decode frame: header information (current_header) missing during payload read")
-                    .length as usize;
-                self.payload_buf.push(byte);
-                if self.payload_buf.len() == expected_len { // Compare with length
-                    let parsed_header = self.current_header
-                        .take() // Got all payload bytes
-                        .expect("Failed to complete frame: header missing after collecting payload");
-                    let payload = core::mem::take(&mut self.payload_buf);
-                    self.finish_frame(parsed_header, payload, &mut result);
+            }
+        }
+        (result, &[])
+    }
+
+    /// Feed one byte; returns `true` if it ended a frame (decoded, failed or skipped).
+    fn decode_byte(&mut self, byte: u8, result: &mut impl FrameSink) -> bool {
+        if self.framing == Framing::Slip {
+            return self.decode_slip_byte(byte, result);
+        }
+        if self.skip_remaining > 0 { // Discarding the payload of a rejected frame
+            self.skip_remaining -= 1;
+            return self.skip_remaining == 0;
+        }
+
+        if self.current_header.is_none() { // State 1 - building header until we find a payload
+            self.header_buf.push(byte); // Accumulate header bytes
+            if let Some(parsed_header) = self.try_extract_header(result) {
+                if let Some(err) = self.check_header(&parsed_header) { // Fail fast: skip the payload unbuffered
+                    result.error(FrameError::Header(err));
+                    self.skip_remaining = parsed_header.length as usize;
+                    return self.skip_remaining == 0;
+                } else if parsed_header.length == 0 { // Zero-length payload (Ping/Pong)
+                    self.finish_frame(parsed_header, Vec::new(), result);
+                    return true;
+                } else if !self.reserve(parsed_header.length as usize) { // Over budget: skip the payload
+                    result.error(FrameError::BudgetExceeded { requested: parsed_header.length as usize });
+                    self.skip_remaining = parsed_header.length as usize;
+                } else { // Need to read `header.length` more bytes
+                    self.payload_buf.clear();
+                    self.current_header = Some(parsed_header); 
                 }
             }
+        } else if let Some(parsed_header) = self.current_header { // State 2 - after finding payload
+            self.payload_buf.push(byte);
+            if self.payload_buf.len() == parsed_header.length as usize { // Compare with length
+                self.current_header = None; // Got all payload bytes
+                let payload = core::mem::take(&mut self.payload_buf);
+                self.release_reserved();
+                self.finish_frame(parsed_header, payload, result);
+                return true;
+            }
         }
 
-        result
+        false
+    }
+
+    fn decode_slip_byte(&mut self, byte: u8, result: &mut impl FrameSink) -> bool {
+        if byte == SLIP_END {
+            self.slip_escaped = false;
+            if core::mem::take(&mut self.slip_discarding) {
+                self.payload_buf.clear();
+                return true;
+            }
+            if self.payload_buf.is_empty() { // Back-to-back ENDs carry no frame
+                return false;
+            }
+            self.release_reserved();
+            let body = core::mem::take(&mut self.payload_buf);
+            self.finish_slip_frame(&body, result);
+            self.payload_buf = body;
+            self.payload_buf.clear();
+            return true;
+        }
+        if self.slip_discarding {
+            return false;
+        }
+
+        let byte = if core::mem::take(&mut self.slip_escaped) {
+            match byte {
+                SLIP_ESC_END => SLIP_END,
+                SLIP_ESC_ESC => SLIP_ESC,
+                other => {
+                    result.error(FrameError::InvalidEscape(other));
+                    self.resync.frames_failed += 1;
+                    self.corrupted = true;
+                    self.slip_discarding = true;
+                    return false;
+                }
+            }
+        } else if byte == SLIP_ESC {
+            self.slip_escaped = true;
+            return false;
+        } else {
+            byte
+        };
+
+        if self.payload_buf.len() >= header::MAX_HEADER_LEN.saturating_add(self.max_length()) { // Longer than any accepted frame: lost END
+            result.error(FrameError::Codec(CodecError::PayloadTooLarge(self.payload_buf.len() + 1)));
+            self.resync.frames_failed += 1;
+            self.corrupted = true;
+            self.discard_slip_frame();
+            return false;
+        }
+        if self.budget.is_some() && self.payload_buf.len() == self.reserved && !self.reserve_more(SLIP_RESERVE_STEP) {
+            result.error(FrameError::BudgetExceeded { requested: self.payload_buf.len() + 1 });
+            self.discard_slip_frame();
+            return false;
+        }
+        self.payload_buf.push(byte);
+        false
+    }
+
+    /// Drop the SLIP frame being collected, and its budget, up to the next END.
+    fn discard_slip_frame(&mut self) {
+        self.slip_discarding = true;
+        self.payload_buf.clear();
+        self.release_reserved();
+    }
+
+    /// Largest payload the validation rules (or their default) accept.
+    fn max_length(&self) -> usize {
+        self.validation.as_ref().map_or(header::DEFAULT_MAX_LENGTH, |config| config.max_length) as usize
+    }
+
+    fn finish_slip_frame(&mut self, body: &[u8], result: &mut impl FrameSink) {
+        let parsed = match header::Header::from_bytes(body) {
+            Err(header::HeaderError::ShortBuffer(len)) => Err(CodecError::FrameTooShort(len)),
+            other => other.map_err(CodecError::from),
+        };
+        let parsed_header = match parsed {
+            Ok(parsed_header) => parsed_header,
+            Err(err) => {
+                result.error(FrameError::Codec(err));
+                self.resync.frames_failed += 1;
+                self.corrupted = true;
+                return;
+            }
+        };
+        if let Some(err) = self.check_header(&parsed_header) {
+            result.error(FrameError::Header(err));
+            return;
+        }
+        self.finish_frame(parsed_header, body[parsed_header.wire_len()..].to_vec(), result);
+    }
+
+    /// Apply the validation rules, or without any, refuse versions above
+    /// [`header::PROTOCOL_VERSION`] and lengths above [`header::DEFAULT_MAX_LENGTH`].
+    fn check_header(&self, parsed_header: &header::Header) -> Option<header::HeaderError> {
+        match &self.validation {
+            Some(config) => parsed_header.validate(config).err(),
+            None => default_check(parsed_header),
+        }
     }
 
-    fn try_extract_header(&mut self, result: &mut DecodeResult) -> Option<header::Header> {
+    fn try_extract_header(&mut self, result: &mut impl FrameSink) -> Option<header::Header> {
         loop {
             if self.header_buf.len() < header::HEADER_LEN {
                 return None;
             }
 
-            match header::Header::from_bytes(&self.header_buf[..header::HEADER_LEN]) { // Take the first 9 bytes
+            match header::Header::from_bytes(&self.header_buf) { // Parse whichever layout the magic selects
                 Ok(parsed_header) => { // Parse them into a Header struct
-                    self.header_buf.drain(..header::HEADER_LEN); // Remove the first 9 bytes and shift everything else down
+                    self.header_buf.drain(..parsed_header.wire_len()); // Remove the header bytes and shift everything else down
                     return Some(parsed_header);
                 }
+                Err(header::HeaderError::ShortBuffer(_)) => return None, // Longer layout (or varint length) still incomplete
                 Err(header::HeaderError::InvalidMagic(magic)) => {
-                    result.errors.push(FrameError::InvalidMagic(magic));
+                    result.error(FrameError::InvalidMagic(magic));
                     self.header_buf.remove(0);
+                    self.resync.bytes_skipped += 1;
+                    self.corrupted = true;
+                }
+                Err(err @ (header::HeaderError::InvalidVarint | header::HeaderError::HeaderCrcMismatch)) => { // Treat like a bad magic and rescan from the next byte
+                    result.error(FrameError::Header(err));
+                    self.header_buf.remove(0);
+                    self.resync.bytes_skipped += 1;
+                    self.corrupted = true;
+                }
+                Err(_) => { // Unreachable for a full buffer today; drop the bytes rather than panic
+                    result.error(FrameError::InternalState("header rejected after its length was checked"));
+                    self.resync.bytes_skipped += self.header_buf.len() as u64;
+                    self.header_buf.clear();
+                    self.corrupted = true;
+                    return None;
+                }
+            }
+        }
+    }
+
+    fn reserve(&mut self, bytes: usize) -> bool {
+        match &self.budget {
+            Some(budget) if !budget.try_reserve(bytes) => false,
+            Some(_) => {
+                self.reserved = bytes;
+                true
+            }
+            None => true,
+        }
+    }
+
+    /// Grow the current reservation by `bytes`, for payloads whose length is not known up front.
+    fn reserve_more(&mut self, bytes: usize) -> bool {
+        match &self.budget {
+            Some(budget) if !budget.try_reserve(bytes) => false,
+            Some(_) => {
+                self.reserved += bytes;
+                true
+            }
+            None => true,
+        }
+    }
+
+    fn release_reserved(&mut self) {
+        if let Some(budget) = &self.budget {
+            budget.release(core::mem::take(&mut self.reserved));
+        }
+    }
+
+    fn finish_frame(&mut self, parsed_header: header::Header, payload: Vec<u8>, result: &mut impl FrameSink) {
+        self.frame_sizes.record(parsed_header.wire_len() + payload.len());
+        let decoded = codec::decode_frame(&parsed_header, &payload, self.checksum_mode);
+        match &decoded {
+            Ok(_) => {
+                self.resync.frames_ok += 1;
+                if core::mem::take(&mut self.corrupted) {
+                    self.resync.frames_recovered += 1;
                 }
-                Err(header::HeaderError::ShortBuffer(_)) => unreachable!(
-                    "Decoder error: Buffer length validated ({} bytes >= {} required), but header parsing still failed. Please report this bug.",
-                    self.header_buf.len(),
-                    header::HEADER_LEN
-                ),
             }
+            Err(CodecError::ChecksumMismatch { .. }) => {
+                self.resync.checksum_failures += 1;
+                self.corrupted = true;
+            }
+            Err(_) => {
+                self.resync.frames_failed += 1;
+                self.corrupted = true;
+            }
+        }
+        match decoded {
+            Ok(decoded_packet) => result.packet(decoded_packet),
+            Err(err) => result.error(FrameError::Codec(err)),
         }
     }
+}
+
+/// Where [`FrameDecoder`] delivers the packets and errors it finds.
+trait FrameSink {
+    fn packet(&mut self, packet: packet::Packet);
+    fn error(&mut self, err: FrameError);
+}
+
+impl FrameSink for DecodeResult {
+    fn packet(&mut self, packet: packet::Packet) {
+        match packet {
+            packet::Packet::Batch(packets) => self.packets.extend(packets), // Unpack coalesced frames
+            other => self.packets.push(other),
+        }
+    }
+
+    fn error(&mut self, err: FrameError) {
+        self.errors.push(err);
+    }
+}
+
+/// The single outcome of [`FrameDecoder::push_byte`]; one byte finishes at
+/// most one frame. If a resync error and a frame both come from one byte,
+/// the error is kept so the caller still learns the stream was damaged.
+impl FrameSink for Option<Result<packet::Packet, FrameError>> {
+    fn packet(&mut self, packet: packet::Packet) {
+        self.get_or_insert(Ok(packet));
+    }
+
+    fn error(&mut self, err: FrameError) {
+        self.get_or_insert(Err(err));
+    }
+}
+
+impl Drop for FrameDecoder {
+    fn drop(&mut self) {
+        self.release_reserved();
+    }
+}
+
+/// Rules applied to every header when no [`header::ValidationConfig`] is set.
+fn default_check(parsed_header: &header::Header) -> Option<header::HeaderError> {
+    if parsed_header.version > header::PROTOCOL_VERSION {
+        return Some(header::HeaderError::UnsupportedVersion(parsed_header.version));
+    }
+    if parsed_header.length > header::DEFAULT_MAX_LENGTH {
+        return Some(header::HeaderError::LengthExceeded {
+            length: parsed_header.length,
+            max: header::DEFAULT_MAX_LENGTH,
+        });
+    }
+    None
+}
+
+/// Errors reported by [`StaticFrameDecoder::poll`]; the decoder keeps going after each.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum StaticFrameError {
+    /// A header failed to parse, and the decoder skipped one byte to resync;
+    /// or it names a newer version, and its whole frame was skipped.
+    Header(header::HeaderError),
+    /// The payload does not fit the `N`-byte buffer; it was skipped.
+    TooLarge { length: u32, capacity: usize },
+    ChecksumMismatch { expected: u32, actual: u32 },
+}
+
+/// A complete frame borrowed from a [`StaticFrameDecoder`]'s buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawFrame<'a> {
+    pub header: header::Header,
+    pub payload: &'a [u8],
+}
+
+impl RawFrame<'_> {
+    pub fn opcode(&self) -> u8 {
+        self.header.opcode
+    }
+
+    /// Decode into a [`Packet`](packet::Packet); unlike the decoder itself, this allocates.
+    pub fn to_packet(&self) -> Result<packet::Packet, CodecError> {
+        codec::decode_frame(&self.header, self.payload, ChecksumMode::Disabled) // Already verified
+    }
+}
+
+/// Allocation-free decoder for targets without a heap allocator to spare.
+///
+/// Buffers live inline: one header and up to `N` payload bytes. Frames come
+/// out one at a time from [`poll`](Self::poll), borrowed from the decoder,
+/// so each must be handled before the next call. Frames with a payload
+/// over `N` bytes are skipped with [`StaticFrameError::TooLarge`].
+///
+/// ```
+/// use byteframe::framing::StaticFrameDecoder;
+/// use byteframe::{codec, Packet};
+///
+/// let mut stream = Vec::new();
+/// codec::encode(&Packet::Ping, &mut stream).unwrap();
+/// codec::encode(&Packet::Data(vec![1, 2, 3]), &mut stream).unwrap();
+///
+/// let mut decoder = StaticFrameDecoder::<64>::new();
+/// let mut input = &stream[..];
+/// let mut opcodes = Vec::new();
+/// loop {
+///     let (used, frame) = decoder.poll(input);
+///     input = &input[used..];
+///     match frame {
+///         Some(Ok(frame)) => opcodes.push(frame.opcode()),
+///         Some(Err(err)) => panic!("{:?}", err),
+///         None => break, // Everything consumed; wait for more input
+///     }
+/// }
+/// assert_eq!(opcodes, [0x01, 0x04]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct StaticFrameDecoder<const N: usize> {
+    header_buf: [u8; header::MAX_HEADER_LEN],
+    header_len: usize,
+    current_header: Option<header::Header>,
+    payload_buf: [u8; N],
+    payload_len: usize,
+    skip_remaining: usize,
+    checksum_mode: ChecksumMode,
+}
+
+impl<const N: usize> StaticFrameDecoder<N> {
+    pub const fn new() -> Self {
+        Self {
+            header_buf: [0; header::MAX_HEADER_LEN],
+            header_len: 0,
+            current_header: None,
+            payload_buf: [0; N],
+            payload_len: 0,
+            skip_remaining: 0,
+            checksum_mode: ChecksumMode::Enabled,
+        }
+    }
+
+    /// Choose whether payload checksums are verified; see [`ChecksumMode`].
+    pub fn set_checksum_mode(&mut self, mode: ChecksumMode) {
+        self.checksum_mode = mode;
+    }
+
+    /// Consume bytes from `input` until one frame or error is ready.
+    ///
+    /// Returns how many bytes were consumed and the frame or error, if any.
+    /// `None` means all of `input` was consumed without completing a frame.
+    pub fn poll(&mut self, input: &[u8]) -> (usize, Option<Result<RawFrame<'_>, StaticFrameError>>) {
+        let mut used = 0;
+        while used < input.len() {
+            if self.skip_remaining > 0 { // Discarding an oversized payload
+                let skipped = self.skip_remaining.min(input.len() - used);
+                self.skip_remaining -= skipped;
+                used += skipped;
+                continue;
+            }
+
+            let Some(parsed_header) = self.current_header else {
+                self.header_buf[self.header_len] = input[used];
+                self.header_len += 1;
+                used += 1;
+                if self.header_len < header::HEADER_LEN {
+                    continue;
+                }
+                match header::Header::from_bytes(&self.header_buf[..self.header_len]) {
+                    Ok(parsed_header) => {
+                        self.header_len = 0;
+                        self.payload_len = 0;
+                        if parsed_header.version > header::PROTOCOL_VERSION { // Skip the frame like FrameDecoder does
+                            self.skip_remaining = parsed_header.length as usize;
+                            let err = header::HeaderError::UnsupportedVersion(parsed_header.version);
+                            return (used, Some(Err(StaticFrameError::Header(err))));
+                        }
+                        if parsed_header.length as usize > N {
+                            self.skip_remaining = parsed_header.length as usize;
+                            let err = StaticFrameError::TooLarge { length: parsed_header.length, capacity: N };
+                            return (used, Some(Err(err)));
+                        }
+                        if parsed_header.length == 0 {
+                            return (used, Some(self.finish(parsed_header)));
+                        }
+                        self.current_header = Some(parsed_header);
+                    }
+                    Err(header::HeaderError::ShortBuffer(_)) => {} // Longer layout (or varint length) still incomplete
+                    Err(err) => { // Slide the window by one byte and keep scanning
+                        self.header_buf.copy_within(1.., 0);
+                        self.header_len -= 1;
+                        return (used, Some(Err(StaticFrameError::Header(err))));
+                    }
+                }
+                continue;
+            };
+
+            let wanted = parsed_header.length as usize - self.payload_len;
+            let take = wanted.min(input.len() - used);
+            self.payload_buf[self.payload_len..self.payload_len + take].copy_from_slice(&input[used..used + take]);
+            self.payload_len += take;
+            used += take;
+            if take == wanted {
+                self.current_header = None;
+                return (used, Some(self.finish(parsed_header)));
+            }
+        }
+        (used, None)
+    }
+
+    /// Consume all of `input`, handing each frame or error to `on_frame`.
+    ///
+    /// Meant for a completed DMA half-buffer (see [`crate::dma::DmaHalves`]):
+    /// the whole slice is decoded before the DMA engine refills it, and a
+    /// frame split across halves is finished by the next call. Returns how
+    /// many frames and errors were delivered.
+    pub fn feed<F>(&mut self, mut input: &[u8], mut on_frame: F) -> usize
+    where
+        F: FnMut(Result<RawFrame<'_>, StaticFrameError>),
+    {
+        let mut delivered = 0;
+        loop {
+            let (used, item) = self.poll(input);
+            input = &input[used..];
+            match item {
+                Some(item) => on_frame(item),
+                None => return delivered,
+            }
+            delivered += 1;
+        }
+    }
+
+    /// Whether a partial header or payload is buffered.
+    pub fn is_mid_frame(&self) -> bool {
+        self.header_len > 0 || self.current_header.is_some() || self.skip_remaining > 0
+    }
+
+    /// Drop any partial frame.
+    pub fn reset(&mut self) {
+        self.header_len = 0;
+        self.current_header = None;
+        self.payload_len = 0;
+        self.skip_remaining = 0;
+    }
 
-    fn finish_frame(&mut self, parsed_header: header::Header, payload: Vec<u8>, result: &mut DecodeResult) {
-        match codec::decode_frame(&parsed_header, &payload) {
-            Ok(decoded_packet) => result.packets.push(decoded_packet),
-            Err(err) => result.errors.push(FrameError::Codec(err)),
+    fn finish(&self, parsed_header: header::Header) -> Result<RawFrame<'_>, StaticFrameError> {
+        let payload = &self.payload_buf[..self.payload_len];
+        if self.checksum_mode == ChecksumMode::Enabled {
+            let actual = crate::checksum::fnv1a32(payload);
+            if actual != parsed_header.checksum {
+                return Err(StaticFrameError::ChecksumMismatch { expected: parsed_header.checksum, actual });
+            }
         }
+        Ok(RawFrame { header: parsed_header, payload })
+    }
+}
+
+impl<const N: usize> Default for StaticFrameDecoder<N> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -97,7 +803,9 @@ impl FrameDecoder {
 mod tests {
     use super::*;
     use crate::codec;
+    use crate::dma::{DmaHalves, Half};
     use crate::packet::Packet;
+    use crate::rng::{Rng, SeededRng};
 
     fn encode(input_packet: &Packet) -> Vec<u8> {
         let mut buf = Vec::new();
@@ -105,6 +813,7 @@ mod tests {
         buf
     }
 
+    #[cfg(feature = "text")]
     #[test]
     fn decodes_across_partial_chunks() {
         let mut stream = Vec::new();
@@ -138,6 +847,7 @@ mod tests {
         assert!(output.errors.iter().any(|err| matches!(err, FrameError::InvalidMagic(_))));
     }
 
+    #[cfg(feature = "text")]
     #[test]
     fn detects_checksum_failure_and_continues() {
         let mut stream = encode(&packet::Packet::Message("hello".into()));
@@ -154,4 +864,476 @@ mod tests {
             .iter()
             .any(|err| matches!(err, FrameError::Codec(CodecError::ChecksumMismatch { .. }))));
     }
+
+    #[test]
+    fn unpacks_batches_into_individual_packets() {
+        let batch = packet::Packet::Batch(vec![packet::Packet::Ping, packet::Packet::Data(vec![4])]);
+        let mut stream = encode(&batch);
+        stream.extend_from_slice(&encode(&packet::Packet::Pong));
+
+        let mut decoder = FrameDecoder::new();
+        let output = decoder.decode(&stream);
+
+        assert_eq!(
+            output.packets,
+            vec![packet::Packet::Ping, packet::Packet::Data(vec![4]), packet::Packet::Pong]
+        );
+    }
+
+    #[test]
+    fn varint_lengths_parse_across_chunk_boundaries() {
+        let packets = [Packet::Ping, Packet::Data(vec![3; 200]), Packet::Data(vec![9; 70000]), Packet::Pong];
+        let mut stream = Vec::new();
+        for packet in &packets {
+            codec::encode_format(packet, header::Flags::empty(), FrameFormat::Varint, &mut stream, ChecksumMode::Enabled)
+                .unwrap();
+        }
+        assert_eq!(stream[..2], header::HEADER_MAGIC_VARINT.to_be_bytes());
+        assert_eq!(stream.len(), 4 * 8 + 1 + 2 + 3 + 1 + 200 + 70000);
+
+        for chunk_len in [1, 3, 10, 4096] {
+            let mut decoder = FrameDecoder::new();
+            decoder.set_validation(header::ValidationConfig { max_length: u32::MAX, ..Default::default() });
+            let mut decoded = Vec::new();
+            for chunk in stream.chunks(chunk_len) {
+                let result = decoder.decode(chunk);
+                assert!(result.errors.is_empty(), "{:?}", result.errors);
+                decoded.extend(result.packets);
+            }
+            assert_eq!(decoded, packets);
+            assert!(!decoder.is_mid_frame());
+        }
+
+        let mut static_decoder = StaticFrameDecoder::<256>::new();
+        let (used, frame) = static_decoder.poll(&stream);
+        assert_eq!(used, header::HEADER_LEN); // Ping's varint header is nine bytes like v1
+        assert_eq!(frame.unwrap().unwrap().opcode(), crate::packet::OPCODE_PING);
+    }
+
+    #[test]
+    fn header_crc_rejects_a_corrupted_length_without_waiting_for_it() {
+        let mut stream = Vec::new();
+        for payload in [vec![1; 10], vec![2; 10]] {
+            let packet = Packet::Data(payload);
+            codec::encode_flagged(&packet, header::Flags::HEADER_CRC, &mut stream, ChecksumMode::Enabled).unwrap();
+        }
+        stream[4] ^= 0x80; // First frame now claims 32778 bytes
+
+        let mut decoder = FrameDecoder::new();
+        let result = decoder.decode(&stream);
+        assert!(matches!(result.errors[0], FrameError::Header(header::HeaderError::HeaderCrcMismatch)));
+        assert_eq!(result.packets, [Packet::Data(vec![2; 10])]);
+        assert!(!decoder.is_mid_frame());
+    }
+
+    #[test]
+    fn accepts_only_negotiated_versions() {
+        let payload = [9u8; 3];
+        let mut newer = header::Header::new(packet::OPCODE_DATA, 3, crate::checksum::fnv1a32(&payload))
+            .with_version(1)
+            .to_bytes()
+            .to_vec();
+        newer.extend_from_slice(&payload);
+        let mut stream = encode(&Packet::hello());
+        stream.extend_from_slice(&newer);
+        stream.extend_from_slice(&encode(&Packet::Pong));
+
+        let output = FrameDecoder::new().decode(&stream);
+        assert_eq!(output.packets, vec![Packet::hello(), Packet::Pong]);
+        assert!(matches!(output.errors[..], [FrameError::Header(header::HeaderError::UnsupportedVersion(1))]));
+
+        let (frames, errors) = poll_all(&mut StaticFrameDecoder::<16>::new(), &stream);
+        let opcodes: Vec<u8> = frames.iter().map(|(opcode, _)| *opcode).collect();
+        assert_eq!(opcodes, [packet::OPCODE_HELLO, packet::OPCODE_PONG]);
+        assert_eq!(errors, [StaticFrameError::Header(header::HeaderError::UnsupportedVersion(1))]);
+
+        let (version, _) = packet::negotiate((1, packet::SUPPORTED_FEATURES), (3, 0));
+        let mut decoder = FrameDecoder::new();
+        decoder.set_validation(header::ValidationConfig { max_version: version, ..Default::default() });
+        let output = decoder.decode(&stream);
+        assert!(output.errors.is_empty());
+        assert_eq!(output.packets, vec![Packet::hello(), Packet::Data(vec![9; 3]), Packet::Pong]);
+    }
+
+    #[test]
+    fn skips_frames_failing_validation() {
+        let mut stream = encode(&packet::Packet::Data(vec![0; 32]));
+        stream.extend_from_slice(&encode(&packet::Packet::Data(vec![1; 8])));
+
+        let mut decoder = FrameDecoder::new();
+        decoder.set_validation(header::ValidationConfig { max_length: 16, ..Default::default() });
+        let output = decoder.decode(&stream);
+
+        assert_eq!(output.packets, vec![packet::Packet::Data(vec![1; 8])]);
+        assert!(matches!(
+            output.errors[..],
+            [FrameError::Header(header::HeaderError::LengthExceeded { length: 32, max: 16 })]
+        ));
+    }
+
+    #[test]
+    fn budgeted_decode_stops_at_frame_limit() {
+        let mut stream = Vec::new();
+        for i in 0..5 {
+            stream.extend_from_slice(&encode(&packet::Packet::Data(vec![i])));
+        }
+        stream.extend_from_slice(&encode(&packet::Packet::Ping)[..4]); // Partial trailing frame
+
+        let mut decoder = FrameDecoder::new();
+        let (output, rest) = decoder.decode_budgeted(&stream, DecodeBudget::Frames(2));
+        assert_eq!(output.packets, vec![packet::Packet::Data(vec![0]), packet::Packet::Data(vec![1])]);
+        assert_eq!(rest.len(), stream.len() - 2 * encode(&packet::Packet::Data(vec![0])).len());
+
+        let (output, rest) = decoder.decode_budgeted(rest, DecodeBudget::Frames(10));
+        assert_eq!(output.packets.len(), 3);
+        assert!(rest.is_empty());
+        assert!(decoder.is_mid_frame());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn budgeted_decode_stops_when_time_runs_out() {
+        use crate::clock::MockClock;
+
+        /// Every reading of the clock costs a millisecond.
+        struct Ticking(MockClock);
+
+        impl Clock for Ticking {
+            fn now(&self) -> std::time::Instant {
+                let now = self.0.now();
+                self.0.advance(Duration::from_millis(1));
+                now
+            }
+        }
+
+        let stream: Vec<u8> = (0..10).flat_map(|_| encode(&packet::Packet::Ping)).collect();
+        let clock = Ticking(MockClock::new());
+        let (output, rest) = FrameDecoder::new()
+            .decode_budgeted_with_clock(&stream, DecodeBudget::Time(Duration::from_millis(3)), &clock);
+        assert_eq!(output.packets.len(), 3);
+        assert_eq!(rest.len(), 7 * header::HEADER_LEN);
+    }
+
+    /// Drain `input` through `decoder`, collecting opcodes/payloads and errors.
+    fn poll_all<const N: usize>(
+        decoder: &mut StaticFrameDecoder<N>,
+        mut input: &[u8],
+    ) -> (Vec<(u8, Vec<u8>)>, Vec<StaticFrameError>) {
+        let (mut frames, mut errors) = (Vec::new(), Vec::new());
+        loop {
+            let (used, item) = decoder.poll(input);
+            input = &input[used..];
+            match item {
+                Some(Ok(frame)) => frames.push((frame.opcode(), frame.payload.to_vec())),
+                Some(Err(err)) => errors.push(err),
+                None => return (frames, errors),
+            }
+        }
+    }
+
+    #[test]
+    fn static_decoder_handles_split_input_and_resyncs() {
+        let mut stream = vec![0x00, 0x11]; // Leading garbage
+        stream.extend_from_slice(&encode(&Packet::Data(vec![1, 2, 3])));
+        stream.extend_from_slice(&encode(&Packet::Pong));
+
+        let mut decoder = StaticFrameDecoder::<8>::new();
+        let mut frames = Vec::new();
+        let mut errors = Vec::new();
+        for chunk in stream.chunks(4) {
+            let (mut chunk_frames, mut chunk_errors) = poll_all(&mut decoder, chunk);
+            frames.append(&mut chunk_frames);
+            errors.append(&mut chunk_errors);
+        }
+
+        assert_eq!(frames, vec![(packet::OPCODE_DATA, vec![1, 2, 3]), (packet::OPCODE_PONG, vec![])]);
+        assert_eq!(errors.len(), 2);
+        assert!(matches!(errors[0], StaticFrameError::Header(header::HeaderError::InvalidMagic(_))));
+        assert!(!decoder.is_mid_frame());
+    }
+
+    #[test]
+    fn feed_decodes_dma_halves_in_turn() {
+        let mut dma_buf = encode(&Packet::Data(vec![7; 6])); // The frame straddles both halves
+        dma_buf.extend_from_slice(&encode(&Packet::Ping));
+        assert_eq!(dma_buf.len(), 24);
+
+        let halves = DmaHalves::new();
+        let mut decoder = StaticFrameDecoder::<8>::new();
+        let mut frames = Vec::new();
+        let mut delivered = Vec::new();
+        for half in [Half::First, Half::Second] {
+            halves.complete(half); // Half-transfer, then transfer-complete interrupt
+            let ready = halves.take().unwrap();
+            assert_eq!(ready, half);
+            delivered.push(decoder.feed(&dma_buf[ready.range(dma_buf.len())], |frame| {
+                let frame = frame.unwrap();
+                frames.push((frame.opcode(), frame.payload.to_vec()));
+            }));
+        }
+
+        assert_eq!(delivered, [0, 2]);
+        assert_eq!(frames, vec![(packet::OPCODE_DATA, vec![7; 6]), (packet::OPCODE_PING, vec![])]);
+        assert!(halves.take().is_none());
+        assert!(!halves.take_overrun());
+    }
+
+    #[test]
+    fn static_decoder_skips_oversized_and_corrupt_frames() {
+        let mut stream = encode(&Packet::Data(vec![9; 20]));
+        let mut damaged = encode(&Packet::Data(vec![1, 2]));
+        let last = damaged.len() - 1;
+        damaged[last] ^= 0xFF;
+        stream.extend_from_slice(&damaged);
+        stream.extend_from_slice(&encode(&Packet::Data(vec![5; 16])));
+
+        let (frames, errors) = poll_all(&mut StaticFrameDecoder::<16>::new(), &stream);
+        assert_eq!(frames, vec![(packet::OPCODE_DATA, vec![5; 16])]);
+        assert!(matches!(
+            errors[..],
+            [StaticFrameError::TooLarge { length: 20, capacity: 16 }, StaticFrameError::ChecksumMismatch { .. }]
+        ));
+    }
+
+    #[test]
+    fn raw_frame_converts_to_packet() {
+        let stream = encode(&Packet::Batch(vec![Packet::Ping, Packet::Data(vec![7])]));
+        let mut decoder = StaticFrameDecoder::<32>::new();
+        let (_, frame) = decoder.poll(&stream);
+        let packet = frame.unwrap().unwrap().to_packet().unwrap();
+        assert_eq!(packet, Packet::Batch(vec![Packet::Ping, Packet::Data(vec![7])]));
+    }
+
+    #[test]
+    fn tracks_resync_statistics() {
+        let mut stream = vec![0x00, 0x11, 0x22]; // leading garbage
+        stream.extend_from_slice(&encode(&packet::Packet::Ping));
+        let mut damaged = encode(&packet::Packet::Data(vec![1, 2, 3]));
+        let last = damaged.len() - 1;
+        damaged[last] ^= 0xFF;
+        stream.extend_from_slice(&damaged);
+        stream.extend_from_slice(&encode(&packet::Packet::Pong));
+
+        let mut decoder = FrameDecoder::new();
+        decoder.decode(&stream);
+        let stats = decoder.resync_stats();
+
+        assert_eq!(stats.bytes_skipped, 3);
+        assert_eq!(stats.checksum_failures, 1);
+        assert_eq!(stats.frames_ok, 2);
+        assert_eq!(stats.frames_recovered, 2);
+        assert!(stats.link_quality() < 1.0);
+    }
+
+    #[test]
+    fn records_frame_sizes() {
+        let mut stream = encode(&packet::Packet::Ping);
+        stream.extend_from_slice(&encode(&packet::Packet::Data(vec![0; 100])));
+
+        let mut decoder = FrameDecoder::new();
+        decoder.decode(&stream);
+
+        assert_eq!(decoder.frame_sizes().count(), 2);
+        assert_eq!(decoder.frame_sizes().max(), header::HEADER_LEN + 100);
+    }
+
+    #[test]
+    fn skips_payloads_over_budget() {
+        let budget = MemoryBudget::new(4);
+        let mut stream = encode(&packet::Packet::Data(vec![0; 8]));
+        stream.extend_from_slice(&encode(&packet::Packet::Data(vec![1, 2, 3])));
+
+        let mut decoder = FrameDecoder::with_budget(budget.clone());
+        let output = decoder.decode(&stream);
+
+        assert_eq!(output.packets, vec![packet::Packet::Data(vec![1, 2, 3])]);
+        assert!(matches!(output.errors[..], [FrameError::BudgetExceeded { requested: 8 }]));
+        assert_eq!(budget.used(), 0);
+    }
+
+    #[test]
+    fn releases_reservation_on_drop() {
+        let budget = MemoryBudget::new(64);
+        let stream = encode(&packet::Packet::Data(vec![7; 16]));
+
+        let mut decoder = FrameDecoder::with_budget(budget.clone());
+        decoder.decode(&stream[..stream.len() - 1]);
+        assert_eq!(budget.used(), 16);
+
+        drop(decoder);
+        assert_eq!(budget.used(), 0);
+    }
+
+    #[test]
+    fn push_byte_yields_each_frame_once() {
+        let mut stream = vec![0x00]; // Stray byte ahead of the frames
+        stream.extend(encode(&Packet::Data(vec![4, 5])));
+        stream.extend(encode(&Packet::Batch(vec![Packet::Ping, Packet::Pong])));
+
+        let mut decoder = FrameDecoder::new();
+        let outcomes: Vec<_> = stream.iter().filter_map(|&byte| decoder.push_byte(byte)).collect();
+        assert!(matches!(outcomes[..], [Err(FrameError::InvalidMagic(_)), Ok(_), Ok(_)]));
+        assert_eq!(outcomes[1].as_ref().unwrap(), &Packet::Data(vec![4, 5]));
+        assert_eq!(outcomes[2].as_ref().unwrap(), &Packet::Batch(vec![Packet::Ping, Packet::Pong]));
+
+        let mut outcome = None;
+        FrameSink::error(&mut outcome, FrameError::InvalidMagic(0));
+        FrameSink::packet(&mut outcome, Packet::Ping);
+        assert!(matches!(outcome, Some(Err(FrameError::InvalidMagic(0)))));
+    }
+
+    /// Valid frames with random damage: flipped bits, random bytes and cuts.
+    fn hostile_stream(rng: &mut SeededRng) -> Vec<u8> {
+        let mut stream = Vec::new();
+        for _ in 0..rng.below(12) {
+            match rng.below(4) {
+                0 => stream.extend(encode(&Packet::Data(vec![rng.below(256) as u8; rng.below(40) as usize]))),
+                1 => stream.extend(encode(&Packet::Batch(vec![Packet::Ping, Packet::Data(vec![1; 3])]))),
+                2 => {
+                    let mut noise = vec![0u8; rng.below(24) as usize];
+                    rng.fill_bytes(&mut noise);
+                    stream.extend(noise);
+                }
+                _ => stream.extend(header::Header::new(rng.below(8) as u8, rng.below(400) as u32, 0).to_bytes()),
+            }
+        }
+        for _ in 0..rng.below(6) {
+            if !stream.is_empty() {
+                let at = rng.below(stream.len() as u64) as usize;
+                stream[at] ^= 1 << rng.below(8);
+            }
+        }
+        stream
+    }
+
+    /// No input sequence may panic any decoding path, and a decoder must
+    /// still decode a good frame after the garbage once it is reset.
+    #[test]
+    fn hostile_input_never_panics_any_decoder() {
+        let mut rng = SeededRng::new(0x5EED);
+        let good = encode(&Packet::Data(vec![7, 7]));
+        for _ in 0..2_000 {
+            let stream = hostile_stream(&mut rng);
+            let split = rng.below(stream.len() as u64 + 1) as usize;
+
+            let mut decoders = [FrameDecoder::new(), FrameDecoder::with_budget(MemoryBudget::new(32)), FrameDecoder::new()];
+            decoders[1].set_checksum_mode(ChecksumMode::Disabled);
+            decoders[2].set_validation(header::ValidationConfig { max_length: 16, ..Default::default() });
+            for decoder in &mut decoders {
+                let _ = decoder.decode(&stream[..split]);
+                let _ = decoder.decode_budgeted(&stream[split..], DecodeBudget::Frames(2));
+                decoder.reset();
+                assert_eq!(decoder.decode(&good).packets, [Packet::Data(vec![7, 7])]);
+            }
+
+            let mut slip = FrameDecoder::new();
+            slip.set_framing(Framing::Slip);
+            let _ = slip.decode(&stream);
+
+            let mut byte_wise = FrameDecoder::new();
+            stream.iter().for_each(|&byte| drop(byte_wise.push_byte(byte)));
+
+            let mut fixed = StaticFrameDecoder::<16>::new();
+            let mut input = &stream[..];
+            while !input.is_empty() {
+                let (used, _) = fixed.poll(input);
+                input = &input[used..];
+            }
+        }
+    }
+
+    fn slip(packet: &Packet) -> Vec<u8> {
+        let mut buf = Vec::new();
+        Framing::Slip.encode_with(packet, &mut buf, ChecksumMode::Enabled).unwrap();
+        buf
+    }
+
+    #[test]
+    fn slip_round_trips_escaped_bytes() {
+        let packet = Packet::Data(alloc::vec![SLIP_END, 1, SLIP_ESC, SLIP_END]);
+        let wire = slip(&packet);
+        assert_eq!(wire.iter().filter(|&&byte| byte == SLIP_END).count(), 2); // Only the delimiters
+
+        let mut decoder = FrameDecoder::new();
+        decoder.set_framing(Framing::Slip);
+        let mut packets = Vec::new();
+        for byte in &wire {
+            packets.extend(decoder.decode(core::slice::from_ref(byte)).packets);
+        }
+        assert_eq!(packets, [packet]);
+        assert!(!decoder.is_mid_frame());
+    }
+
+    #[test]
+    fn slip_resyncs_at_next_end() {
+        let mut corrupted = slip(&Packet::Data(alloc::vec![7; 20]));
+        corrupted.truncate(12); // Lose the tail of the frame and its END
+        corrupted.extend_from_slice(&[SLIP_ESC, 0x01]); // Bad escape while discarding
+        corrupted.extend(slip(&Packet::Ping));
+        corrupted.extend(slip(&Packet::Pong));
+
+        let mut decoder = FrameDecoder::new();
+        decoder.set_framing(Framing::Slip);
+        let result = decoder.decode(&corrupted);
+        assert_eq!(result.packets, [Packet::Ping, Packet::Pong]); // Ping's leading END closed the damaged frame
+        assert_eq!(result.errors.len(), 1);
+        assert!(matches!(result.errors[0], FrameError::InvalidEscape(0x01)));
+        assert_eq!(decoder.resync_stats().frames_recovered, 1);
+    }
+
+    #[test]
+    fn slip_frames_draw_on_the_budget_and_respect_max_length() {
+        let budget = MemoryBudget::new(SLIP_RESERVE_STEP);
+        let mut decoder = FrameDecoder::with_budget(budget.clone());
+        decoder.set_framing(Framing::Slip);
+        let mut stream = slip(&Packet::Data(alloc::vec![1; 300]));
+        stream.extend(slip(&Packet::Data(alloc::vec![2; 30])));
+        let result = decoder.decode(&stream);
+        assert!(matches!(result.errors[..], [FrameError::BudgetExceeded { requested: 257 }]));
+        assert_eq!(result.packets, [Packet::Data(alloc::vec![2; 30])]);
+        assert_eq!(budget.used(), 0);
+
+        let mut decoder = FrameDecoder::new();
+        decoder.set_framing(Framing::Slip);
+        decoder.set_validation(header::ValidationConfig { max_length: 8, ..Default::default() });
+        let result = decoder.decode(&slip(&Packet::Data(alloc::vec![3; 100])));
+        assert!(matches!(result.errors[..], [FrameError::Codec(CodecError::PayloadTooLarge(_))]));
+        assert!(!decoder.is_mid_frame());
+    }
+}
+
+/// Kani proof harnesses; run with `cargo kani`.
+#[cfg(kani)]
+mod proofs {
+    use super::*;
+
+    /// Any input, split at any point, decodes without panicking or indexing
+    /// out of bounds. Twelve bytes cover a header plus a short payload.
+    #[kani::proof]
+    #[kani::unwind(14)]
+    fn decode_never_panics() {
+        let bytes: [u8; 12] = kani::any();
+        let split: usize = kani::any();
+        kani::assume(split <= bytes.len());
+
+        let mut decoder = FrameDecoder::new();
+        let _ = decoder.decode(&bytes[..split]);
+        let _ = decoder.decode(&bytes[split..]);
+    }
+
+    /// A frame that declares more payload than arrived leaves the decoder mid-frame.
+    #[kani::proof]
+    #[kani::unwind(14)]
+    fn short_payload_stays_buffered() {
+        let opcode: u8 = kani::any();
+        let length: u16 = kani::any();
+        kani::assume(opcode <= 0x0F && length > 2);
+        let mut bytes = header::Header::new(opcode, length.into(), kani::any()).to_bytes().to_vec();
+        bytes.extend_from_slice(&[kani::any(), kani::any()]);
+
+        let mut decoder = FrameDecoder::new();
+        let result = decoder.decode(&bytes);
+        assert!(result.packets.is_empty());
+        assert!(decoder.is_mid_frame());
+    }
 }
@@ -1,32 +1,282 @@
 //! Header definition and serialization helpers.
+//!
+//! Frames without flags use the original 9-byte layout under
+//! [`HEADER_MAGIC`], byte for byte as before flags existed. A frame with
+//! flags uses [`HEADER_MAGIC_FLAGGED`] and a 10-byte layout with a flags
+//! byte after the opcode, so decoders tell the two apart by magic alone.
+//! Payloads over 65535 bytes need the 12-byte v2 layout under
+//! [`HEADER_MAGIC_V2`], which carries flags and a `u32` length; the other
+//! layouts are used whenever the length fits in a `u16`.
+//!
+//! A sender may instead opt into the varint layout under
+//! [`HEADER_MAGIC_VARINT`]: flags, then the length as LEB128 (1 to 5
+//! bytes), then the checksum. Frames up to 127 bytes spend one byte on
+//! their length, and no size needs a different layout.
+//!
+//! Any layout with a flags byte can end in a CRC-8 of the header's other
+//! bytes, announced by [`Flags::HEADER_CRC`]. A bit flip in the length is
+//! then caught at the header instead of after the payload it misdescribes.
+//!
+//! In every layout the opcode byte holds the protocol version in its high
+//! nibble and the opcode in its low nibble. Every frame from before
+//! versioning has a zero high nibble, which is version 0.
+
+use core::ops::{BitOr, RangeInclusive};
+
+use crate::checksum::crc8;
+use crate::codec::FrameFormat;
+use crate::packet::{OPCODE_HELLO, OPCODE_PING};
 
 /// Magic value that prefixes every header.
 pub const HEADER_MAGIC: u16 = 0xAA55;
 /// Total number of bytes taken by the header.
 pub const HEADER_LEN: usize = 9;
+/// Magic value of a header carrying [`Flags`].
+pub const HEADER_MAGIC_FLAGGED: u16 = 0xAA56;
+/// Bytes taken by a header carrying [`Flags`].
+pub const FLAGGED_HEADER_LEN: usize = 10;
+/// Magic value of a header with a 32-bit length.
+pub const HEADER_MAGIC_V2: u16 = 0xAA57;
+/// Bytes taken by a header with a 32-bit length.
+pub const V2_HEADER_LEN: usize = 12;
+/// Magic value of a header with a LEB128 length.
+pub const HEADER_MAGIC_VARINT: u16 = 0xAA58;
+/// Bytes taken by a varint header whose length needs all five LEB128 bytes.
+pub const VARINT_MAX_HEADER_LEN: usize = 8 + MAX_VARINT_LEN;
+/// Most bytes a LEB128-encoded `u32` takes.
+pub const MAX_VARINT_LEN: usize = 5;
+/// Largest header of any layout, for sizing buffers.
+pub const MAX_HEADER_LEN: usize = VARINT_MAX_HEADER_LEN + 1; // Plus a header CRC
+/// Largest payload accepted unless a [`ValidationConfig`] raises the limit.
+///
+/// Matches what the 16-bit layouts can carry, so a peer cannot make a
+/// decoder buffer toward a multi-gigabyte v2 or varint length by default.
+pub const DEFAULT_MAX_LENGTH: u32 = u16::MAX as u32;
+/// Protocol version this crate speaks and sends.
+pub const PROTOCOL_VERSION: u8 = 0;
+/// Highest version the 4-bit field can carry.
+pub const MAX_VERSION: u8 = 0x0F;
+
+/// Bytes a frame of `format` with `flags` adds on top of a payload of
+/// `payload_len` bytes. Only the varint layout, and v2 past 65535 bytes,
+/// depend on the length.
+pub const fn frame_overhead(format: FrameFormat, flags: Flags, payload_len: usize) -> usize {
+    let length = if payload_len > u32::MAX as usize { u32::MAX } else { payload_len as u32 };
+    format.header(0, length, 0).with_flags(flags).wire_len()
+}
+
+/// Per-frame flag bits carried by the flagged header layout.
+///
+/// `COMPRESSED`, `ENCRYPTED` and `FRAGMENTED` say the payload was
+/// transformed by a layer above the codec; the codec only carries them.
+/// `HEADER_CRC` is handled by the header itself. The two priority bits are
+/// advisory. Bits 4 and 5 are reserved and must be zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Flags(u8);
+
+impl Flags {
+    pub const COMPRESSED: Flags = Flags(0x01);
+    pub const ENCRYPTED: Flags = Flags(0x02);
+    pub const FRAGMENTED: Flags = Flags(0x04);
+    /// The header ends with a CRC-8 of its other bytes.
+    pub const HEADER_CRC: Flags = Flags(0x08);
+    const PRIORITY_SHIFT: u32 = 6;
+
+    pub const fn empty() -> Self {
+        Flags(0)
+    }
+
+    pub const fn from_bits(bits: u8) -> Self {
+        Flags(bits)
+    }
+
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn contains(self, other: Flags) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Priority from 0 (lowest) to 3.
+    pub const fn priority(self) -> u8 {
+        self.0 >> Self::PRIORITY_SHIFT
+    }
+
+    /// Set the priority; values above 3 are clamped.
+    pub const fn with_priority(self, priority: u8) -> Self {
+        let priority = if priority > 3 { 3 } else { priority };
+        Flags(self.0 & !(0b11 << Self::PRIORITY_SHIFT) | priority << Self::PRIORITY_SHIFT)
+    }
+
+    /// Whether a payload with these flags can be read as a plain packet:
+    /// no transform or reserved bit is set, only priority and `HEADER_CRC`.
+    pub const fn is_plain(self) -> bool {
+        let payload_bits = self.0 & !Self::HEADER_CRC.0;
+        payload_bits >> Self::PRIORITY_SHIFT << Self::PRIORITY_SHIFT == payload_bits
+    }
+}
+
+impl BitOr for Flags {
+    type Output = Flags;
+
+    fn bitor(self, rhs: Flags) -> Flags {
+        Flags(self.0 | rhs.0)
+    }
+}
 
 /// Wire header for every packet.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Header {
     pub magic: u16,
+    /// Protocol version, 0 to [`MAX_VERSION`].
+    pub version: u8,
+    /// Opcode, 0 to 0x0F on the wire.
     pub opcode: u8,
-    pub length: u16,
+    /// Always empty in the 9-byte layout.
+    pub flags: Flags,
+    /// Payload length; only the v2 layout carries more than 16 bits.
+    pub length: u32,
     pub checksum: u32,
 }
 
 impl Header {
-    /// Build a header using the protocol's fixed magic value.
-    pub fn new(opcode: u8, length: u16, checksum: u32) -> Self {
+    /// Build a header using [`PROTOCOL_VERSION`] and the smallest layout
+    /// that holds `length`.
+    ///
+    /// `opcode` must fit the low nibble of the opcode byte; debug builds
+    /// panic on anything above 0x0F.
+    pub const fn new(opcode: u8, length: u32, checksum: u32) -> Self {
+        debug_assert!(opcode <= 0x0F, "opcode does not fit in a nibble");
         Self {
-            magic: HEADER_MAGIC,
+            magic: layout_magic(Flags::empty(), length),
+            version: PROTOCOL_VERSION,
             opcode,
+            flags: Flags::empty(),
             length,
             checksum,
         }
     }
 
-    /// Serialize the header into network byte order.
-    pub fn to_bytes(&self) -> [u8; HEADER_LEN] {
+    /// Set `flags`, switching to the flagged layout unless they are empty
+    /// or the length already needs v2. A varint header stays varint.
+    pub const fn with_flags(mut self, flags: Flags) -> Self {
+        self.flags = flags;
+        if self.magic != HEADER_MAGIC_VARINT {
+            self.magic = layout_magic(flags, self.length);
+        }
+        self
+    }
+
+    /// Switch to the varint layout, whatever the length and flags.
+    pub const fn with_varint_length(mut self) -> Self {
+        self.magic = HEADER_MAGIC_VARINT;
+        self
+    }
+
+    /// Set the version nibble; debug builds panic above [`MAX_VERSION`].
+    pub const fn with_version(mut self, version: u8) -> Self {
+        debug_assert!(version <= MAX_VERSION, "version does not fit in a nibble");
+        self.version = version;
+        self
+    }
+
+    /// The shared version/opcode byte. Fields set directly rather than
+    /// through the constructors are checked here, in debug builds.
+    const fn opcode_byte(&self) -> u8 {
+        debug_assert!(self.version <= MAX_VERSION && self.opcode <= 0x0F, "version or opcode does not fit in a nibble");
+        self.version << 4 | self.opcode
+    }
+
+    /// Bytes this header takes on the wire.
+    pub const fn wire_len(&self) -> usize {
+        let crc_len = self.has_crc() as usize;
+        match self.magic {
+            HEADER_MAGIC_FLAGGED => FLAGGED_HEADER_LEN + crc_len,
+            HEADER_MAGIC_V2 => V2_HEADER_LEN + crc_len,
+            HEADER_MAGIC_VARINT => 8 + varint_len(self.length) + crc_len,
+            _ => HEADER_LEN,
+        }
+    }
+
+    /// Whether the header ends with a CRC; the 9-byte layout has no flags
+    /// to announce one.
+    const fn has_crc(&self) -> bool {
+        self.magic != HEADER_MAGIC && self.flags.contains(Flags::HEADER_CRC)
+    }
+
+    /// Append the header in whichever layout its magic selects, followed
+    /// by its CRC if the flags ask for one.
+    pub fn write_to(&self, buf: &mut alloc::vec::Vec<u8>) {
+        let start = buf.len();
+        match self.magic {
+            HEADER_MAGIC_FLAGGED => buf.extend_from_slice(&self.to_flagged_bytes()),
+            HEADER_MAGIC_V2 => buf.extend_from_slice(&self.to_v2_bytes()),
+            HEADER_MAGIC_VARINT => {
+                buf.extend_from_slice(&self.magic.to_be_bytes());
+                buf.extend_from_slice(&[self.opcode_byte(), self.flags.bits()]);
+                let mut length = self.length;
+                while length >= 0x80 {
+                    buf.push(length as u8 | 0x80);
+                    length >>= 7;
+                }
+                buf.push(length as u8);
+                buf.extend_from_slice(&self.checksum.to_be_bytes());
+            }
+            _ => buf.extend_from_slice(&self.to_bytes()),
+        }
+        if self.has_crc() {
+            buf.push(crc8(&buf[start..]));
+        }
+    }
+
+    /// Serialize in the 12-byte v2 layout, without any header CRC.
+    pub const fn to_v2_bytes(&self) -> [u8; V2_HEADER_LEN] {
+        let [m0, m1] = self.magic.to_be_bytes();
+        let [l0, l1, l2, l3] = self.length.to_be_bytes();
+        let [c0, c1, c2, c3] = self.checksum.to_be_bytes();
+        [m0, m1, self.opcode_byte(), self.flags.bits(), l0, l1, l2, l3, c0, c1, c2, c3]
+    }
+
+    /// Serialize in the 10-byte flagged layout, without any header CRC.
+    pub const fn to_flagged_bytes(&self) -> [u8; FLAGGED_HEADER_LEN] {
+        let plain = self.to_bytes();
+        let mut bytes = [0u8; FLAGGED_HEADER_LEN];
+        bytes[0] = plain[0];
+        bytes[1] = plain[1];
+        bytes[2] = plain[2];
+        bytes[3] = self.flags.bits();
+        let mut i = 3;
+        while i < HEADER_LEN { // Length and checksum move one byte right
+            bytes[i + 1] = plain[i];
+            i += 1;
+        }
+        bytes
+    }
+
+    /// Serialize the header into network byte order, in the 9-byte layout.
+    ///
+    /// Flags are not part of this layout; use [`write_to`](Self::write_to)
+    /// for headers that may carry them.
+    ///
+    /// Together with [`Header::new`] and [`fnv1a32`](crate::checksum::fnv1a32)
+    /// this works in `const` items, so fixed frames can be built at compile time:
+    ///
+    /// ```
+    /// use byteframe::checksum::fnv1a32;
+    /// use byteframe::header::Header;
+    /// use byteframe::packet::OPCODE_PING;
+    ///
+    /// const PING: [u8; 9] = Header::new(OPCODE_PING, 0, fnv1a32(&[])).to_bytes();
+    /// assert_eq!(byteframe::decode(&PING).unwrap(), byteframe::Packet::Ping);
+    /// ```
+    pub const fn to_bytes(&self) -> [u8; HEADER_LEN] {
         let mut bytes = [0u8; HEADER_LEN];
 
         // Magic (2 bytes)
@@ -34,11 +284,11 @@ impl Header {
         bytes[0] = magic_bytes[0];
         bytes[1] = magic_bytes[1];
         
-        // Opcode (1 byte)
-        bytes[2] = self.opcode;
+        // Version and opcode (1 byte, a nibble each)
+        bytes[2] = self.opcode_byte();
         
         // Length (2 bytes)
-        let length_bytes: [u8; 2] = self.length.to_be_bytes();
+        let length_bytes: [u8; 2] = (self.length as u16).to_be_bytes();
         bytes[3] = length_bytes[0];
         bytes[4] = length_bytes[1];
         
@@ -53,34 +303,197 @@ impl Header {
     }
 
     /// Deserialize a header from raw bytes.
+    ///
+    /// Any layout is accepted; the longer ones need all their bytes and
+    /// report `ShortBuffer` until they are there. A header announcing a CRC
+    /// that does not match fails with `HeaderCrcMismatch`.
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, HeaderError> {
-        if bytes.len() < HEADER_LEN {
+        let Some(&[b0, b1, b2, b3, b4, b5, b6, b7, b8]) = bytes.get(..HEADER_LEN) else {
             return Err(HeaderError::ShortBuffer(bytes.len()));
+        };
+        let magic = u16::from_be_bytes([b0, b1]);
+        let (flags, length, checksum) = match magic {
+            HEADER_MAGIC_FLAGGED => {
+                let Some(&b9) = bytes.get(HEADER_LEN) else {
+                    return Err(HeaderError::ShortBuffer(bytes.len()));
+                };
+                (b3, u16::from_be_bytes([b4, b5]) as u32, u32::from_be_bytes([b6, b7, b8, b9]))
+            }
+            HEADER_MAGIC_V2 => {
+                let Some(&[b9, b10, b11]) = bytes.get(HEADER_LEN..V2_HEADER_LEN) else {
+                    return Err(HeaderError::ShortBuffer(bytes.len()));
+                };
+                (b3, u32::from_be_bytes([b4, b5, b6, b7]), u32::from_be_bytes([b8, b9, b10, b11]))
+            }
+            HEADER_MAGIC_VARINT => {
+                let (length, varint_len) = read_varint(&bytes[4..])?;
+                let end = 4 + varint_len;
+                let Some(&[c0, c1, c2, c3]) = bytes.get(end..end + 4) else {
+                    return Err(HeaderError::ShortBuffer(bytes.len()));
+                };
+                (b3, length, u32::from_be_bytes([c0, c1, c2, c3]))
+            }
+            _ => return Self::from_bytes_const(&[b0, b1, b2, b3, b4, b5, b6, b7, b8]),
+        };
+        let header = Self {
+            magic,
+            version: b2 >> 4,
+            opcode: b2 & 0x0F,
+            flags: Flags(flags),
+            length,
+            checksum,
+        };
+        if header.has_crc() {
+            let covered = header.wire_len() - 1;
+            let Some(&crc) = bytes.get(covered) else {
+                return Err(HeaderError::ShortBuffer(bytes.len()));
+            };
+            if crc != crc8(&bytes[..covered]) {
+                return Err(HeaderError::HeaderCrcMismatch);
+            }
         }
+        Ok(header)
+    }
 
+    /// [`from_bytes`](Self::from_bytes) for a fixed-size buffer, usable in `const` contexts.
+    ///
+    /// Nine bytes hold the unflagged layout, and a varint header whose
+    /// length fits in one byte (under 128) and that carries no CRC. Any
+    /// longer header reports `ShortBuffer`.
+    pub const fn from_bytes_const(bytes: &[u8; HEADER_LEN]) -> Result<Self, HeaderError> {
         let magic = u16::from_be_bytes([bytes[0], bytes[1]]);
+        let version = bytes[2] >> 4;
+        let opcode = bytes[2] & 0x0F;
+        let checksum = u32::from_be_bytes([bytes[5], bytes[6], bytes[7], bytes[8]]);
+        if magic == HEADER_MAGIC_VARINT {
+            let flags = Flags(bytes[3]);
+            if bytes[4] >= 0x80 || flags.contains(Flags::HEADER_CRC) { // Length or CRC runs past nine bytes
+                return Err(HeaderError::ShortBuffer(HEADER_LEN));
+            }
+            return Ok(Self { magic, version, opcode, flags, length: bytes[4] as u32, checksum });
+        }
+        if magic == HEADER_MAGIC_FLAGGED || magic == HEADER_MAGIC_V2 {
+            return Err(HeaderError::ShortBuffer(HEADER_LEN));
+        }
         if magic != HEADER_MAGIC {
             return Err(HeaderError::InvalidMagic(magic));
         }
 
-        let opcode = bytes[2];
-        let length = u16::from_be_bytes([bytes[3], bytes[4]]);
-        let checksum = u32::from_be_bytes([bytes[5], bytes[6], bytes[7], bytes[8]]);
+        let length = u16::from_be_bytes([bytes[3], bytes[4]]) as u32;
 
         Ok(Self {
             magic,
+            version,
             opcode,
+            flags: Flags::empty(),
             length,
             checksum,
         })
     }
+
+    /// Check the header against `config` before any payload is buffered.
+    ///
+    /// Every ingestion path should call this so that length caps and opcode
+    /// rules are enforced identically wherever frames enter the process.
+    pub fn validate(&self, config: &ValidationConfig) -> Result<(), HeaderError> {
+        if !matches!(self.magic, HEADER_MAGIC | HEADER_MAGIC_FLAGGED | HEADER_MAGIC_V2 | HEADER_MAGIC_VARINT) {
+            return Err(HeaderError::InvalidMagic(self.magic));
+        }
+        if self.version > config.max_version {
+            return Err(HeaderError::UnsupportedVersion(self.version));
+        }
+        if self.length > config.max_length {
+            return Err(HeaderError::LengthExceeded { length: self.length, max: config.max_length });
+        }
+        if !config.opcodes.contains(&self.opcode) {
+            return Err(HeaderError::UnknownOpcode(self.opcode));
+        }
+        Ok(())
+    }
+}
+
+/// Magic for the smallest layout carrying `flags` and `length`.
+const fn layout_magic(flags: Flags, length: u32) -> u16 {
+    if length > u16::MAX as u32 {
+        HEADER_MAGIC_V2
+    } else if !flags.is_empty() {
+        HEADER_MAGIC_FLAGGED
+    } else {
+        HEADER_MAGIC
+    }
+}
+
+/// Bytes `length` takes as LEB128.
+pub const fn varint_len(length: u32) -> usize {
+    match length {
+        0..=0x7F => 1,
+        0x80..=0x3FFF => 2,
+        0x4000..=0x1F_FFFF => 3,
+        0x20_0000..=0x0FFF_FFFF => 4,
+        _ => MAX_VARINT_LEN,
+    }
+}
+
+/// Decode a LEB128 `u32` from the front of `bytes`, returning it and the
+/// bytes it took.
+///
+/// `ShortBuffer` means the last byte so far still had its continuation
+/// bit set. `InvalidVarint` means the value would not fit in 32 bits or
+/// was padded with zero groups, which would make
+/// [`wire_len`](Header::wire_len) disagree with the bytes read.
+fn read_varint(bytes: &[u8]) -> Result<(u32, usize), HeaderError> {
+    let mut value = 0u32;
+    for (i, &byte) in bytes.iter().take(MAX_VARINT_LEN).enumerate() {
+        if i == MAX_VARINT_LEN - 1 && byte > 0x0F { // Only four bits left, and no continuation
+            return Err(HeaderError::InvalidVarint);
+        }
+        value |= u32::from(byte & 0x7F) << (7 * i);
+        if byte & 0x80 == 0 {
+            if byte == 0 && i > 0 { // Overlong: the frame would re-encode shorter
+                return Err(HeaderError::InvalidVarint);
+            }
+            return Ok((value, i + 1));
+        }
+    }
+    Err(HeaderError::ShortBuffer(4 + bytes.len()))
+}
+
+/// Rules applied by [`Header::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationConfig {
+    /// Largest payload length accepted.
+    pub max_length: u32,
+    /// Opcodes accepted; everything outside the range is rejected.
+    pub opcodes: RangeInclusive<u8>,
+    /// Highest protocol version accepted.
+    pub max_version: u8,
+}
+
+impl Default for ValidationConfig {
+    /// Accept up to [`DEFAULT_MAX_LENGTH`] bytes and every opcode this crate knows.
+    fn default() -> Self {
+        Self {
+            max_length: DEFAULT_MAX_LENGTH,
+            opcodes: OPCODE_PING..=OPCODE_HELLO,
+            max_version: PROTOCOL_VERSION,
+        }
+    }
 }
 
 /// Errors while parsing the header from bytes.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum HeaderError {
     ShortBuffer(usize),
     InvalidMagic(u16),
+    LengthExceeded { length: u32, max: u32 },
+    UnknownOpcode(u8),
+    /// The frame's version is above what this side accepts.
+    UnsupportedVersion(u8),
+    /// A varint length overflowed 32 bits or was not in its shortest form.
+    InvalidVarint,
+    /// The header's own CRC did not match; its fields cannot be trusted.
+    HeaderCrcMismatch,
 }
 
 impl core::fmt::Display for HeaderError {
@@ -88,27 +501,127 @@ impl core::fmt::Display for HeaderError {
         match self {
             HeaderError::ShortBuffer(len) => write!(f, "buffer length {len} < header size {}", HEADER_LEN),
             HeaderError::InvalidMagic(value) => write!(f, "invalid header magic 0x{value:04X}"),
+            HeaderError::LengthExceeded { length, max } => write!(f, "payload length {length} exceeds limit {max}"),
+            HeaderError::UnknownOpcode(opcode) => write!(f, "unknown opcode 0x{opcode:02X}"),
+            HeaderError::UnsupportedVersion(version) => write!(f, "unsupported protocol version {version}"),
+            HeaderError::InvalidVarint => write!(f, "malformed varint length"),
+            HeaderError::HeaderCrcMismatch => write!(f, "header CRC mismatch"),
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for HeaderError {}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::packet::OPCODE_BATCH;
 
     #[test]
     fn round_trips_header_bytes() {
-        let header = Header::new(0x10, 42, 0xDEADBEEF);
+        let header = Header::new(0x0A, 42, 0xDEADBEEF);
         let bytes = header.to_bytes();
         let decoded = Header::from_bytes(&bytes).unwrap();
         assert_eq!(decoded.magic, HEADER_MAGIC);
-        assert_eq!(decoded.opcode, 0x10);
+        assert_eq!(decoded.opcode, 0x0A);
         assert_eq!(decoded.length, 42);
         assert_eq!(decoded.checksum, 0xDEADBEEF);
     }
 
+    #[test]
+    fn header_round_trips_in_const_context() {
+        const BYTES: [u8; HEADER_LEN] = Header::new(OPCODE_BATCH, 3, 0x01020304).to_bytes();
+        const PARSED: Result<Header, HeaderError> = Header::from_bytes_const(&BYTES);
+        assert_eq!(PARSED, Ok(Header::new(OPCODE_BATCH, 3, 0x01020304)));
+        assert_eq!(Header::from_bytes(&BYTES[..8]), Err(HeaderError::ShortBuffer(8)));
+
+        let mut varint = Vec::new();
+        Header::new(OPCODE_BATCH, 100, 0x01020304).with_varint_length().with_flags(Flags::COMPRESSED).write_to(&mut varint);
+        let nine: [u8; HEADER_LEN] = varint[..].try_into().unwrap();
+        assert_eq!(Header::from_bytes_const(&nine), Header::from_bytes(&varint));
+
+        varint.clear();
+        Header::new(OPCODE_BATCH, 200, 0).with_varint_length().write_to(&mut varint);
+        let first_nine: [u8; HEADER_LEN] = varint[..HEADER_LEN].try_into().unwrap();
+        assert_eq!(Header::from_bytes_const(&first_nine), Err(HeaderError::ShortBuffer(HEADER_LEN)));
+    }
+
+    #[test]
+    fn flagged_layout_round_trips_and_unflagged_stays_nine_bytes() {
+        let flags = (Flags::COMPRESSED | Flags::FRAGMENTED).with_priority(2);
+        let header = Header::new(OPCODE_BATCH, 300, 0xCAFEF00D).with_flags(flags);
+        let mut bytes = Vec::new();
+        header.write_to(&mut bytes);
+        assert_eq!(bytes.len(), FLAGGED_HEADER_LEN);
+        assert_eq!(bytes[3], 0x85);
+        assert_eq!(Header::from_bytes(&bytes), Ok(header));
+        assert_eq!(Header::from_bytes(&bytes[..HEADER_LEN]), Err(HeaderError::ShortBuffer(HEADER_LEN)));
+        assert_eq!(flags.priority(), 2);
+        assert!(!flags.is_plain() && Flags::empty().with_priority(3).is_plain());
+
+        let plain = Header::new(OPCODE_BATCH, 300, 0xCAFEF00D).with_flags(Flags::empty());
+        bytes.clear();
+        plain.write_to(&mut bytes);
+        assert_eq!(bytes, plain.to_bytes());
+    }
+
+    #[test]
+    fn long_lengths_pick_the_v2_layout() {
+        let header = Header::new(OPCODE_BATCH, 0x0001_0000, 0xCAFEF00D).with_flags(Flags::empty().with_priority(1));
+        assert_eq!(header.magic, HEADER_MAGIC_V2);
+        let mut bytes = Vec::new();
+        header.write_to(&mut bytes);
+        assert_eq!(bytes, [0xAA, 0x57, 0x05, 0x40, 0x00, 0x01, 0x00, 0x00, 0xCA, 0xFE, 0xF0, 0x0D]);
+        assert_eq!(Header::from_bytes(&bytes), Ok(header));
+        assert_eq!(Header::from_bytes(&bytes[..11]), Err(HeaderError::ShortBuffer(11)));
+        assert_eq!(Header::new(OPCODE_BATCH, u16::MAX as u32, 0).magic, HEADER_MAGIC);
+    }
+
+    #[test]
+    fn varint_layout_spends_one_length_byte_on_small_frames() {
+        let header = Header::new(OPCODE_BATCH, 100, 0xCAFEF00D).with_varint_length();
+        let mut bytes = Vec::new();
+        header.write_to(&mut bytes);
+        assert_eq!(bytes, [0xAA, 0x58, 0x05, 0x00, 0x64, 0xCA, 0xFE, 0xF0, 0x0D]);
+        assert_eq!(Header::from_bytes(&bytes), Ok(header));
+
+        for length in [0x80, 0x3FFF, 0x4000, 0x0010_0000, u32::MAX] {
+            let header = Header::new(OPCODE_BATCH, length, 7).with_varint_length().with_flags(Flags::COMPRESSED);
+            bytes.clear();
+            header.write_to(&mut bytes);
+            assert_eq!(bytes.len(), header.wire_len());
+            assert_eq!(Header::from_bytes(&bytes), Ok(header));
+            assert!(matches!(Header::from_bytes(&bytes[..bytes.len() - 1]), Err(HeaderError::ShortBuffer(_))));
+        }
+        assert_eq!(bytes.len(), VARINT_MAX_HEADER_LEN);
+
+        bytes[8] = 0x10; // Fifth length byte with more than four bits
+        assert_eq!(Header::from_bytes(&bytes), Err(HeaderError::InvalidVarint));
+        let overlong = [0xAA, 0x58, 0x05, 0x00, 0x80, 0x00, 0, 0, 0, 0];
+        assert_eq!(Header::from_bytes(&overlong), Err(HeaderError::InvalidVarint));
+    }
+
+    #[test]
+    fn header_crc_catches_a_flipped_length_bit() {
+        for header in [
+            Header::new(OPCODE_BATCH, 300, 0xCAFEF00D).with_flags(Flags::HEADER_CRC),
+            Header::new(OPCODE_BATCH, 0x0001_0000, 0xCAFEF00D).with_flags(Flags::HEADER_CRC),
+            Header::new(OPCODE_BATCH, 300, 0xCAFEF00D).with_varint_length().with_flags(Flags::HEADER_CRC),
+        ] {
+            let mut bytes = Vec::new();
+            header.write_to(&mut bytes);
+            assert_eq!(bytes.len(), header.wire_len());
+            assert_eq!(*bytes.last().unwrap(), crc8(&bytes[..bytes.len() - 1]));
+            assert_eq!(Header::from_bytes(&bytes), Ok(header));
+            assert!(matches!(Header::from_bytes(&bytes[..bytes.len() - 1]), Err(HeaderError::ShortBuffer(_))));
+
+            bytes[5] ^= 0x01; // Inside the length
+            assert_eq!(Header::from_bytes(&bytes), Err(HeaderError::HeaderCrcMismatch));
+        }
+        assert!(Flags::HEADER_CRC.with_priority(1).is_plain());
+    }
+
     #[test]
     fn rejects_wrong_magic() {
         let mut bytes = Header::new(1, 0, 0).to_bytes();
@@ -117,6 +630,30 @@ mod tests {
         assert!(matches!(err, HeaderError::InvalidMagic(_)));
     }
 
+    #[test]
+    fn validates_length_and_opcode() {
+        let config = ValidationConfig { max_length: 100, ..ValidationConfig::default() };
+        assert!(Header::new(OPCODE_PING, 100, 0).validate(&config).is_ok());
+        assert_eq!(
+            Header::new(OPCODE_PING, 101, 0).validate(&config),
+            Err(HeaderError::LengthExceeded { length: 101, max: 100 })
+        );
+        let out_of_range = Header { opcode: 0x7F, ..Header::new(OPCODE_PING, 0, 0) };
+        assert_eq!(out_of_range.validate(&config), Err(HeaderError::UnknownOpcode(0x7F)));
+    }
+
+    #[test]
+    fn version_shares_the_opcode_byte() {
+        let header = Header::new(OPCODE_BATCH, 0, 0).with_version(3);
+        let bytes = header.to_bytes();
+        assert_eq!(bytes[2], 0x35);
+        assert_eq!(Header::from_bytes(&bytes), Ok(header));
+
+        let config = ValidationConfig::default();
+        assert_eq!(header.validate(&config), Err(HeaderError::UnsupportedVersion(3)));
+        assert!(header.validate(&ValidationConfig { max_version: 3, ..config }).is_ok());
+    }
+
     #[test]
     fn detects_short_buffer() {
         let bytes = [0u8; 4];
@@ -124,3 +661,34 @@ mod tests {
         assert!(matches!(err, HeaderError::ShortBuffer(4)));
     }
 }
+
+/// Kani proof harnesses; run with `cargo kani`.
+#[cfg(kani)]
+mod proofs {
+    use super::*;
+
+    #[kani::proof]
+    fn from_bytes_never_panics() {
+        let bytes: [u8; MAX_HEADER_LEN + 1] = kani::any();
+        let len: usize = kani::any();
+        kani::assume(len <= bytes.len());
+        let _ = Header::from_bytes(&bytes[..len]);
+    }
+
+    #[kani::proof]
+    fn to_bytes_round_trips() {
+        let (version, opcode): (u8, u8) = (kani::any(), kani::any());
+        kani::assume(version <= MAX_VERSION && opcode <= 0x0F);
+        let length: u16 = kani::any();
+        let header = Header::new(opcode, length.into(), kani::any()).with_version(version);
+        assert_eq!(Header::from_bytes(&header.to_bytes()), Ok(header));
+    }
+
+    #[kani::proof]
+    fn parsed_headers_reencode_identically() {
+        let bytes: [u8; HEADER_LEN] = kani::any();
+        if let Ok(header) = Header::from_bytes(&bytes) {
+            assert_eq!(header.to_bytes(), bytes);
+        }
+    }
+}
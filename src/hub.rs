@@ -0,0 +1,200 @@
+//! Fan-out of packets to many async connections (`tokio` feature).
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadHalf};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::task::JoinHandle;
+
+use crate::async_io::{AsyncPacketReader, AsyncPacketWriter, Connection};
+use crate::packet::Packet;
+
+/// Identifies a peer added to a [`Hub`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PeerId(u64);
+
+/// Owns the write side of many connections and sends to them concurrently.
+///
+/// Every peer has its own writer task fed by a bounded queue of
+/// `queue_capacity` packets. [`broadcast`](Self::broadcast) never waits:
+/// a peer whose queue is full is too slow to keep up and is disconnected,
+/// so one stalled client cannot hold up the rest. Reading stays with the
+/// caller; [`add_connection`](Self::add_connection) hands back the read half.
+pub struct Hub {
+    peers: Mutex<HashMap<PeerId, Peer>>,
+    next_id: Mutex<u64>,
+    queue_capacity: usize,
+}
+
+struct Peer {
+    queue: mpsc::Sender<Arc<Packet>>,
+    task: JoinHandle<()>,
+}
+
+impl Drop for Peer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl Hub {
+    /// Create a hub whose peers each queue up to `queue_capacity` packets.
+    pub fn new(queue_capacity: usize) -> Self {
+        Self {
+            peers: Mutex::new(HashMap::new()),
+            next_id: Mutex::new(0),
+            queue_capacity: queue_capacity.max(1),
+        }
+    }
+
+    /// Take over `writer` and start its writer task.
+    pub fn add<W>(&self, writer: AsyncPacketWriter<W>) -> PeerId
+    where
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        let (queue, packets) = mpsc::channel(self.queue_capacity);
+        let task = tokio::spawn(write_loop(writer, packets));
+        let id = {
+            let mut next_id = self.next_id.lock().unwrap();
+            *next_id += 1;
+            PeerId(*next_id)
+        };
+        self.peers.lock().unwrap().insert(id, Peer { queue, task });
+        id
+    }
+
+    /// Add the write half of `connection` and return its read half.
+    pub fn add_connection<T>(&self, connection: Connection<T>) -> (PeerId, AsyncPacketReader<ReadHalf<T>>)
+    where
+        T: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let (reader, writer) = connection.split();
+        (self.add(writer), reader)
+    }
+
+    /// Queue `packet` for every peer and return how many accepted it.
+    ///
+    /// Peers with a full queue or a failed connection are removed.
+    pub fn broadcast(&self, packet: &Packet) -> usize {
+        let packet = Arc::new(packet.clone());
+        let mut peers = self.peers.lock().unwrap();
+        peers.retain(|_, peer| peer.queue.try_send(Arc::clone(&packet)).is_ok());
+        peers.len()
+    }
+
+    /// Queue `packet` for one peer, waiting while its queue is full.
+    ///
+    /// Fails with `NotFound` for an unknown or removed peer and with
+    /// `BrokenPipe` if its connection has failed.
+    pub async fn send(&self, peer: PeerId, packet: &Packet) -> io::Result<()> {
+        let queue = match self.peers.lock().unwrap().get(&peer) {
+            Some(peer) => peer.queue.clone(),
+            None => return Err(io::Error::new(io::ErrorKind::NotFound, "unknown peer")),
+        };
+        if queue.send(Arc::new(packet.clone())).await.is_err() {
+            self.remove(peer);
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, "peer connection closed"));
+        }
+        Ok(())
+    }
+
+    /// Queue `packet` for one peer without waiting.
+    ///
+    /// Unlike [`broadcast`](Self::broadcast), a full queue is reported as
+    /// `WouldBlock` and the peer is kept.
+    pub fn try_send(&self, peer: PeerId, packet: &Packet) -> io::Result<()> {
+        let mut peers = self.peers.lock().unwrap();
+        let Some(entry) = peers.get(&peer) else {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "unknown peer"));
+        };
+        match entry.queue.try_send(Arc::new(packet.clone())) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => Err(io::Error::new(io::ErrorKind::WouldBlock, "peer queue full")),
+            Err(TrySendError::Closed(_)) => {
+                peers.remove(&peer);
+                Err(io::Error::new(io::ErrorKind::BrokenPipe, "peer connection closed"))
+            }
+        }
+    }
+
+    /// Disconnect `peer`, discarding anything still queued for it.
+    pub fn remove(&self, peer: PeerId) -> bool {
+        self.peers.lock().unwrap().remove(&peer).is_some()
+    }
+
+    pub fn contains(&self, peer: PeerId) -> bool {
+        self.peers.lock().unwrap().contains_key(&peer)
+    }
+
+    /// Number of connected peers.
+    pub fn len(&self) -> usize {
+        self.peers.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Write queued packets, flushing whenever the queue runs dry.
+async fn write_loop<W: AsyncWrite + Unpin>(mut writer: AsyncPacketWriter<W>, mut packets: mpsc::Receiver<Arc<Packet>>) {
+    while let Some(packet) = packets.recv().await {
+        if writer.write_packet(&packet).await.is_err() {
+            return;
+        }
+        while let Ok(packet) = packets.try_recv() {
+            if writer.write_packet(&packet).await.is_err() {
+                return;
+            }
+        }
+        if writer.flush().await.is_err() {
+            return;
+        }
+    }
+    let _ = writer.close().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::DuplexStream;
+
+    fn peer(hub: &Hub, buffer: usize) -> (PeerId, Connection<DuplexStream>) {
+        let (local, remote) = tokio::io::duplex(buffer);
+        let (id, _reader) = hub.add_connection(Connection::new(local));
+        (id, Connection::new(remote))
+    }
+
+    #[tokio::test]
+    async fn broadcast_and_targeted_send() {
+        let hub = Hub::new(8);
+        let (first, mut a) = peer(&hub, 1024);
+        let (_, mut b) = peer(&hub, 1024);
+
+        assert_eq!(hub.broadcast(&Packet::Ping), 2);
+        hub.send(first, &Packet::Data(vec![1])).await.unwrap();
+
+        assert_eq!(a.read_packet().await.unwrap(), Packet::Ping);
+        assert_eq!(a.read_packet().await.unwrap(), Packet::Data(vec![1]));
+        assert_eq!(b.read_packet().await.unwrap(), Packet::Ping);
+    }
+
+    #[tokio::test]
+    async fn slow_peer_is_dropped_without_stalling_others() {
+        let hub = Hub::new(2);
+        let (fast, mut fast_remote) = peer(&hub, 64 * 1024);
+        let (slow, _slow_remote) = peer(&hub, 16); // Never read
+
+        let payload = Packet::Data(vec![0; 100]);
+        for _ in 0..20 {
+            hub.broadcast(&payload);
+            assert_eq!(fast_remote.read_packet().await.unwrap(), payload);
+        }
+
+        assert!(hub.contains(fast));
+        assert!(!hub.contains(slow));
+        assert_eq!(hub.send(slow, &Packet::Ping).await.unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,233 @@
+//! Liveness checking with periodic pings (`tokio` feature).
+
+use std::io;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadHalf, WriteHalf};
+use tokio::sync::{watch, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::{self, Instant, MissedTickBehavior};
+
+use crate::async_io::{AsyncPacketReader, AsyncPacketWriter};
+use crate::packet::Packet;
+
+/// Timing for [`Keepalive`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeepaliveConfig {
+    /// Time between pings.
+    pub interval: Duration,
+    /// Declare the peer dead after this long without a `Pong`.
+    pub timeout: Duration,
+}
+
+impl Default for KeepaliveConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(15),
+            timeout: Duration::from_secs(45),
+        }
+    }
+}
+
+/// The peer stopped answering pings, or a ping could not be written
+/// before the timeout ran out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeadPeer {
+    /// Time since the last `Pong` (or since the keepalive started).
+    pub silent_for: Duration,
+}
+
+/// Background task that pings the peer and watches for `Pong` replies.
+///
+/// The peer must answer `Ping` with `Pong`, as `examples/simple_echo.rs`
+/// does. Feed every received packet to [`observe`](Self::observe). The
+/// task stops when the keepalive is dropped or the peer is declared dead.
+pub struct Keepalive {
+    last_pong: Arc<StdMutex<Instant>>,
+    dead: watch::Receiver<Option<DeadPeer>>,
+    task: JoinHandle<()>,
+}
+
+impl Keepalive {
+    /// Start pinging through `writer`, which stays usable for other packets.
+    pub fn spawn<W>(writer: Arc<Mutex<AsyncPacketWriter<W>>>, config: KeepaliveConfig) -> Self
+    where
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        let last_pong = Arc::new(StdMutex::new(Instant::now()));
+        let (dead_tx, dead) = watch::channel(None);
+        let task = tokio::spawn(ping_loop(writer, config, Arc::clone(&last_pong), dead_tx));
+        Self { last_pong, dead, task }
+    }
+
+    /// Record a received packet; a `Pong` proves the peer is alive.
+    pub fn observe(&self, packet: &Packet) {
+        if *packet == Packet::Pong {
+            *self.last_pong.lock().unwrap() = Instant::now();
+        }
+    }
+
+    /// `Some` once the peer has been declared dead.
+    pub fn dead_peer(&self) -> Option<DeadPeer> {
+        *self.dead.borrow()
+    }
+
+    /// Wait until the peer is declared dead.
+    pub async fn wait_dead(&self) -> DeadPeer {
+        let mut dead = self.dead.clone();
+        loop {
+            if let Some(event) = *dead.borrow_and_update() {
+                return event;
+            }
+            if dead.changed().await.is_err() {
+                // Task ended without a verdict; only happens if it panicked
+                return DeadPeer { silent_for: self.last_pong.lock().unwrap().elapsed() };
+            }
+        }
+    }
+}
+
+impl Drop for Keepalive {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn ping_loop<W: AsyncWrite + Unpin>(
+    writer: Arc<Mutex<AsyncPacketWriter<W>>>,
+    config: KeepaliveConfig,
+    last_pong: Arc<StdMutex<Instant>>,
+    dead: watch::Sender<Option<DeadPeer>>,
+) {
+    let mut ticks = time::interval(config.interval);
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        ticks.tick().await;
+        let silent_for = last_pong.lock().unwrap().elapsed();
+        if silent_for >= config.timeout {
+            dead.send_replace(Some(DeadPeer { silent_for }));
+            return;
+        }
+        let ping = async {
+            let mut writer = writer.lock().await;
+            writer.write_packet(&Packet::Ping).await?;
+            writer.flush().await
+        };
+        // A peer that stopped reading can stall the write forever; it is as dead as a silent one
+        if !matches!(time::timeout(config.timeout.saturating_sub(silent_for), ping).await, Ok(Ok(()))) {
+            let silent_for = last_pong.lock().unwrap().elapsed();
+            dead.send_replace(Some(DeadPeer { silent_for }));
+            return;
+        }
+    }
+}
+
+/// A [`Connection`](crate::async_io::Connection) with keepalive attached;
+/// see [`Connection::keepalive`](crate::async_io::Connection::keepalive).
+pub struct KeepaliveConnection<T> {
+    reader: AsyncPacketReader<ReadHalf<T>>,
+    writer: Arc<Mutex<AsyncPacketWriter<WriteHalf<T>>>>,
+    keepalive: Keepalive,
+}
+
+impl<T: AsyncRead + AsyncWrite + Send + 'static> KeepaliveConnection<T> {
+    pub(crate) fn new(
+        reader: AsyncPacketReader<ReadHalf<T>>,
+        writer: AsyncPacketWriter<WriteHalf<T>>,
+        config: KeepaliveConfig,
+    ) -> Self {
+        let writer = Arc::new(Mutex::new(writer));
+        let keepalive = Keepalive::spawn(Arc::clone(&writer), config);
+        Self { reader, writer, keepalive }
+    }
+
+    /// Read the next packet, failing with `TimedOut` once the peer is dead.
+    ///
+    /// Every packet, including `Pong` replies to keepalive pings, is returned.
+    pub async fn read_packet(&mut self) -> io::Result<Packet> {
+        tokio::select! {
+            result = self.reader.read_packet() => {
+                let packet = result?;
+                self.keepalive.observe(&packet);
+                Ok(packet)
+            }
+            dead = self.keepalive.wait_dead() => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("peer unresponsive for {:?}", dead.silent_for),
+            )),
+        }
+    }
+
+    pub async fn write_packet(&self, packet: &Packet) -> io::Result<()> {
+        let mut writer = self.writer.lock().await;
+        writer.write_packet(packet).await?;
+        writer.flush().await
+    }
+
+    /// The shared writer, for sending from other tasks.
+    pub fn writer(&self) -> Arc<Mutex<AsyncPacketWriter<WriteHalf<T>>>> {
+        Arc::clone(&self.writer)
+    }
+
+    pub fn keepalive(&self) -> &Keepalive {
+        &self.keepalive
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::async_io::Connection;
+
+    const CONFIG: KeepaliveConfig = KeepaliveConfig {
+        interval: Duration::from_secs(1),
+        timeout: Duration::from_secs(3),
+    };
+
+    #[tokio::test(start_paused = true)]
+    async fn answered_pings_keep_peer_alive() {
+        let (client, server) = tokio::io::duplex(256);
+        let mut connection = Connection::new(client).keepalive(CONFIG);
+        tokio::spawn(async move {
+            let mut server = Connection::new(server);
+            while let Ok(packet) = server.read_packet().await {
+                if packet == Packet::Ping {
+                    server.write_packet(&Packet::Pong).await.unwrap();
+                }
+            }
+        });
+
+        for _ in 0..10 {
+            assert_eq!(connection.read_packet().await.unwrap(), Packet::Pong);
+        }
+        assert_eq!(connection.keepalive().dead_peer(), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn silent_peer_is_declared_dead() {
+        let (client, server) = tokio::io::duplex(256);
+        let mut connection = Connection::new(client).keepalive(CONFIG);
+        tokio::spawn(async move {
+            let mut server = Connection::new(server);
+            while server.read_packet().await.is_ok() {} // Swallow pings
+        });
+
+        let started = Instant::now();
+        let err = connection.read_packet().await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert_eq!(started.elapsed(), Duration::from_secs(3));
+        assert_eq!(connection.keepalive().dead_peer(), Some(DeadPeer { silent_for: Duration::from_secs(3) }));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn peer_that_never_reads_is_declared_dead() {
+        let (client, _server) = tokio::io::duplex(4); // Too small for one ping, and never drained
+        let mut connection = Connection::new(client).keepalive(CONFIG);
+
+        let err = connection.read_packet().await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert_eq!(connection.keepalive().dead_peer(), Some(DeadPeer { silent_for: Duration::from_secs(3) }));
+        drop(connection.writer().lock().await); // The stalled ping gave the writer back
+    }
+}
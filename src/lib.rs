@@ -1,19 +1,101 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+#![forbid(unsafe_code)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod batch;
+pub mod budget;
 pub mod checksum;
+#[cfg(feature = "std")]
+pub mod clock;
 pub mod codec;
+pub mod diff;
+pub mod dma;
+#[cfg(feature = "std")]
+pub mod extensions;
+#[cfg(feature = "embedded-io")]
+pub mod embedded;
 pub mod framing;
 pub mod header;
 pub mod packet;
+pub mod pool;
+pub mod profile;
+#[cfg(feature = "prometheus")]
+pub mod prometheus;
+pub mod rng;
+pub mod snapshot;
+pub mod state;
+pub mod stats;
+#[cfg(feature = "postcard")]
+pub mod structured;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 // Optional I/O helpers (require std::io)
+#[cfg(feature = "io")]
 pub mod reader;
+#[cfg(feature = "io")]
 pub mod writer;
+#[cfg(feature = "io")]
+pub mod trace;
+#[cfg(feature = "io")]
+pub mod net;
+#[cfg(feature = "tokio")]
+pub mod async_io;
+#[cfg(feature = "tokio")]
+pub mod client;
+#[cfg(feature = "tokio")]
+pub mod events;
+#[cfg(feature = "tokio")]
+pub mod hub;
+#[cfg(feature = "tokio")]
+pub mod keepalive;
+#[cfg(feature = "tokio")]
+pub mod server;
+
+// Test utilities for exercising live connections
+#[cfg(feature = "io")]
+pub mod scenario;
+#[cfg(feature = "io")]
+pub mod soak;
+#[cfg(feature = "std")]
+pub mod testing;
 
+#[cfg(feature = "std")]
+pub use batch::{BatchConfig, Batcher};
+pub use budget::MemoryBudget;
 pub use checksum::fnv1a32;
-pub use codec::{decode, encode, CodecError};
-pub use framing::{FrameDecoder, FrameError, DecodeResult};
-pub use header::{Header, HeaderError, HEADER_LEN, HEADER_MAGIC};
-pub use packet::Packet;
-pub use reader::PacketReader;
-pub use writer::PacketWriter;
+#[cfg(feature = "std")]
+pub use clock::{Clock, MockClock, SystemClock};
+pub use codec::{
+    decode, decode_flagged, decode_ref, encode, encode_flagged, encode_format, encode_into, encode_ref, encoded_len,
+    ChecksumMode, CodecError, FrameFormat,
+};
+#[cfg(feature = "embedded-io")]
+pub use embedded::{EmbeddedError, EmbeddedPacketReader, EmbeddedPacketWriter};
+pub use framing::{DecodeBudget, DecodeResult, FrameDecoder, FrameError, Framing, StaticFrameDecoder};
+pub use header::{
+    frame_overhead, Flags, Header, HeaderError, ValidationConfig, DEFAULT_MAX_LENGTH, FLAGGED_HEADER_LEN, HEADER_LEN,
+    HEADER_MAGIC, HEADER_MAGIC_FLAGGED, HEADER_MAGIC_V2, HEADER_MAGIC_VARINT, MAX_HEADER_LEN, MAX_VERSION,
+    PROTOCOL_VERSION, V2_HEADER_LEN, VARINT_MAX_HEADER_LEN,
+};
+pub use packet::{Packet, PacketRef};
+pub use pool::{DecoderKey, DecoderPool};
+pub use profile::{Compact, Profile, WireProfile};
+#[cfg(any(feature = "getrandom", all(feature = "std", unix)))]
+pub use rng::OsRng;
+pub use rng::{Rng, SeededRng};
+pub use snapshot::{SnapshotReceiver, SnapshotSender};
+pub use state::ConnectionState;
+pub use stats::{FrameSizeHistogram, ResyncStats};
+#[cfg(feature = "io")]
+pub use trace::{SchedulingTrace, TraceEvent, TraceKind};
+#[cfg(feature = "io")]
+pub use reader::{AdaptiveBuffer, FrameTimeout, PacketReader, ReadOpts, VectoredRead};
+#[cfg(feature = "io")]
+pub use writer::{PacketWriter, WriteOpts};
+#[cfg(feature = "io")]
+pub use net::connect_dual_stack;
 
 
@@ -0,0 +1,214 @@
+//! Recommended socket settings for byteframe connections.
+
+use std::io;
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+/// Delay before starting the next connection attempt (RFC 8305 recommends 250 ms).
+pub const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+/// Upper bound on each individual connection attempt.
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Socket options applied by [`configure`].
+///
+/// The protocol sends many small frames, so Nagle's algorithm (which holds
+/// small writes back waiting for ACKs) is disabled by default. OS keepalive
+/// and kernel buffer sizes are not reachable through `std`; setting them
+/// requires the `socket2` feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SocketOptions {
+    /// Set `TCP_NODELAY`.
+    pub nodelay: bool,
+    /// Enable OS keepalive probes after this much idle time (`socket2` feature).
+    pub keepalive: Option<Duration>,
+    /// `SO_RCVBUF` size in bytes (`socket2` feature).
+    pub recv_buffer_size: Option<usize>,
+    /// `SO_SNDBUF` size in bytes (`socket2` feature).
+    pub send_buffer_size: Option<usize>,
+}
+
+impl Default for SocketOptions {
+    fn default() -> Self {
+        Self {
+            nodelay: true,
+            keepalive: None,
+            recv_buffer_size: None,
+            send_buffer_size: None,
+        }
+    }
+}
+
+/// Apply `options` to a connected stream.
+///
+/// # Errors
+///
+/// Returns the OS error if a setting is rejected, or
+/// `io::ErrorKind::Unsupported` if keepalive or buffer sizes are requested
+/// without the `socket2` feature.
+pub fn configure(stream: &TcpStream, options: &SocketOptions) -> io::Result<()> {
+    stream.set_nodelay(options.nodelay)?;
+    configure_extended(stream, options)
+}
+
+#[cfg(feature = "socket2")]
+fn configure_extended(stream: &TcpStream, options: &SocketOptions) -> io::Result<()> {
+    let socket = socket2::SockRef::from(stream);
+    if let Some(idle) = options.keepalive {
+        socket.set_tcp_keepalive(&socket2::TcpKeepalive::new().with_time(idle))?;
+    }
+    if let Some(size) = options.recv_buffer_size {
+        socket.set_recv_buffer_size(size)?;
+    }
+    if let Some(size) = options.send_buffer_size {
+        socket.set_send_buffer_size(size)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "socket2"))]
+fn configure_extended(_stream: &TcpStream, options: &SocketOptions) -> io::Result<()> {
+    if options.keepalive.is_some() || options.recv_buffer_size.is_some() || options.send_buffer_size.is_some() {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "keepalive and buffer sizes require the `socket2` feature",
+        ));
+    }
+    Ok(())
+}
+
+/// Connect to `host:port`, racing IPv6 and IPv4 addresses (Happy Eyeballs, RFC 8305).
+///
+/// Addresses are tried alternating between families, IPv6 first. A new
+/// attempt starts every [`CONNECTION_ATTEMPT_DELAY`] or as soon as the
+/// previous one fails, and the first connection to succeed wins; the rest
+/// are dropped. This keeps clients responsive on hosts whose IPv6 route is
+/// advertised but broken.
+///
+/// Wrap the returned stream in [`PacketReader`](crate::reader::PacketReader)
+/// and [`PacketWriter`](crate::writer::PacketWriter) as usual.
+///
+/// # Errors
+///
+/// Returns the resolver error, or the last connection error if every address failed.
+pub fn connect_dual_stack(host: &str, port: u16) -> io::Result<TcpStream> {
+    let addrs = interleave_families((host, port).to_socket_addrs()?.collect());
+    if addrs.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, format!("no addresses for {host}")));
+    }
+
+    let (results, outcomes) = mpsc::channel();
+    let mut next = addrs.into_iter();
+    let mut in_flight = 0;
+    let mut last_error = None;
+
+    loop {
+        if let Some(addr) = next.next() {
+            let results = results.clone();
+            in_flight += 1;
+            thread::spawn(move || {
+                let _ = results.send(TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)); // losers are dropped
+            });
+        } else if in_flight == 0 {
+            return Err(last_error.expect("at least one attempt was made"));
+        }
+
+        let outcome = if next.len() > 0 {
+            match outcomes.recv_timeout(CONNECTION_ATTEMPT_DELAY) {
+                Ok(outcome) => outcome,
+                Err(_) => continue, // Delay elapsed: start the next attempt
+            }
+        } else {
+            outcomes.recv().expect("attempt threads hold a sender")
+        };
+
+        in_flight -= 1;
+        match outcome {
+            Ok(stream) => return Ok(stream),
+            Err(err) => last_error = Some(err),
+        }
+    }
+}
+
+/// Order addresses IPv6, IPv4, IPv6, ... keeping resolver order within each family.
+fn interleave_families(addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let (v6, v4): (Vec<_>, Vec<_>) = addrs.into_iter().partition(SocketAddr::is_ipv6);
+    let mut v6 = v6.into_iter();
+    let mut v4 = v4.into_iter();
+    let mut ordered = Vec::new();
+    loop {
+        match (v6.next(), v4.next()) {
+            (None, None) => return ordered,
+            (a, b) => ordered.extend(a.into_iter().chain(b)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    fn connected_pair() -> (TcpStream, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        (client, server)
+    }
+
+    #[test]
+    fn interleaves_ipv6_first() {
+        let addrs: Vec<SocketAddr> = ["10.0.0.1:1", "10.0.0.2:1", "[::1]:1"]
+            .iter()
+            .map(|a| a.parse().unwrap())
+            .collect();
+        let ordered = interleave_families(addrs.clone());
+        assert_eq!(ordered, vec![addrs[2], addrs[0], addrs[1]]);
+    }
+
+    #[test]
+    fn dual_stack_connects_to_listening_address() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let stream = connect_dual_stack("127.0.0.1", port).unwrap();
+        assert_eq!(stream.peer_addr().unwrap().port(), port);
+    }
+
+    #[test]
+    fn dual_stack_reports_failure_when_all_attempts_fail() {
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port(); // closed again
+        assert!(connect_dual_stack("127.0.0.1", port).is_err());
+    }
+
+    #[test]
+    fn disables_nagle_by_default() {
+        let (client, _server) = connected_pair();
+        configure(&client, &SocketOptions::default()).unwrap();
+        assert!(client.nodelay().unwrap());
+    }
+
+    #[cfg(not(feature = "socket2"))]
+    #[test]
+    fn extended_options_need_socket2() {
+        let (client, _server) = connected_pair();
+        let options = SocketOptions { keepalive: Some(Duration::from_secs(30)), ..SocketOptions::default() };
+        let err = configure(&client, &options).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[cfg(feature = "socket2")]
+    #[test]
+    fn applies_keepalive_and_buffer_sizes() {
+        let (client, _server) = connected_pair();
+        let options = SocketOptions {
+            keepalive: Some(Duration::from_secs(30)),
+            recv_buffer_size: Some(64 * 1024),
+            ..SocketOptions::default()
+        };
+        configure(&client, &options).unwrap();
+        let socket = socket2::SockRef::from(&client);
+        assert!(socket.keepalive().unwrap());
+        assert!(socket.recv_buffer_size().unwrap() >= 64 * 1024);
+    }
+}
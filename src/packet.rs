@@ -1,18 +1,46 @@
 //! High-level packet definitions.
 
+#[cfg(feature = "text")]
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::header::PROTOCOL_VERSION;
+
 /// Opcodes assigned to each packet variant.
 pub const OPCODE_PING: u8 = 0x01;
 pub const OPCODE_PONG: u8 = 0x02;
 pub const OPCODE_MESSAGE: u8 = 0x03;
 pub const OPCODE_DATA: u8 = 0x04;
+pub const OPCODE_BATCH: u8 = 0x05;
+pub const OPCODE_HELLO: u8 = 0x06;
+
+/// `Hello` feature bit: the sender understands flagged headers.
+pub const FEATURE_FLAGS: u32 = 0x01;
+/// `Hello` feature bit: the sender understands v2 headers with 32-bit lengths.
+pub const FEATURE_V2_LENGTH: u32 = 0x02;
+/// `Hello` feature bit: the sender understands varint-length headers.
+pub const FEATURE_VARINT_LENGTH: u32 = 0x04;
+/// `Hello` feature bit: the sender verifies header CRCs.
+pub const FEATURE_HEADER_CRC: u32 = 0x08;
+/// Every feature bit this crate supports.
+pub const SUPPORTED_FEATURES: u32 = FEATURE_FLAGS | FEATURE_V2_LENGTH | FEATURE_VARINT_LENGTH | FEATURE_HEADER_CRC;
 
 /// Binary packets supported by the protocol.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Packet {
     Ping,
     Pong,
+    /// UTF-8 text (`text` feature). Without it, opcode 0x03 is rejected as unknown.
+    #[cfg(feature = "text")]
     Message(String),
     Data(Vec<u8>),
+    /// Several packets coalesced into one frame; see [`crate::batch`].
+    Batch(Vec<Packet>),
+    /// Sent by both peers at connection start to agree on a version and
+    /// features; see [`negotiate`].
+    ///
+    /// Always framed as version 0 so that any peer can read it.
+    Hello { version: u8, features: u32 },
 }
 
 impl Packet {
@@ -20,8 +48,178 @@ impl Packet {
         match self {
             Packet::Ping => OPCODE_PING,
             Packet::Pong => OPCODE_PONG,
+            #[cfg(feature = "text")]
             Packet::Message(_) => OPCODE_MESSAGE,
             Packet::Data(_) => OPCODE_DATA,
+            Packet::Batch(_) => OPCODE_BATCH,
+            Packet::Hello { .. } => OPCODE_HELLO,
+        }
+    }
+
+    /// The `Hello` this crate sends: [`PROTOCOL_VERSION`] and [`SUPPORTED_FEATURES`].
+    pub fn hello() -> Self {
+        Packet::Hello { version: PROTOCOL_VERSION, features: SUPPORTED_FEATURES }
+    }
+}
+
+/// Settle a `Hello` exchange: the lower of the two versions and the
+/// features both sides set.
+///
+/// Both peers reach the same answer from the same pair of `Hello`s. Raise
+/// [`ValidationConfig::max_version`](crate::header::ValidationConfig::max_version)
+/// to the agreed version so the reader accepts it.
+///
+/// ```
+/// use byteframe::packet::negotiate;
+///
+/// assert_eq!(negotiate((2, 0b011), (1, 0b110)), (1, 0b010));
+/// ```
+pub const fn negotiate(local: (u8, u32), remote: (u8, u32)) -> (u8, u32) {
+    let version = if local.0 < remote.0 { local.0 } else { remote.0 };
+    (version, local.1 & remote.1)
+}
+
+/// A decoded packet borrowing its payload from the frame buffer.
+///
+/// Produced by [`codec::decode_ref`](crate::codec::decode_ref) for callers
+/// that already own the bytes and want to skip the copies made when
+/// building a [`Packet`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacketRef<'a> {
+    Ping,
+    Pong,
+    #[cfg(feature = "text")]
+    Message(&'a str),
+    Data(&'a [u8]),
+    Batch(BatchRef<'a>),
+    Hello { version: u8, features: u32 },
+}
+
+impl<'a> PacketRef<'a> {
+    pub fn opcode(&self) -> u8 {
+        match self {
+            PacketRef::Ping => OPCODE_PING,
+            PacketRef::Pong => OPCODE_PONG,
+            #[cfg(feature = "text")]
+            PacketRef::Message(_) => OPCODE_MESSAGE,
+            PacketRef::Data(_) => OPCODE_DATA,
+            PacketRef::Batch(_) => OPCODE_BATCH,
+            PacketRef::Hello { .. } => OPCODE_HELLO,
+        }
+    }
+
+    /// Copy into an owned [`Packet`].
+    pub fn to_packet(&self) -> Packet {
+        match *self {
+            PacketRef::Ping => Packet::Ping,
+            PacketRef::Pong => Packet::Pong,
+            #[cfg(feature = "text")]
+            PacketRef::Message(text) => Packet::Message(text.into()),
+            PacketRef::Data(bytes) => Packet::Data(bytes.to_vec()),
+            PacketRef::Batch(batch) => Packet::Batch(batch.iter().map(|entry| entry.to_packet()).collect()),
+            PacketRef::Hello { version, features } => Packet::Hello { version, features },
+        }
+    }
+}
+
+/// The entries of a `Batch` frame, parsed lazily from its payload.
+///
+/// Only [`decode_ref`](crate::codec::decode_ref) creates one, after
+/// checking every entry, so iteration cannot fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchRef<'a> {
+    pub(crate) payload: &'a [u8],
+}
+
+impl<'a> BatchRef<'a> {
+    pub fn iter(&self) -> BatchIter<'a> {
+        BatchIter { rest: self.payload }
+    }
+
+    /// The raw batch payload (entries back to back).
+    pub fn payload(&self) -> &'a [u8] {
+        self.payload
+    }
+}
+
+impl<'a> IntoIterator for BatchRef<'a> {
+    type Item = PacketRef<'a>;
+    type IntoIter = BatchIter<'a>;
+
+    fn into_iter(self) -> BatchIter<'a> {
+        self.iter()
+    }
+}
+
+/// Iterator over the entries of a [`BatchRef`].
+#[derive(Debug, Clone)]
+pub struct BatchIter<'a> {
+    rest: &'a [u8],
+}
+
+impl<'a> Iterator for BatchIter<'a> {
+    type Item = PacketRef<'a>;
+
+    fn next(&mut self) -> Option<PacketRef<'a>> {
+        match crate::codec::next_batch_entry(self.rest) {
+            Ok(Some((entry, rest))) => {
+                self.rest = rest;
+                Some(entry)
+            }
+            _ => None,
+        }
+    }
+}
+
+// Written by hand: the derive cannot resolve `Vec<Packet>` recursively.
+#[cfg(feature = "defmt")]
+impl defmt::Format for Packet {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        match self {
+            Packet::Ping => defmt::write!(f, "Ping"),
+            Packet::Pong => defmt::write!(f, "Pong"),
+            #[cfg(feature = "text")]
+            Packet::Message(text) => defmt::write!(f, "Message({=str})", text.as_str()),
+            Packet::Data(bytes) => defmt::write!(f, "Data({=[u8]})", bytes.as_slice()),
+            Packet::Batch(packets) => {
+                defmt::write!(f, "Batch([");
+                for (i, packet) in packets.iter().enumerate() {
+                    if i > 0 {
+                        defmt::write!(f, ", ");
+                    }
+                    packet.format(f);
+                }
+                defmt::write!(f, "])");
+            }
+            Packet::Hello { version, features } => {
+                defmt::write!(f, "Hello {{ version: {}, features: {=u32:#x} }}", version, features)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for PacketRef<'_> {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        match self {
+            PacketRef::Ping => defmt::write!(f, "Ping"),
+            PacketRef::Pong => defmt::write!(f, "Pong"),
+            #[cfg(feature = "text")]
+            PacketRef::Message(text) => defmt::write!(f, "Message({=str})", *text),
+            PacketRef::Data(bytes) => defmt::write!(f, "Data({=[u8]})", *bytes),
+            PacketRef::Batch(batch) => {
+                defmt::write!(f, "Batch([");
+                for (i, packet) in batch.iter().enumerate() {
+                    if i > 0 {
+                        defmt::write!(f, ", ");
+                    }
+                    packet.format(f);
+                }
+                defmt::write!(f, "])");
+            }
+            PacketRef::Hello { version, features } => {
+                defmt::write!(f, "Hello {{ version: {}, features: {=u32:#x} }}", version, features)
+            }
         }
     }
 }
@@ -34,7 +232,10 @@ mod tests {
     fn opcode_matches_variant() {
         assert_eq!(Packet::Ping.opcode(), OPCODE_PING);
         assert_eq!(Packet::Pong.opcode(), OPCODE_PONG);
+        #[cfg(feature = "text")]
         assert_eq!(Packet::Message(String::new()).opcode(), OPCODE_MESSAGE);
         assert_eq!(Packet::Data(vec![]).opcode(), OPCODE_DATA);
+        assert_eq!(Packet::Batch(vec![]).opcode(), OPCODE_BATCH);
+        assert_eq!(Packet::hello().opcode(), OPCODE_HELLO);
     }
 }
@@ -0,0 +1,239 @@
+//! Slab of reusable frame decoders for servers with many connections.
+
+use alloc::vec::Vec;
+
+use crate::budget::MemoryBudget;
+use crate::framing::{DecodeResult, FrameDecoder};
+use crate::stats::{FrameSizeHistogram, ResyncStats};
+
+/// Handle to a decoder checked out of a [`DecoderPool`].
+///
+/// Keys carry a generation, so a key kept after [`DecoderPool::release`]
+/// never aliases the connection that later reuses the same slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DecoderKey {
+    index: usize,
+    generation: u32,
+}
+
+#[derive(Debug)]
+struct Slot {
+    decoder: FrameDecoder,
+    generation: u32,
+    occupied: bool,
+}
+
+/// Slab-allocated decoder state, recycled when connections go away.
+///
+/// Released decoders are reset rather than freed, and their slots are
+/// handed to the next connections. The decoders sit side by side in one
+/// slab, while each keeps its own header and payload buffers on the heap;
+/// those buffers retain their capacity, so a reused slot does not have to
+/// grow them again.
+///
+/// # Example
+///
+/// ```
+/// use byteframe::pool::DecoderPool;
+///
+/// let mut pool = DecoderPool::with_capacity(1024);
+/// let conn = pool.acquire();
+/// let output = pool.decode(conn, &[]).unwrap();
+/// assert!(output.packets.is_empty());
+/// pool.release(conn);
+/// ```
+#[derive(Debug, Default)]
+pub struct DecoderPool {
+    slots: Vec<Slot>,
+    free: Vec<usize>,
+    budget: Option<MemoryBudget>,
+    retired_sizes: FrameSizeHistogram, // Frame sizes from decoders already released
+    retired_resync: ResyncStats,       // Resync counters from decoders already released
+}
+
+impl DecoderPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a pool with room for `capacity` decoders before the slab grows.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            slots: Vec::with_capacity(capacity),
+            free: Vec::with_capacity(capacity),
+            budget: None,
+            retired_sizes: FrameSizeHistogram::new(),
+            retired_resync: ResyncStats::default(),
+        }
+    }
+
+    /// Create a pool whose decoders all draw from the same memory budget.
+    pub fn with_budget(budget: MemoryBudget) -> Self {
+        Self {
+            budget: Some(budget),
+            ..Self::default()
+        }
+    }
+
+    /// Check out a decoder for a new connection.
+    pub fn acquire(&mut self) -> DecoderKey {
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index];
+            slot.occupied = true;
+            return DecoderKey { index, generation: slot.generation };
+        }
+
+        let decoder = match &self.budget {
+            Some(budget) => FrameDecoder::with_budget(budget.clone()),
+            None => FrameDecoder::new(),
+        };
+        self.slots.push(Slot { decoder, generation: 0, occupied: true });
+        DecoderKey { index: self.slots.len() - 1, generation: 0 }
+    }
+
+    /// Reset the decoder behind `key` and return it to the pool.
+    ///
+    /// Settings changed through [`get_mut`](Self::get_mut) (checksum mode,
+    /// validation, framing) go back to their defaults, so the next
+    /// connection never inherits them.
+    ///
+    /// Returns `false` if `key` was already released.
+    pub fn release(&mut self, key: DecoderKey) -> bool {
+        let Some(slot) = self
+            .slots
+            .get_mut(key.index)
+            .filter(|slot| slot.occupied && slot.generation == key.generation)
+        else {
+            return false;
+        };
+        self.retired_sizes.merge(slot.decoder.frame_sizes());
+        self.retired_resync.merge(slot.decoder.resync_stats());
+        slot.decoder.reset_config();
+        slot.occupied = false;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(key.index);
+        true
+    }
+
+    pub fn get_mut(&mut self, key: DecoderKey) -> Option<&mut FrameDecoder> {
+        self.slot_mut(key).map(|slot| &mut slot.decoder)
+    }
+
+    /// Feed `input` to the decoder behind `key`.
+    pub fn decode(&mut self, key: DecoderKey, input: &[u8]) -> Option<DecodeResult> {
+        self.get_mut(key).map(|decoder| decoder.decode(input))
+    }
+
+    /// Aggregate frame sizes across every connection this pool has served.
+    pub fn frame_sizes(&self) -> FrameSizeHistogram {
+        let mut total = self.retired_sizes.clone();
+        for slot in self.slots.iter().filter(|slot| slot.occupied) {
+            total.merge(slot.decoder.frame_sizes());
+        }
+        total
+    }
+
+    /// Aggregate resync counters across every connection this pool has served.
+    pub fn resync_stats(&self) -> ResyncStats {
+        let mut total = self.retired_resync;
+        for slot in self.slots.iter().filter(|slot| slot.occupied) {
+            total.merge(slot.decoder.resync_stats());
+        }
+        total
+    }
+
+    /// Number of decoders currently checked out.
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn slot_mut(&mut self, key: DecoderKey) -> Option<&mut Slot> {
+        self.slots
+            .get_mut(key.index)
+            .filter(|slot| slot.occupied && slot.generation == key.generation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec;
+    use crate::packet::Packet;
+
+    #[test]
+    fn recycles_slots_and_rejects_stale_keys() {
+        let mut pool = DecoderPool::new();
+        let first = pool.acquire();
+        assert!(pool.release(first));
+        assert!(!pool.release(first));
+
+        let second = pool.acquire();
+        assert_eq!(pool.len(), 1);
+        assert_ne!(first, second);
+        assert!(pool.get_mut(first).is_none());
+        assert!(pool.get_mut(second).is_some());
+    }
+
+    #[cfg(feature = "text")]
+    #[test]
+    fn released_decoder_starts_clean() {
+        let mut frame = Vec::new();
+        codec::encode(&Packet::Message("hello".into()), &mut frame).unwrap();
+
+        let mut pool = DecoderPool::new();
+        let key = pool.acquire();
+        pool.decode(key, &frame[..4]).unwrap(); // leave a partial header behind
+        pool.release(key);
+
+        let key = pool.acquire();
+        let output = pool.decode(key, &frame).unwrap();
+        assert_eq!(output.packets, vec![Packet::Message("hello".into())]);
+        assert!(output.errors.is_empty());
+        assert_eq!(pool.get_mut(key).unwrap().frame_sizes().count(), 1);
+    }
+
+    #[test]
+    fn released_decoder_forgets_its_settings() {
+        use crate::codec::ChecksumMode;
+        use crate::framing::{FrameError, Framing};
+        use crate::header::ValidationConfig;
+
+        let mut frame = Vec::new();
+        codec::encode(&Packet::Data(vec![1, 2, 3]), &mut frame).unwrap();
+        let mut corrupted = frame.clone();
+        *corrupted.last_mut().unwrap() ^= 0xFF;
+
+        let mut pool = DecoderPool::new();
+        let key = pool.acquire();
+        let decoder = pool.get_mut(key).unwrap();
+        decoder.set_framing(Framing::Slip);
+        decoder.set_checksum_mode(ChecksumMode::Disabled);
+        decoder.set_validation(ValidationConfig { max_length: 1, ..ValidationConfig::default() });
+        pool.release(key);
+
+        let key = pool.acquire();
+        let output = pool.decode(key, &frame).unwrap();
+        assert_eq!(output.packets, vec![Packet::Data(vec![1, 2, 3])]);
+        let output = pool.decode(key, &corrupted).unwrap();
+        assert!(matches!(output.errors[..], [FrameError::Codec(codec::CodecError::ChecksumMismatch { .. })]));
+    }
+
+    #[test]
+    fn aggregates_frame_sizes_across_connections() {
+        let mut frame = Vec::new();
+        codec::encode(&Packet::Ping, &mut frame).unwrap();
+
+        let mut pool = DecoderPool::new();
+        let first = pool.acquire();
+        let second = pool.acquire();
+        pool.decode(first, &frame).unwrap();
+        pool.decode(second, &frame).unwrap();
+        pool.release(first);
+
+        assert_eq!(pool.frame_sizes().count(), 2);
+    }
+}
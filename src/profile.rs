@@ -0,0 +1,273 @@
+//! Compile-time protocol profiles.
+//!
+//! A [`Profile`] fixes the payload limit and checksum mode as const
+//! generics, so the checks are resolved at compile time and the branches a
+//! profile never takes (e.g. checksumming on a CRC-protected UART) are
+//! removed from the binary.
+//!
+//! [`Compact`] swaps the 9-byte header for a 5-byte one on links where
+//! per-frame overhead dominates. Code generic over [`WireProfile`] can take
+//! either kind as a type parameter.
+
+use alloc::vec::Vec;
+
+use crate::checksum::fnv1a32;
+use crate::codec::{self, ChecksumMode, CodecError};
+use crate::framing::FrameDecoder;
+use crate::header::{Header, HeaderError, ValidationConfig, HEADER_LEN};
+use crate::packet::Packet;
+
+/// Codec settings fixed at compile time.
+///
+/// `MAX_PAYLOAD` must not exceed 65535 (the wire length field); larger
+/// values fail to compile.
+///
+/// ```
+/// use byteframe::profile::Profile;
+/// use byteframe::Packet;
+///
+/// type Sensor = Profile<64, false>;
+///
+/// let mut frame = Vec::new();
+/// Sensor::encode(&Packet::Data(vec![1, 2, 3]), &mut frame).unwrap();
+/// assert_eq!(Sensor::decode(&frame).unwrap(), Packet::Data(vec![1, 2, 3]));
+/// assert!(Sensor::encode(&Packet::Data(vec![0; 65]), &mut frame).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Profile<const MAX_PAYLOAD: usize, const CHECKSUM: bool>;
+
+/// The full protocol: 64 KiB payloads with checksums.
+pub type Standard = Profile<{ u16::MAX as usize }, true>;
+
+impl<const MAX_PAYLOAD: usize, const CHECKSUM: bool> Profile<MAX_PAYLOAD, CHECKSUM> {
+    /// Largest payload accepted in either direction.
+    pub const MAX_PAYLOAD: usize = {
+        assert!(MAX_PAYLOAD <= u16::MAX as usize, "MAX_PAYLOAD exceeds the u16 length field");
+        MAX_PAYLOAD
+    };
+    /// Largest complete frame, for sizing static buffers.
+    pub const MAX_FRAME: usize = HEADER_LEN + Self::MAX_PAYLOAD;
+    /// Checksum mode used by this profile.
+    pub const CHECKSUM_MODE: ChecksumMode = if CHECKSUM { ChecksumMode::Enabled } else { ChecksumMode::Disabled };
+
+    /// Encode `packet`, rejecting payloads above `MAX_PAYLOAD`.
+    ///
+    /// On error `buf` is left as it was.
+    pub fn encode(packet: &Packet, buf: &mut Vec<u8>) -> Result<(), CodecError> {
+        let start = buf.len();
+        codec::encode_with(packet, buf, Self::CHECKSUM_MODE)?;
+        let payload_len = buf.len() - start - HEADER_LEN;
+        if payload_len > Self::MAX_PAYLOAD {
+            buf.truncate(start);
+            return Err(CodecError::PayloadTooLarge(payload_len));
+        }
+        Ok(())
+    }
+
+    /// Decode one frame, rejecting headers that declare more than `MAX_PAYLOAD`.
+    pub fn decode(bytes: &[u8]) -> Result<Packet, CodecError> {
+        if let Ok(header) = Header::from_bytes(bytes) {
+            if header.length as usize > Self::MAX_PAYLOAD {
+                return Err(HeaderError::LengthExceeded { length: header.length, max: Self::MAX_PAYLOAD as u32 }.into());
+            }
+        }
+        codec::decode_with(bytes, Self::CHECKSUM_MODE)
+    }
+
+    /// A streaming decoder configured for this profile.
+    pub fn decoder() -> FrameDecoder {
+        let mut decoder = FrameDecoder::new();
+        decoder.set_checksum_mode(Self::CHECKSUM_MODE);
+        decoder.set_validation(ValidationConfig {
+            max_length: Self::MAX_PAYLOAD as u32,
+            ..ValidationConfig::default()
+        });
+        decoder
+    }
+}
+
+/// Frame encode/decode rules selected by a type parameter.
+pub trait WireProfile {
+    /// Bytes of header in front of every payload.
+    const HEADER_LEN: usize;
+    /// Largest payload accepted in either direction.
+    const MAX_PAYLOAD: usize;
+
+    fn encode(packet: &Packet, buf: &mut Vec<u8>) -> Result<(), CodecError>;
+    fn decode(bytes: &[u8]) -> Result<Packet, CodecError>;
+}
+
+impl<const MAX_PAYLOAD: usize, const CHECKSUM: bool> WireProfile for Profile<MAX_PAYLOAD, CHECKSUM> {
+    const HEADER_LEN: usize = HEADER_LEN;
+    const MAX_PAYLOAD: usize = Self::MAX_PAYLOAD;
+
+    fn encode(packet: &Packet, buf: &mut Vec<u8>) -> Result<(), CodecError> {
+        Self::encode(packet, buf)
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Packet, CodecError> {
+        Self::decode(bytes)
+    }
+}
+
+/// First byte of every [`Compact`] frame.
+pub const COMPACT_MAGIC: u8 = 0xA5;
+/// Header size of a [`Compact`] frame.
+pub const COMPACT_HEADER_LEN: usize = 5;
+
+/// A 5-byte header for constrained radio links.
+///
+/// The layout is magic (u8), opcode (u8), length (u8), then a 16-bit
+/// checksum: the two halves of the payload's FNV-1a hash XORed together.
+/// Payloads are capped at 255 bytes. Both peers must use this profile;
+/// its frames are not understood by [`FrameDecoder`].
+///
+/// ```
+/// use byteframe::profile::Compact;
+/// use byteframe::Packet;
+///
+/// let mut frame = Vec::new();
+/// Compact::encode(&Packet::Data(vec![1, 2, 3]), &mut frame).unwrap();
+/// assert_eq!(frame.len(), 5 + 3);
+/// assert_eq!(Compact::decode(&frame).unwrap(), Packet::Data(vec![1, 2, 3]));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Compact;
+
+impl Compact {
+    /// Largest payload the one-byte length field can describe.
+    pub const MAX_PAYLOAD: usize = u8::MAX as usize;
+    /// Largest complete frame, for sizing static buffers.
+    pub const MAX_FRAME: usize = COMPACT_HEADER_LEN + Self::MAX_PAYLOAD;
+
+    /// Encode `packet`, rejecting payloads above 255 bytes.
+    ///
+    /// On error `buf` is left as it was.
+    pub fn encode(packet: &Packet, buf: &mut Vec<u8>) -> Result<(), CodecError> {
+        let start = buf.len();
+        codec::encode_with(packet, buf, ChecksumMode::Disabled)?;
+        let payload_len = buf.len() - start - HEADER_LEN;
+        if payload_len > Self::MAX_PAYLOAD {
+            buf.truncate(start);
+            return Err(CodecError::PayloadTooLarge(payload_len));
+        }
+        let checksum = fold_checksum(&buf[start + HEADER_LEN..]).to_be_bytes();
+        let header = [COMPACT_MAGIC, packet.opcode(), payload_len as u8, checksum[0], checksum[1]];
+        buf.splice(start..start + HEADER_LEN, header);
+        Ok(())
+    }
+
+    /// Decode one compact frame.
+    pub fn decode(bytes: &[u8]) -> Result<Packet, CodecError> {
+        let Some(&[magic, opcode, length, sum_hi, sum_lo]) = bytes.get(..COMPACT_HEADER_LEN) else {
+            return Err(CodecError::FrameTooShort(bytes.len()));
+        };
+        if magic != COMPACT_MAGIC {
+            return Err(HeaderError::InvalidMagic(magic as u16).into());
+        }
+        if opcode > 0x0F {
+            return Err(HeaderError::UnknownOpcode(opcode).into());
+        }
+        let Some(payload) = bytes.get(COMPACT_HEADER_LEN..COMPACT_HEADER_LEN + length as usize) else {
+            return Err(CodecError::FrameTooShort(bytes.len()));
+        };
+        let expected = u16::from_be_bytes([sum_hi, sum_lo]);
+        let actual = fold_checksum(payload);
+        if actual != expected {
+            return Err(CodecError::ChecksumMismatch { expected: expected as u32, actual: actual as u32 });
+        }
+        codec::decode_frame(&Header::new(opcode, length as u32, 0), payload, ChecksumMode::Disabled)
+    }
+}
+
+impl WireProfile for Compact {
+    const HEADER_LEN: usize = COMPACT_HEADER_LEN;
+    const MAX_PAYLOAD: usize = Self::MAX_PAYLOAD;
+
+    fn encode(packet: &Packet, buf: &mut Vec<u8>) -> Result<(), CodecError> {
+        Self::encode(packet, buf)
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Packet, CodecError> {
+        Self::decode(bytes)
+    }
+}
+
+fn fold_checksum(payload: &[u8]) -> u16 {
+    let hash = fnv1a32(payload);
+    (hash >> 16) as u16 ^ hash as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framing::FrameError;
+
+    type Small = Profile<16, false>;
+
+    #[test]
+    fn constants_follow_parameters() {
+        assert_eq!(Standard::MAX_PAYLOAD, 65535);
+        assert_eq!(Standard::CHECKSUM_MODE, ChecksumMode::Enabled);
+        assert_eq!(Small::MAX_FRAME, HEADER_LEN + 16);
+        assert_eq!(Small::CHECKSUM_MODE, ChecksumMode::Disabled);
+    }
+
+    #[cfg(feature = "text")]
+    #[test]
+    fn small_profile_omits_checksum_and_enforces_limit() {
+        let mut frame = Vec::new();
+        Small::encode(&Packet::Message("sixteen bytes!!!".into()), &mut frame).unwrap();
+        assert_eq!(&frame[5..9], &[0, 0, 0, 0]);
+        assert_eq!(Small::decode(&frame).unwrap(), Packet::Message("sixteen bytes!!!".into()));
+
+        let before = frame.len();
+        assert!(matches!(
+            Small::encode(&Packet::Data(vec![0; 17]), &mut frame),
+            Err(CodecError::PayloadTooLarge(17))
+        ));
+        assert_eq!(frame.len(), before);
+
+        let mut large = Vec::new();
+        Standard::encode(&Packet::Data(vec![0; 17]), &mut large).unwrap();
+        assert!(matches!(
+            Small::decode(&large),
+            Err(CodecError::Header(HeaderError::LengthExceeded { length: 17, max: 16 }))
+        ));
+    }
+
+    #[test]
+    fn decoder_applies_profile() {
+        let mut stream = Vec::new();
+        Standard::encode(&Packet::Data(vec![0; 17]), &mut stream).unwrap();
+        Small::encode(&Packet::Ping, &mut stream).unwrap();
+
+        let result = Small::decoder().decode(&stream);
+        assert_eq!(result.packets, vec![Packet::Ping]);
+        assert!(matches!(result.errors[..], [FrameError::Header(HeaderError::LengthExceeded { .. })]));
+    }
+
+    fn round_trip<P: WireProfile>(packet: &Packet) -> usize {
+        let mut frame = Vec::new();
+        P::encode(packet, &mut frame).unwrap();
+        assert_eq!(P::decode(&frame).unwrap(), *packet);
+        frame.len()
+    }
+
+    #[test]
+    fn compact_profile_saves_four_bytes_and_checks_payload() {
+        let packet = Packet::Batch(vec![Packet::Ping, Packet::Data(vec![9; 10])]);
+        assert_eq!(round_trip::<Standard>(&packet) - round_trip::<Compact>(&packet), 4);
+
+        let mut frame = Vec::new();
+        Compact::encode(&Packet::Data(vec![1, 2, 3]), &mut frame).unwrap();
+        frame[6] ^= 0xFF;
+        assert!(matches!(Compact::decode(&frame), Err(CodecError::ChecksumMismatch { .. })));
+        assert!(matches!(Compact::decode(&frame[..6]), Err(CodecError::FrameTooShort(6))));
+        assert!(matches!(
+            Compact::encode(&Packet::Data(vec![0; 256]), &mut frame),
+            Err(CodecError::PayloadTooLarge(256))
+        ));
+        assert_eq!(frame.len(), 8);
+    }
+}
@@ -0,0 +1,170 @@
+//! Render [`stats`](crate::stats) in the Prometheus text exposition format
+//! (`prometheus` feature).
+//!
+//! Only the text is produced; serve it from whatever HTTP endpoint the
+//! application already has.
+//!
+//! ```
+//! use byteframe::prometheus::PrometheusExporter;
+//! use byteframe::FrameDecoder;
+//!
+//! let decoder = FrameDecoder::new();
+//! let text = PrometheusExporter::new("byteframe")
+//!     .frame_sizes("decoded_frame", &[("peer", "sensor-1")], decoder.frame_sizes())
+//!     .resync(&[("peer", "sensor-1")], decoder.resync_stats())
+//!     .render();
+//! assert!(text.contains("byteframe_frames_ok_total{peer=\"sensor-1\"} 0"));
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::stats::{FrameSizeHistogram, ResyncStats};
+
+/// Collects samples from many sources and renders them grouped by metric.
+///
+/// Samples for the same metric with different labels (one per connection,
+/// say) end up in a single family, as the exposition format requires.
+#[derive(Debug, Clone, Default)]
+pub struct PrometheusExporter {
+    prefix: String,
+    families: BTreeMap<String, Family>,
+}
+
+#[derive(Debug, Clone)]
+struct Family {
+    help: &'static str,
+    kind: &'static str,
+    samples: Vec<String>,
+}
+
+impl PrometheusExporter {
+    /// Metric names are `<prefix>_<metric>`.
+    pub fn new(prefix: &str) -> Self {
+        Self {
+            prefix: prefix.to_string(),
+            families: BTreeMap::new(),
+        }
+    }
+
+    /// Add `histogram` as `<prefix>_<name>_bytes`.
+    pub fn frame_sizes(&mut self, name: &str, labels: &[(&str, &str)], histogram: &FrameSizeHistogram) -> &mut Self {
+        let metric = format!("{}_{}_bytes", self.prefix, name);
+        let mut samples = Vec::new();
+        let mut cumulative = 0;
+        let mut buckets = histogram.buckets().peekable();
+        while let Some((bound, count)) = buckets.next() {
+            cumulative += count;
+            // The last bucket also holds oversized frames, so it is the +Inf bucket
+            let le = if buckets.peek().is_some() { bound.to_string() } else { "+Inf".to_string() };
+            samples.push(format!("{}_bucket{} {}", metric, label_set(labels, Some(&le)), cumulative));
+        }
+        samples.push(format!("{}_sum{} {}", metric, label_set(labels, None), histogram.total_bytes()));
+        samples.push(format!("{}_count{} {}", metric, label_set(labels, None), histogram.count()));
+        self.family(metric, "Wire frame sizes (header + payload) in bytes.", "histogram")
+            .extend(samples);
+        self
+    }
+
+    /// Add every [`ResyncStats`] counter plus a `link_quality` gauge.
+    pub fn resync(&mut self, labels: &[(&str, &str)], stats: &ResyncStats) -> &mut Self {
+        let labels = label_set(labels, None);
+        let counters = [
+            ("frames_ok_total", "Frames that decoded successfully.", stats.frames_ok),
+            ("checksum_failures_total", "Frames rejected for a checksum mismatch.", stats.checksum_failures),
+            ("frames_failed_total", "Frames rejected for any other codec error.", stats.frames_failed),
+            ("bytes_skipped_total", "Bytes discarded while scanning for a header.", stats.bytes_skipped),
+            ("frames_recovered_total", "Corruption episodes that ended with a good frame.", stats.frames_recovered),
+        ];
+        for (name, help, value) in counters {
+            let metric = format!("{}_{}", self.prefix, name);
+            let sample = format!("{}{} {}", metric, labels, value);
+            self.family(metric, help, "counter").push(sample);
+        }
+        let metric = format!("{}_link_quality", self.prefix);
+        let sample = format!("{}{} {}", metric, labels, stats.link_quality());
+        self.family(metric, "Share of frames that arrived intact (0 to 1).", "gauge")
+            .push(sample);
+        self
+    }
+
+    /// The exposition text, one family after another in name order.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for (name, family) in &self.families {
+            let _ = writeln!(out, "# HELP {} {}", name, family.help);
+            let _ = writeln!(out, "# TYPE {} {}", name, family.kind);
+            for sample in &family.samples {
+                out.push_str(sample);
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    fn family(&mut self, name: String, help: &'static str, kind: &'static str) -> &mut Vec<String> {
+        &mut self
+            .families
+            .entry(name)
+            .or_insert_with(|| Family { help, kind, samples: Vec::new() })
+            .samples
+    }
+}
+
+/// `{a="1",le="16"}`, or nothing when there are no labels.
+fn label_set(labels: &[(&str, &str)], le: Option<&str>) -> String {
+    let pairs: Vec<String> = labels
+        .iter()
+        .copied()
+        .chain(le.map(|le| ("le", le)))
+        .map(|(key, value)| format!("{}=\"{}\"", key, escape(value)))
+        .collect();
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", pairs.join(","))
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_buckets_are_cumulative_and_end_at_inf() {
+        let mut histogram = FrameSizeHistogram::new();
+        histogram.record(9);
+        histogram.record(20);
+        histogram.record(100_000);
+
+        let text = PrometheusExporter::new("bf").frame_sizes("read", &[], &histogram).render();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "# HELP bf_read_bytes Wire frame sizes (header + payload) in bytes.");
+        assert_eq!(lines[1], "# TYPE bf_read_bytes histogram");
+        assert_eq!(lines[2], "bf_read_bytes_bucket{le=\"16\"} 1");
+        assert_eq!(lines[3], "bf_read_bytes_bucket{le=\"32\"} 2");
+        assert!(text.contains("bf_read_bytes_bucket{le=\"+Inf\"} 3\n"));
+        assert!(text.contains("bf_read_bytes_sum 100029\n"));
+        assert!(text.ends_with("bf_read_bytes_count 3\n"));
+    }
+
+    #[test]
+    fn groups_labeled_series_into_one_family() {
+        let healthy = ResyncStats { frames_ok: 4, ..ResyncStats::default() };
+        let noisy = ResyncStats { frames_ok: 1, checksum_failures: 1, ..ResyncStats::default() };
+
+        let text = PrometheusExporter::new("bf")
+            .resync(&[("peer", "a")], &healthy)
+            .resync(&[("peer", "say \"b\"")], &noisy)
+            .render();
+        assert_eq!(text.matches("# TYPE bf_frames_ok_total counter").count(), 1);
+        assert!(text.contains(
+            "bf_frames_ok_total{peer=\"a\"} 4\nbf_frames_ok_total{peer=\"say \\\"b\\\"\"} 1\n"
+        ));
+        assert!(text.contains("# TYPE bf_link_quality gauge\nbf_link_quality{peer=\"a\"} 1\nbf_link_quality{peer=\"say \\\"b\\\"\"} 0.5\n"));
+    }
+}
@@ -1,9 +1,17 @@
 //! Packet reader that wraps any `std::io::Read` source.
 
-use std::io::{self, Read};
+use std::fmt;
+use std::io::{self, IoSliceMut, Read};
+use std::time::{Duration, Instant};
 
-use crate::framing::FrameDecoder;
+use crate::budget::MemoryBudget;
+use crate::clock::{Clock, SystemClock};
+use crate::codec::ChecksumMode;
+use crate::framing::{DecodeResult, FrameDecoder, FrameError, Framing};
+use crate::header::ValidationConfig;
 use crate::packet::Packet;
+use crate::state::ConnectionState;
+use crate::stats::{FrameSizeHistogram, ResyncStats};
 
 /// Wraps a `Read` source and provides packet-level reading.
 ///
@@ -32,8 +40,21 @@ use crate::packet::Packet;
 pub struct PacketReader<R> {
     reader: R,
     decoder: FrameDecoder,
-    read_buffer: Vec<u8>,
+    read_buffers: Vec<Vec<u8>>, // One buffer for plain reads, several for `read_vectored`
     packet_buffer: Vec<Packet>,
+    deferred_error: Option<io::Error>, // Framing error to report once `packet_buffer` drains
+    state: ConnectionState,
+    adaptive: Option<AdaptiveBuffer>, // Bounds for resizing the single read buffer
+    small_reads: u32,                 // Consecutive reads that used under a quarter of the buffer
+    frame_deadline: Option<FrameDeadline>, // Limit on how long one frame may take to arrive
+    clock: Box<dyn Clock>,                 // Measures `ReadOpts::timeout`
+    wait_started: Option<Instant>,         // First stall of the current `read_packet_with` wait
+}
+
+struct FrameDeadline {
+    limit: Duration,
+    clock: Box<dyn Clock>,
+    started: Option<Instant>, // When the incomplete frame's first bytes arrived
 }
 
 impl<R: Read> PacketReader<R> {
@@ -47,11 +68,82 @@ impl<R: Read> PacketReader<R> {
         Self {
             reader,
             decoder: FrameDecoder::new(),
-            read_buffer: vec![0u8; capacity],
+            read_buffers: vec![vec![0u8; capacity]],
             packet_buffer: Vec::new(),
+            deferred_error: None,
+            state: ConnectionState::Healthy,
+            adaptive: None,
+            small_reads: 0,
+            frame_deadline: None,
+            clock: Box::new(SystemClock),
+            wait_started: None,
         }
     }
 
+    /// Create a new packet reader whose read buffer grows and shrinks with traffic.
+    ///
+    /// The buffer starts at `bounds.min`, doubles whenever a read fills it
+    /// completely (bulk transfer), and halves after
+    /// [`AdaptiveBuffer::shrink_after`] consecutive reads that used less than
+    /// a quarter of it (e.g. keepalive-only traffic), always staying within
+    /// `bounds.min..=bounds.max`.
+    pub fn with_adaptive(reader: R, bounds: AdaptiveBuffer) -> Self {
+        let min = bounds.min.max(1);
+        let mut packet_reader = Self::with_capacity(reader, min);
+        packet_reader.adaptive = Some(AdaptiveBuffer {
+            min,
+            max: bounds.max.max(min),
+            ..bounds
+        });
+        packet_reader
+    }
+
+    /// Create a new packet reader that fills several buffers per syscall
+    /// using `read_vectored`.
+    ///
+    /// Useful for high-throughput ingest where one `readv` can pick up many
+    /// frames at once. See [`VectoredRead`] for the tunables.
+    pub fn with_vectored(reader: R, config: VectoredRead) -> Self {
+        let mut packet_reader = Self::new(reader);
+        packet_reader.read_buffers = (0..config.buffers.max(1))
+            .map(|_| vec![0u8; config.buffer_len.max(1)])
+            .collect();
+        packet_reader
+    }
+
+    /// Create a new packet reader whose decoder draws payload memory from `budget`.
+    ///
+    /// A frame that does not fit in the budget when it arrives is skipped:
+    /// [`read_packet`](Self::read_packet) returns an `InvalidData` error
+    /// wrapping [`FrameError::BudgetExceeded`] once, and the reader stays
+    /// healthy, so a budget that is only full for the moment costs that
+    /// frame and not the connection. Keep reading to get the frames after it.
+    pub fn with_budget(reader: R, budget: MemoryBudget) -> Self {
+        let mut packet_reader = Self::new(reader);
+        packet_reader.decoder = FrameDecoder::with_budget(budget);
+        packet_reader
+    }
+
+    /// Fail with [`FrameTimeout`] once a frame has been incomplete for longer than `limit`.
+    ///
+    /// Protects against peers that hold a connection open by trickling a
+    /// frame in byte by byte. The deadline is checked whenever a read
+    /// returns, so against a peer that stops sending entirely it only fires
+    /// if the stream has a read timeout (e.g. `TcpStream::set_read_timeout`).
+    pub fn set_frame_deadline(&mut self, limit: Duration) {
+        self.set_frame_deadline_with_clock(limit, SystemClock);
+    }
+
+    /// [`set_frame_deadline`](Self::set_frame_deadline) measuring time with `clock`.
+    pub fn set_frame_deadline_with_clock(&mut self, limit: Duration, clock: impl Clock + 'static) {
+        self.frame_deadline = Some(FrameDeadline { limit, clock: Box::new(clock), started: None });
+    }
+
+    /// Measure [`ReadOpts::timeout`] with `clock` instead of the system clock.
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.clock = Box::new(clock);
+    }
+
     /// Read one complete packet from the stream.
     ///
     /// This method blocks until a complete packet is available or an error occurs.
@@ -63,17 +155,60 @@ impl<R: Read> PacketReader<R> {
     /// - The stream ends unexpectedly (EOF)
     /// - A packet fails checksum validation
     /// - An invalid opcode is encountered
+    /// - The reader was already poisoned or closed by an earlier error
+    /// - A frame missed the [frame deadline](Self::set_frame_deadline); the
+    ///   error has kind `TimedOut` and wraps a [`FrameTimeout`]
+    ///
+    /// A framing error is returned with kind `InvalidData` after the packets
+    /// decoded in the same read. If it only rejected one frame (see
+    /// [`FrameError::is_desync`]) the reader stays healthy and the next call
+    /// carries on with the following frame. Framing errors that lost track
+    /// of frame boundaries, missed frame deadlines and I/O errors other than
+    /// `WouldBlock`, `TimedOut` and `Interrupted` poison the reader; reaching
+    /// EOF closes it. See [`state`](Self::state).
     pub fn read_packet(&mut self) -> io::Result<Packet> {
+        let result = self.next_packet();
+        if !matches!(&result, Err(err) if is_transient(err)) {
+            self.wait_started = None; // A `read_packet_with` wait ends here too
+        }
+        result
+    }
+
+    fn next_packet(&mut self) -> io::Result<Packet> {
         loop {
             // Return buffered packet if available
             if !self.packet_buffer.is_empty() {
                 return Ok(self.packet_buffer.remove(0));
             }
+            if let Some(err) = self.deferred_error.take() {
+                return Err(err);
+            }
+
+            match &self.state {
+                ConnectionState::Healthy => {}
+                ConnectionState::Poisoned(reason) => {
+                    return Err(io::Error::new(io::ErrorKind::Other, format!("reader poisoned: {reason}")));
+                }
+                ConnectionState::Closed => {
+                    return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "reader is closed"));
+                }
+            }
 
             // Read more data from the underlying stream
-            let bytes_read = self.reader.read(&mut self.read_buffer)?;
+            let bytes_read = match self.fill_buffers() {
+                Ok(n) => n,
+                Err(err) => {
+                    if !is_transient(&err) {
+                        self.state = ConnectionState::Poisoned(err.to_string());
+                        return Err(err);
+                    }
+                    self.check_frame_deadline(false)?;
+                    return Err(err);
+                }
+            };
 
             if bytes_read == 0 {
+                self.state = ConnectionState::Closed;
                 return Err(io::Error::new(
                     io::ErrorKind::UnexpectedEof,
                     "Stream closed before complete packet received",
@@ -81,16 +216,21 @@ impl<R: Read> PacketReader<R> {
             }
 
             // Feed bytes to the decoder
-            let decode_result = self.decoder.decode(&self.read_buffer[..bytes_read]);
+            let decode_result = self.decode_filled(bytes_read);
+            self.adapt_buffer(bytes_read);
 
-            // Check for errors (optional: you could log these instead of failing)
-            if let Some(err) = decode_result.errors.first() {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    format!("framing error: {:?}", err),
-                ));
+            // Report errors once the packets decoded alongside them are returned
+            if let Some((err, desync)) = framing_error(&decode_result.errors) {
+                if desync {
+                    self.state = ConnectionState::Poisoned(err.to_string());
+                }
+                self.deferred_error = Some(err);
             }
 
+            // A frame ending here means any partial frame left over is a new one
+            let completed = !decode_result.packets.is_empty();
+            self.check_frame_deadline(completed)?;
+
             // Buffer all decoded packets
             self.packet_buffer.extend(decode_result.packets);
 
@@ -98,6 +238,148 @@ impl<R: Read> PacketReader<R> {
         }
     }
 
+    /// [`read_packet`](Self::read_packet) with per-call options.
+    ///
+    /// Reads that return `WouldBlock`, `TimedOut` or `Interrupted` hand
+    /// that error back to the caller, who polls again when the stream is
+    /// ready. With a [`timeout`](ReadOpts::timeout), the first such error
+    /// starts a wait that carries over between calls; once the wait is
+    /// longer than the timeout, by the reader's [clock](Self::set_clock),
+    /// the call fails with `TimedOut` instead. A packet or any other error
+    /// ends the wait, whether this method or `read_packet` returns it. Like the frame deadline it is only checked when a read
+    /// returns, so it needs a read timeout on the stream to fire against a
+    /// silent peer.
+    pub fn read_packet_with(&mut self, opts: &ReadOpts) -> io::Result<Packet> {
+        loop {
+            let packet = match self.read_packet() {
+                Err(err) if is_transient(&err) && opts.timeout.is_some() => {
+                    let now = self.clock.now();
+                    let started = *self.wait_started.get_or_insert(now);
+                    if opts.timeout.is_some_and(|timeout| now.duration_since(started) < timeout) {
+                        return Err(err); // Still waiting; the caller polls again
+                    }
+                    self.wait_started = None;
+                    return Err(io::Error::new(io::ErrorKind::TimedOut, "no complete packet before the deadline"));
+                }
+                result => result?,
+            };
+            if !(opts.skip_keepalive && matches!(packet, Packet::Ping | Packet::Pong)) {
+                return Ok(packet);
+            }
+        }
+    }
+
+    /// Start, clear or enforce the frame deadline after a read.
+    fn check_frame_deadline(&mut self, frame_completed: bool) -> io::Result<()> {
+        let Some(deadline) = &mut self.frame_deadline else {
+            return Ok(());
+        };
+        if !self.decoder.is_mid_frame() {
+            deadline.started = None;
+            return Ok(());
+        }
+        let now = deadline.clock.now();
+        let started = match deadline.started {
+            Some(started) if !frame_completed => started,
+            _ => *deadline.started.insert(now),
+        };
+        let elapsed = now.duration_since(started);
+        if elapsed <= deadline.limit {
+            return Ok(());
+        }
+        let timeout = FrameTimeout { elapsed, limit: deadline.limit };
+        self.state = ConnectionState::Poisoned(timeout.to_string());
+        Err(timeout.into())
+    }
+
+    fn fill_buffers(&mut self) -> io::Result<usize> {
+        if let [buffer] = self.read_buffers.as_mut_slice() {
+            return self.reader.read(buffer);
+        }
+        let mut slices: Vec<IoSliceMut<'_>> = self.read_buffers.iter_mut().map(|b| IoSliceMut::new(b)).collect();
+        self.reader.read_vectored(&mut slices)
+    }
+
+    /// Resize the read buffer after a read of `bytes_read` bytes, if adaptive sizing is on.
+    fn adapt_buffer(&mut self, bytes_read: usize) {
+        let Some(bounds) = self.adaptive else {
+            return;
+        };
+        let buffer = &mut self.read_buffers[0];
+        let len = buffer.len();
+        if bytes_read == len && len < bounds.max {
+            self.small_reads = 0;
+            buffer.resize((len * 2).min(bounds.max), 0);
+        } else if bytes_read < len / 4 && len > bounds.min {
+            self.small_reads += 1;
+            if self.small_reads >= bounds.shrink_after {
+                self.small_reads = 0;
+                buffer.truncate((len / 2).max(bounds.min));
+                buffer.shrink_to_fit();
+            }
+        } else {
+            self.small_reads = 0;
+        }
+    }
+
+    /// Current size of the read buffer (the first one, in vectored mode).
+    pub fn read_buffer_len(&self) -> usize {
+        self.read_buffers[0].len()
+    }
+
+    /// Decode the first `filled` bytes spread across the read buffers, in order.
+    fn decode_filled(&mut self, mut filled: usize) -> DecodeResult {
+        let mut result = DecodeResult::default();
+        for buffer in &self.read_buffers {
+            if filled == 0 {
+                break;
+            }
+            let take = filled.min(buffer.len());
+            let chunk = self.decoder.decode(&buffer[..take]);
+            result.packets.extend(chunk.packets);
+            result.errors.extend(chunk.errors);
+            filled -= take;
+        }
+        result
+    }
+
+    /// Reject frames whose header breaks `config` before buffering their payload.
+    pub fn set_validation(&mut self, config: ValidationConfig) {
+        self.decoder.set_validation(config);
+    }
+
+    /// Choose whether incoming payload checksums are verified.
+    ///
+    /// Only disable verification when the peer's writer does the same and the
+    /// transport already guarantees integrity.
+    pub fn set_checksum_mode(&mut self, mode: ChecksumMode) {
+        self.decoder.set_checksum_mode(mode);
+    }
+
+    pub fn checksum_mode(&self) -> ChecksumMode {
+        self.decoder.checksum_mode()
+    }
+
+    /// Choose how frame boundaries are found; see [`Framing`].
+    pub fn set_framing(&mut self, framing: Framing) {
+        self.decoder.set_framing(framing);
+    }
+
+    /// Sizes of the frames received on this connection.
+    pub fn frame_sizes(&self) -> &FrameSizeHistogram {
+        self.decoder.frame_sizes()
+    }
+
+    /// Corruption and resync counters for this connection.
+    pub fn resync_stats(&self) -> &ResyncStats {
+        self.decoder.resync_stats()
+    }
+
+    /// Current health of the reader.
+    pub fn state(&self) -> &ConnectionState {
+        &self.state
+    }
+
     /// Access the underlying reader.
     pub fn get_ref(&self) -> &R {
         &self.reader
@@ -114,10 +396,121 @@ impl<R: Read> PacketReader<R> {
     }
 }
 
+/// Buffer layout for [`PacketReader::with_vectored`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VectoredRead {
+    /// Number of buffers handed to each `read_vectored` call.
+    pub buffers: usize,
+    /// Size of each buffer; ideally a little above the typical frame length.
+    pub buffer_len: usize,
+}
+
+impl Default for VectoredRead {
+    fn default() -> Self {
+        Self {
+            buffers: 8,
+            buffer_len: 2048,
+        }
+    }
+}
+
+/// Bounds for [`PacketReader::with_adaptive`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdaptiveBuffer {
+    /// Smallest (and initial) read buffer size.
+    pub min: usize,
+    /// Largest read buffer size.
+    pub max: usize,
+    /// Consecutive under-used reads before the buffer is halved.
+    pub shrink_after: u32,
+}
+
+impl Default for AdaptiveBuffer {
+    fn default() -> Self {
+        Self {
+            min: 512,
+            max: 64 * 1024,
+            shrink_after: 8,
+        }
+    }
+}
+
+/// Per-call options for [`PacketReader::read_packet_with`] and its async
+/// counterpart.
+///
+/// Fields may be added in later releases, each defaulting to what
+/// `read_packet` does, so build values with the methods.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ReadOpts {
+    /// Give up with `TimedOut` if no packet arrives within this long.
+    pub timeout: Option<Duration>,
+    /// Pass over `Ping` and `Pong` packets instead of returning them.
+    pub skip_keepalive: bool,
+}
+
+impl ReadOpts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn skip_keepalive(mut self, skip: bool) -> Self {
+        self.skip_keepalive = skip;
+        self
+    }
+}
+
+/// A frame stayed incomplete past the reader's frame deadline.
+///
+/// Returned inside an `io::Error` of kind `TimedOut`; recover it with
+/// `err.get_ref().and_then(|inner| inner.downcast_ref::<FrameTimeout>())`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameTimeout {
+    /// Time since the frame's first bytes arrived.
+    pub elapsed: Duration,
+    pub limit: Duration,
+}
+
+impl fmt::Display for FrameTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "frame incomplete after {:?} (limit {:?})", self.elapsed, self.limit)
+    }
+}
+
+impl std::error::Error for FrameTimeout {}
+
+impl From<FrameTimeout> for io::Error {
+    fn from(timeout: FrameTimeout) -> Self {
+        io::Error::new(io::ErrorKind::TimedOut, timeout)
+    }
+}
+
+/// The error to report for the framing errors of one read, and whether it
+/// left the stream desynchronized. A desync is preferred over a rejected frame.
+pub(crate) fn framing_error(errors: &[FrameError]) -> Option<(io::Error, bool)> {
+    let err = errors.iter().find(|err| err.is_desync()).or(errors.first())?;
+    let message = format!("framing error: {:?}", err);
+    Some((io::Error::new(io::ErrorKind::InvalidData, message), err.is_desync()))
+}
+
+/// Errors that leave the stream intact and may succeed on retry.
+pub(crate) fn is_transient(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut | io::ErrorKind::Interrupted
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::codec;
+    use crate::header::HEADER_LEN;
     use crate::packet::Packet;
     use std::io::Cursor;
 
@@ -139,6 +532,7 @@ mod tests {
         assert_eq!(packet, Packet::Ping);
     }
 
+    #[cfg(feature = "text")]
     #[test]
     fn reads_multiple_packets() {
         let packets = vec![
@@ -156,6 +550,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "text")]
     #[test]
     fn handles_fragmented_reads() {
         // Create a reader that returns 1 byte at a time
@@ -196,5 +591,206 @@ mod tests {
 
         let err = reader.read_packet().unwrap_err();
         assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(reader.state(), &ConnectionState::Closed);
+    }
+
+    #[cfg(feature = "text")]
+    #[test]
+    fn reads_across_vectored_buffers() {
+        let packets = vec![
+            Packet::Data(vec![5; 40]),
+            Packet::Message("spans buffers".into()),
+            Packet::Ping,
+        ];
+        let wire_data = encode_packets(&packets);
+        let config = VectoredRead { buffers: 3, buffer_len: 16 };
+        let mut reader = PacketReader::with_vectored(Cursor::new(wire_data), config);
+
+        for expected in &packets {
+            assert_eq!(&reader.read_packet().unwrap(), expected);
+        }
+    }
+
+    /// Serves scripted chunks, one (or part of one) per read call.
+    struct ChunkedReader {
+        chunks: std::collections::VecDeque<Vec<u8>>,
+    }
+
+    impl Read for ChunkedReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let Some(chunk) = self.chunks.front_mut() else {
+                return Ok(0);
+            };
+            let n = buf.len().min(chunk.len());
+            buf[..n].copy_from_slice(&chunk[..n]);
+            chunk.drain(..n);
+            if chunk.is_empty() {
+                self.chunks.pop_front();
+            }
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn read_opts_skip_keepalives_and_time_out_stalls_by_the_clock() {
+        use crate::clock::MockClock;
+
+        /// Returns `WouldBlock` for the first `stalls` reads.
+        struct Stalling {
+            stalls: usize,
+            data: Cursor<Vec<u8>>,
+        }
+
+        impl Read for Stalling {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                if self.stalls > 0 {
+                    self.stalls -= 1;
+                    return Err(io::ErrorKind::WouldBlock.into());
+                }
+                self.data.read(buf)
+            }
+        }
+
+        let wire_data = encode_packets(&[Packet::Ping, Packet::Pong, Packet::Data(vec![5])]);
+        let mut reader = PacketReader::new(Stalling { stalls: 1, data: Cursor::new(wire_data) });
+        let err = reader.read_packet_with(&ReadOpts::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+
+        let clock = MockClock::new();
+        reader.set_clock(clock.clone());
+        reader.get_mut().stalls = 3;
+        let opts = ReadOpts::new().timeout(Duration::from_secs(1));
+        assert_eq!(reader.read_packet_with(&opts).unwrap_err().kind(), io::ErrorKind::WouldBlock);
+        clock.advance(Duration::from_millis(600));
+        assert_eq!(reader.read_packet_with(&opts).unwrap_err().kind(), io::ErrorKind::WouldBlock);
+        clock.advance(Duration::from_millis(600));
+        assert_eq!(reader.read_packet_with(&opts).unwrap_err().kind(), io::ErrorKind::TimedOut);
+
+        let opts = opts.skip_keepalive(true);
+        assert_eq!(reader.read_packet_with(&opts).unwrap(), Packet::Data(vec![5]));
+    }
+
+    #[test]
+    fn plain_reads_end_a_read_opts_wait() {
+        use crate::clock::MockClock;
+
+        /// Returns `WouldBlock` whenever it has nothing to hand out.
+        struct Gated(std::collections::VecDeque<Vec<u8>>);
+
+        impl Read for Gated {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                let Some(chunk) = self.0.pop_front() else {
+                    return Err(io::ErrorKind::WouldBlock.into());
+                };
+                buf[..chunk.len()].copy_from_slice(&chunk);
+                Ok(chunk.len())
+            }
+        }
+
+        let clock = MockClock::new();
+        let mut reader = PacketReader::new(Gated(Default::default()));
+        reader.set_clock(clock.clone());
+        let opts = ReadOpts::new().timeout(Duration::from_secs(1));
+        assert_eq!(reader.read_packet_with(&opts).unwrap_err().kind(), io::ErrorKind::WouldBlock);
+
+        reader.get_mut().0.push_back(encode_packets(&[Packet::Ping]));
+        assert_eq!(reader.read_packet().unwrap(), Packet::Ping);
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(reader.read_packet_with(&opts).unwrap_err().kind(), io::ErrorKind::WouldBlock);
+    }
+
+    #[test]
+    fn adaptive_buffer_grows_for_bulk_and_shrinks_when_idle() {
+        let ping = encode_packets(&[Packet::Ping]);
+        let mut chunks = vec![encode_packets(&[Packet::Data(vec![1; 4000])])];
+        chunks.extend(std::iter::repeat_n(ping, 2));
+        let source = ChunkedReader { chunks: chunks.into() };
+        let bounds = AdaptiveBuffer { min: 256, max: 2048, shrink_after: 2 };
+        let mut reader = PacketReader::with_adaptive(source, bounds);
+
+        assert_eq!(reader.read_buffer_len(), 256);
+        reader.read_packet().unwrap();
+        assert_eq!(reader.read_buffer_len(), 2048);
+
+        reader.read_packet().unwrap();
+        reader.read_packet().unwrap();
+        assert_eq!(reader.read_buffer_len(), 1024);
+    }
+
+    #[test]
+    fn rejected_frame_is_reported_without_poisoning() {
+        let mut wire_data = encode_packets(&[Packet::Data(vec![1, 2, 3]), Packet::Ping, Packet::Pong]);
+        wire_data[HEADER_LEN] ^= 0xFF; // corrupt the first payload byte
+        let mut reader = PacketReader::new(Cursor::new(wire_data));
+
+        assert_eq!(reader.read_packet().unwrap(), Packet::Ping); // Decoded in the same read as the error
+        assert_eq!(reader.read_packet().unwrap(), Packet::Pong);
+        let err = reader.read_packet().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(reader.state().is_healthy());
+        assert_eq!(reader.read_packet().unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn over_budget_frame_is_skipped_and_reader_stays_healthy() {
+        let wire_data = encode_packets(&[Packet::Data(vec![0; 64]), Packet::Ping]);
+        let mut reader = PacketReader::with_budget(Cursor::new(wire_data), MemoryBudget::new(16));
+
+        assert_eq!(reader.read_packet().unwrap(), Packet::Ping);
+        let err = reader.read_packet().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("BudgetExceeded"));
+        assert!(reader.state().is_healthy());
+    }
+
+    #[test]
+    fn poisons_after_losing_frame_sync() {
+        let mut wire_data = encode_packets(&[Packet::Data(vec![1, 2, 3]), Packet::Ping]);
+        wire_data[0] ^= 0xFF; // corrupt the first magic byte
+        let mut reader = PacketReader::new(Cursor::new(wire_data));
+
+        assert_eq!(reader.read_packet().unwrap(), Packet::Ping);
+        let err = reader.read_packet().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(reader.state().is_poisoned());
+
+        let err = reader.read_packet().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Other);
+    }
+
+    #[test]
+    fn frame_deadline_poisons_slow_frame() {
+        use crate::clock::MockClock;
+
+        /// Delivers one byte per read, advancing the clock 100ms each time.
+        struct Trickle {
+            bytes: Vec<u8>,
+            clock: MockClock,
+        }
+
+        impl Read for Trickle {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                if self.bytes.is_empty() {
+                    return Ok(0);
+                }
+                self.clock.advance(Duration::from_millis(100));
+                buf[0] = self.bytes.remove(0);
+                Ok(1)
+            }
+        }
+
+        let clock = MockClock::new();
+        let mut bytes = encode_packets(&[Packet::Ping]);
+        bytes.extend(encode_packets(&[Packet::Data(vec![0; 16])]));
+        let mut reader = PacketReader::new(Trickle { bytes, clock: clock.clone() });
+        reader.set_frame_deadline_with_clock(Duration::from_secs(1), clock);
+
+        // The 9-byte ping arrives within 800ms and passes
+        assert_eq!(reader.read_packet().unwrap(), Packet::Ping);
+        let err = reader.read_packet().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        let timeout = err.get_ref().and_then(|inner| inner.downcast_ref::<FrameTimeout>()).unwrap();
+        assert_eq!(timeout.elapsed, Duration::from_millis(1100));
+        assert!(reader.state().is_poisoned());
     }
 }
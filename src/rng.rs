@@ -0,0 +1,135 @@
+//! Random number source abstraction.
+//!
+//! Code that needs randomness takes an [`Rng`] so the source is explicit:
+//! `OsRng` for anything security-relevant, [`SeededRng`] where runs must
+//! be reproducible (tests, soak traffic, fault injection).
+//!
+//! `OsRng` only exists where a real CSPRNG backs it: with the `getrandom`
+//! feature on any target that crate supports, otherwise with `std` on Unix.
+//! There is no fallback, so a build that would get weak randomness fails
+//! to compile instead.
+
+/// Source of random bytes.
+pub trait Rng {
+    fn next_u64(&mut self) -> u64;
+
+    /// Fill `dest` with random bytes.
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    /// Value in `0..bound`, by taking `next_u64` modulo `bound`.
+    ///
+    /// Unless `bound` is a power of two, low values are slightly more likely
+    /// (by up to `bound / 2^64`): fine for test traffic and jitter, not for
+    /// sampling that must be exactly uniform.
+    ///
+    /// # Panics
+    ///
+    /// If `bound` is zero.
+    fn below(&mut self, bound: u64) -> u64 {
+        assert!(bound != 0, "Rng::below needs a non-zero bound");
+        self.next_u64() % bound
+    }
+}
+
+/// Randomness from the operating system's CSPRNG.
+///
+/// With the `getrandom` feature this asks the platform through the
+/// `getrandom` crate (which on `wasm32-unknown-unknown` also needs its own
+/// `js` feature); otherwise, on Unix, it reads `/dev/urandom`. Either
+/// way it panics if no randomness can be had, since silently degrading key
+/// material is worse than failing.
+#[cfg(any(feature = "getrandom", all(feature = "std", unix)))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsRng;
+
+#[cfg(any(feature = "getrandom", all(feature = "std", unix)))]
+impl Rng for OsRng {
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    #[cfg(feature = "getrandom")]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if let Err(err) = getrandom::getrandom(dest) {
+            panic!("failed to read randomness from the platform: {err}");
+        }
+    }
+
+    #[cfg(not(feature = "getrandom"))]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        use std::io::Read;
+
+        std::fs::File::open("/dev/urandom")
+            .and_then(|mut urandom| urandom.read_exact(dest))
+            .expect("failed to read randomness from /dev/urandom");
+    }
+}
+
+/// Deterministic generator (xorshift64*) for reproducible runs.
+#[derive(Debug, Clone)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        // Spread the seed with one splitmix64 round; xorshift must never hold zero.
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        Self { state: if z == 0 { 0x9E37_79B9_7F4A_7C15 } else { z } }
+    }
+}
+
+impl Rng for SeededRng {
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeded_rng_is_reproducible() {
+        let mut a = SeededRng::new(42);
+        let mut b = SeededRng::new(42);
+        let mut c = SeededRng::new(43);
+        let first = a.next_u64();
+        assert_eq!(first, b.next_u64());
+        assert_ne!(first, c.next_u64());
+    }
+
+    #[test]
+    fn fills_partial_chunks() {
+        let mut rng = SeededRng::new(7);
+        let mut bytes = [0u8; 13];
+        rng.fill_bytes(&mut bytes);
+        assert!(bytes.iter().any(|&b| b != 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "non-zero bound")]
+    fn below_rejects_a_zero_bound() {
+        SeededRng::new(1).below(0);
+    }
+
+    #[cfg(any(feature = "getrandom", all(feature = "std", unix)))]
+    #[test]
+    fn os_rng_produces_distinct_values() {
+        let mut rng = OsRng;
+        assert_ne!(rng.next_u64(), rng.next_u64());
+    }
+}
@@ -0,0 +1,351 @@
+//! Declarative traffic scenarios for protocol acceptance tests.
+//!
+//! A [`Scenario`] is a list of steps (send a packet, expect a packet
+//! within a deadline, repeat a block) built in code or parsed from a small
+//! line-based script:
+//!
+//! ```text
+//! # handshake, then three pings
+//! send data 68656c6c6f
+//! expect data 68656c6c6f within 500ms
+//! repeat 3
+//!   send ping
+//!   expect pong within 100ms
+//! end
+//! ```
+//!
+//! Packets are written `ping`, `pong`, `data <hex>` or `message <text>`.
+//! [`run`] plays a scenario over any packet reader/writer pair, so it works
+//! against live endpoints as well as in-memory pipes.
+
+use std::io::{self, Read, Write};
+use std::time::Duration;
+
+use crate::clock::{Clock, SystemClock};
+use crate::packet::Packet;
+use crate::reader::PacketReader;
+use crate::writer::PacketWriter;
+
+/// Deadline used by `expect` steps that do not give one.
+pub const DEFAULT_EXPECT_WITHIN: Duration = Duration::from_secs(5);
+
+/// One step of a [`Scenario`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    Send(Packet),
+    /// The next packet received must equal `packet` and arrive within `within`.
+    Expect { packet: Packet, within: Duration },
+    Repeat { times: u32, steps: Vec<Step> },
+}
+
+/// An ordered list of steps.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Scenario {
+    pub steps: Vec<Step>,
+}
+
+impl Scenario {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn send(mut self, packet: Packet) -> Self {
+        self.steps.push(Step::Send(packet));
+        self
+    }
+
+    pub fn expect(mut self, packet: Packet, within: Duration) -> Self {
+        self.steps.push(Step::Expect { packet, within });
+        self
+    }
+
+    pub fn repeat(mut self, times: u32, body: Scenario) -> Self {
+        self.steps.push(Step::Repeat { times, steps: body.steps });
+        self
+    }
+
+    /// Parse the script format described in the [module docs](self).
+    pub fn parse(script: &str) -> Result<Self, ScenarioError> {
+        let mut stack: Vec<(u32, Vec<Step>)> = vec![(1, Vec::new())];
+        for (index, raw) in script.lines().enumerate() {
+            let line = index + 1;
+            let text = raw.trim();
+            if text.is_empty() || text.starts_with('#') {
+                continue;
+            }
+            let parse_error = |reason: &str| ScenarioError::Parse { line, reason: reason.to_string() };
+            let (command, rest) = text.split_once(' ').unwrap_or((text, ""));
+            match command {
+                "send" => {
+                    let packet = parse_packet(rest.trim()).map_err(|reason| parse_error(&reason))?;
+                    stack.last_mut().unwrap().1.push(Step::Send(packet));
+                }
+                "expect" => {
+                    let (packet, within) = match rest.rsplit_once(" within ") {
+                        Some((packet, within)) => (packet, parse_duration(within.trim()).map_err(|r| parse_error(&r))?),
+                        None => (rest, DEFAULT_EXPECT_WITHIN),
+                    };
+                    let packet = parse_packet(packet.trim()).map_err(|reason| parse_error(&reason))?;
+                    stack.last_mut().unwrap().1.push(Step::Expect { packet, within });
+                }
+                "repeat" => {
+                    let times = rest.trim().parse().map_err(|_| parse_error("repeat needs a count"))?;
+                    stack.push((times, Vec::new()));
+                }
+                "end" => {
+                    if stack.len() == 1 {
+                        return Err(parse_error("`end` without `repeat`"));
+                    }
+                    let (times, steps) = stack.pop().unwrap();
+                    stack.last_mut().unwrap().1.push(Step::Repeat { times, steps });
+                }
+                other => return Err(parse_error(&format!("unknown step `{other}`"))),
+            }
+        }
+        if stack.len() > 1 {
+            let line = script.lines().count();
+            return Err(ScenarioError::Parse { line, reason: "`repeat` without `end`".to_string() });
+        }
+        Ok(Self { steps: stack.pop().unwrap().1 })
+    }
+}
+
+fn parse_packet(text: &str) -> Result<Packet, String> {
+    let (kind, arg) = text.split_once(' ').unwrap_or((text, ""));
+    match kind {
+        "ping" => Ok(Packet::Ping),
+        "pong" => Ok(Packet::Pong),
+        "data" => parse_hex(arg.trim()).map(Packet::Data),
+        #[cfg(feature = "text")]
+        "message" => Ok(Packet::Message(arg.to_string())),
+        other => Err(format!("unknown packet `{other}`")),
+    }
+}
+
+fn parse_hex(text: &str) -> Result<Vec<u8>, String> {
+    if text.len() % 2 != 0 {
+        return Err("hex payload needs an even number of digits".to_string());
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).map_err(|_| format!("invalid hex `{text}`")))
+        .collect()
+}
+
+fn parse_duration(text: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid duration `{text}` (use e.g. 250ms or 2s)");
+    if let Some(ms) = text.strip_suffix("ms") {
+        return ms.parse().map(Duration::from_millis).map_err(|_| invalid());
+    }
+    if let Some(secs) = text.strip_suffix('s') {
+        return secs.parse().map(Duration::from_secs).map_err(|_| invalid());
+    }
+    Err(invalid())
+}
+
+/// Summary of a completed scenario.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScenarioReport {
+    pub packets_sent: u64,
+    pub packets_received: u64,
+    pub elapsed: Duration,
+}
+
+/// Why a scenario could not be parsed or did not pass.
+#[derive(Debug)]
+pub enum ScenarioError {
+    /// The script is malformed at `line` (1-based).
+    Parse { line: usize, reason: String },
+    Io(io::Error),
+    /// Expect step number `step` (counting every executed step from 1) got the wrong packet.
+    Mismatch { step: u64, expected: Packet, actual: Packet },
+    /// Expect step number `step` got nothing within its deadline.
+    Timeout { step: u64, within: Duration },
+}
+
+impl From<io::Error> for ScenarioError {
+    fn from(err: io::Error) -> Self {
+        ScenarioError::Io(err)
+    }
+}
+
+impl core::fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ScenarioError::Parse { line, reason } => write!(f, "scenario line {line}: {reason}"),
+            ScenarioError::Io(err) => write!(f, "scenario I/O error: {err}"),
+            ScenarioError::Mismatch { step, expected, actual } => {
+                write!(f, "step {step}: expected {expected:?}, got {actual:?}")
+            }
+            ScenarioError::Timeout { step, within } => write!(f, "step {step}: nothing received within {within:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ScenarioError {}
+
+/// Play `scenario` over `reader`/`writer`.
+///
+/// A blocking reader only notices a deadline between reads, so give live
+/// sockets a read timeout (e.g. 50 ms) shorter than the `expect` deadlines;
+/// `TimedOut` and `WouldBlock` reads are retried until the deadline passes.
+pub fn run<R: Read, W: Write>(
+    scenario: &Scenario,
+    reader: &mut PacketReader<R>,
+    writer: &mut PacketWriter<W>,
+) -> Result<ScenarioReport, ScenarioError> {
+    run_with_clock(scenario, reader, writer, &SystemClock)
+}
+
+/// Like [`run`], measuring deadlines against `clock`.
+pub fn run_with_clock<R: Read, W: Write>(
+    scenario: &Scenario,
+    reader: &mut PacketReader<R>,
+    writer: &mut PacketWriter<W>,
+    clock: &dyn Clock,
+) -> Result<ScenarioReport, ScenarioError> {
+    let started = clock.now();
+    let mut player = Player { reader, writer, clock, report: ScenarioReport::default(), step: 0 };
+    player.play(&scenario.steps)?;
+    player.report.elapsed = clock.now() - started;
+    Ok(player.report)
+}
+
+struct Player<'a, R, W> {
+    reader: &'a mut PacketReader<R>,
+    writer: &'a mut PacketWriter<W>,
+    clock: &'a dyn Clock,
+    report: ScenarioReport,
+    step: u64,
+}
+
+impl<R: Read, W: Write> Player<'_, R, W> {
+    fn play(&mut self, steps: &[Step]) -> Result<(), ScenarioError> {
+        for step in steps {
+            match step {
+                Step::Send(packet) => {
+                    self.step += 1;
+                    self.writer.write_packet(packet)?;
+                    self.writer.flush()?;
+                    self.report.packets_sent += 1;
+                }
+                Step::Expect { packet, within } => {
+                    self.step += 1;
+                    let actual = self.receive(*within)?;
+                    if actual != *packet {
+                        return Err(ScenarioError::Mismatch { step: self.step, expected: packet.clone(), actual });
+                    }
+                    self.report.packets_received += 1;
+                }
+                Step::Repeat { times, steps } => {
+                    for _ in 0..*times {
+                        self.play(steps)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn receive(&mut self, within: Duration) -> Result<Packet, ScenarioError> {
+        let deadline = self.clock.now() + within;
+        loop {
+            match self.reader.read_packet() {
+                Ok(packet) if self.clock.now() <= deadline => return Ok(packet),
+                Ok(_) => return Err(ScenarioError::Timeout { step: self.step, within }),
+                Err(err) if matches!(err.kind(), io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock) => {
+                    if self.clock.now() >= deadline {
+                        return Err(ScenarioError::Timeout { step: self.step, within });
+                    }
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::codec;
+    use std::io::Cursor;
+
+    fn wire(packets: &[Packet]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for packet in packets {
+            codec::encode(packet, &mut bytes).unwrap();
+        }
+        bytes
+    }
+
+    #[test]
+    fn parses_script() {
+        let script = "
+            # comment
+            send data 0102
+            repeat 2
+              send ping
+              expect pong within 250ms
+            end
+            expect data ff within 2s
+        ";
+        let expected = Scenario::new()
+            .send(Packet::Data(vec![1, 2]))
+            .repeat(2, Scenario::new().send(Packet::Ping).expect(Packet::Pong, Duration::from_millis(250)))
+            .expect(Packet::Data(vec![0xFF]), Duration::from_secs(2));
+        assert_eq!(Scenario::parse(script).unwrap(), expected);
+    }
+
+    #[test]
+    fn reports_parse_errors_with_line() {
+        let err = Scenario::parse("send ping\nrepeat 2\nsend bogus\nend").unwrap_err();
+        assert!(matches!(err, ScenarioError::Parse { line: 3, .. }));
+        assert!(matches!(Scenario::parse("end").unwrap_err(), ScenarioError::Parse { line: 1, .. }));
+        assert!(matches!(Scenario::parse("repeat 2\nsend ping").unwrap_err(), ScenarioError::Parse { .. }));
+    }
+
+    #[test]
+    fn plays_against_scripted_peer() {
+        let scenario = Scenario::parse("repeat 3\nsend ping\nexpect pong\nend").unwrap();
+        let mut reader = PacketReader::new(Cursor::new(wire(&[Packet::Pong, Packet::Pong, Packet::Pong])));
+        let mut writer = PacketWriter::new(Vec::new());
+
+        let report = run(&scenario, &mut reader, &mut writer).unwrap();
+        assert_eq!(report.packets_sent, 3);
+        assert_eq!(report.packets_received, 3);
+        assert_eq!(writer.get_ref(), &wire(&[Packet::Ping, Packet::Ping, Packet::Ping]));
+    }
+
+    #[test]
+    fn reports_mismatch_step() {
+        let scenario = Scenario::new().send(Packet::Ping).expect(Packet::Pong, DEFAULT_EXPECT_WITHIN);
+        let mut reader = PacketReader::new(Cursor::new(wire(&[Packet::Ping])));
+        let mut writer = PacketWriter::new(Vec::new());
+
+        let err = run(&scenario, &mut reader, &mut writer).unwrap_err();
+        assert!(matches!(err, ScenarioError::Mismatch { step: 2, actual: Packet::Ping, .. }));
+    }
+
+    /// Source that never delivers data, advancing a mock clock on each attempt.
+    struct SilentPeer(MockClock);
+
+    impl Read for SilentPeer {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            self.0.advance(Duration::from_millis(40));
+            Err(io::Error::new(io::ErrorKind::TimedOut, "read timed out"))
+        }
+    }
+
+    #[test]
+    fn times_out_silent_peer() {
+        let clock = MockClock::new();
+        let scenario = Scenario::new().expect(Packet::Pong, Duration::from_millis(100));
+        let mut reader = PacketReader::new(SilentPeer(clock.clone()));
+        let mut writer = PacketWriter::new(Vec::new());
+
+        let err = run_with_clock(&scenario, &mut reader, &mut writer, &clock).unwrap_err();
+        assert!(matches!(err, ScenarioError::Timeout { step: 1, .. }));
+        assert_eq!(clock.elapsed(), Duration::from_millis(120));
+    }
+}
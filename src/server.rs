@@ -0,0 +1,655 @@
+//! Async TCP server built on the Tokio packet reader and writer (`tokio` feature).
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncWrite, DuplexStream};
+use tokio::net::{TcpListener, ToSocketAddrs};
+use tokio::sync::watch;
+use tokio::task::{JoinHandle, JoinSet};
+use tokio::time::Instant;
+
+use crate::async_io::{AsyncPacketReader, AsyncPacketWriter, Connection};
+use crate::events::{CloseCode, ConnectionEvents, NoEvents};
+use crate::extensions::Extensions;
+use crate::packet::Packet;
+
+/// Application logic for a [`PacketServer`].
+///
+/// Implementations may be written with `async fn`:
+///
+/// ```no_run
+/// use std::net::SocketAddr;
+/// use byteframe::server::{PacketHandler, PacketServer};
+/// use byteframe::Packet;
+///
+/// struct Echo;
+///
+/// impl PacketHandler for Echo {
+///     async fn on_packet(&self, _peer: SocketAddr, packet: Packet) -> Option<Packet> {
+///         match packet {
+///             Packet::Ping => Some(Packet::Pong),
+///             other => Some(other),
+///         }
+///     }
+/// }
+///
+/// # async fn run() -> std::io::Result<()> {
+/// PacketServer::bind("127.0.0.1:8080").await?.serve(Echo).await
+/// # }
+/// ```
+pub trait PacketHandler: Send + Sync + 'static {
+    /// Handle one packet from `peer`; a returned packet is sent back to it.
+    ///
+    /// The default ignores the packet; implement this or
+    /// [`on_packet_with`](Self::on_packet_with).
+    fn on_packet(&self, peer: SocketAddr, packet: Packet) -> impl Future<Output = Option<Packet>> + Send {
+        let _ = (peer, packet);
+        async { None }
+    }
+
+    /// Handle one packet with access to its connection's [`ConnectionContext`].
+    ///
+    /// Override this instead of [`on_packet`](Self::on_packet) to keep
+    /// state across the packets of one connection. The default forwards to
+    /// `on_packet`.
+    fn on_packet_with(
+        &self,
+        ctx: &mut ConnectionContext,
+        packet: Packet,
+    ) -> impl Future<Output = Option<Packet>> + Send {
+        self.on_packet(ctx.peer(), packet)
+    }
+}
+
+/// What a [`PacketHandler`] can see and keep for one connection.
+///
+/// ```
+/// use byteframe::server::{ConnectionContext, PacketHandler};
+/// use byteframe::Packet;
+///
+/// #[derive(Default)]
+/// struct Received(u64);
+///
+/// struct CountingEcho;
+///
+/// impl PacketHandler for CountingEcho {
+///     async fn on_packet_with(&self, ctx: &mut ConnectionContext, packet: Packet) -> Option<Packet> {
+///         ctx.extensions_mut().get_or_insert_default::<Received>().0 += 1;
+///         Some(packet)
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ConnectionContext {
+    peer: SocketAddr,
+    extensions: Extensions,
+}
+
+impl ConnectionContext {
+    fn new(peer: SocketAddr) -> Self {
+        Self { peer, extensions: Extensions::new() }
+    }
+
+    pub fn peer(&self) -> SocketAddr {
+        self.peer
+    }
+
+    /// State stored for this connection; dropped when it closes.
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
+}
+
+/// Accepts connections and runs a [`PacketHandler`] for each of them.
+pub struct PacketServer {
+    listener: TcpListener,
+    shutdown: Arc<watch::Sender<bool>>,
+    events: Arc<dyn ConnectionEvents>,
+    idle_timeout: Option<Duration>,
+    limits: ConnectionLimits,
+}
+
+/// Accept-time limits for a [`PacketServer`]; everything is unlimited by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConnectionLimits {
+    /// Most connections served at once.
+    pub max_connections: Option<usize>,
+    /// Most connections served at once from one IP address.
+    pub max_per_ip: Option<usize>,
+    /// Cap on how fast new connections are accepted.
+    pub accept_rate: Option<AcceptRate>,
+}
+
+/// Token-bucket limit on accepted connections.
+///
+/// Up to `burst` connections are accepted back to back, then one per
+/// `1 / per_second`. Excess connections wait in the listen backlog rather
+/// than being refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AcceptRate {
+    pub per_second: u32,
+    pub burst: u32,
+}
+
+impl PacketServer {
+    pub async fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self::from_listener(TcpListener::bind(addr).await?))
+    }
+
+    pub fn from_listener(listener: TcpListener) -> Self {
+        Self {
+            listener,
+            shutdown: Arc::new(watch::channel(false).0),
+            events: Arc::new(NoEvents),
+            idle_timeout: None,
+            limits: ConnectionLimits::default(),
+        }
+    }
+
+    /// Refuse or delay connections beyond `limits`.
+    ///
+    /// A connection over `max_connections` or `max_per_ip` is closed right
+    /// after accept and reported to [`ConnectionEvents::on_close`] with
+    /// [`CloseCode::Rejected`].
+    pub fn limits(mut self, limits: ConnectionLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Report every connection's lifecycle to `events`.
+    pub fn with_events(mut self, events: impl ConnectionEvents) -> Self {
+        self.events = Arc::new(events);
+        self
+    }
+
+    /// Call [`ConnectionEvents::on_idle`] whenever a connection has been
+    /// silent for `timeout`. Idle connections are not closed.
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// A handle that stops [`serve`](Self::serve) from another task.
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        ShutdownHandle(Arc::clone(&self.shutdown))
+    }
+
+    /// Accept connections until shutdown is requested.
+    ///
+    /// Each connection gets its own task reading packets and passing them to
+    /// `handler` one at a time. On shutdown the server stops accepting,
+    /// lets in-progress handler calls finish and send their replies, closes
+    /// every connection, and returns once all connection tasks are done.
+    pub async fn serve<H: PacketHandler>(self, handler: H) -> io::Result<()> {
+        let handler = Arc::new(handler);
+        let mut stop = self.shutdown.subscribe();
+        let mut connections = JoinSet::new();
+        let admission = Arc::new(Mutex::new(Admission::new(self.limits)));
+        let mut throttle = self.limits.accept_rate.map(AcceptThrottle::new);
+
+        while !*stop.borrow() {
+            let delay = throttle.as_ref().map_or(Duration::ZERO, |throttle| throttle.delay(Instant::now()));
+            if !delay.is_zero() {
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => {}
+                    _ = stop.changed() => {}
+                }
+                continue;
+            }
+            tokio::select! {
+                accepted = self.listener.accept() => {
+                    let (stream, peer) = accepted?;
+                    if let Some(throttle) = &mut throttle {
+                        throttle.record(Instant::now());
+                    }
+                    let Some(slot) = Admission::admit(&admission, peer.ip()) else {
+                        drop(stream);
+                        self.events.on_close(Some(peer), CloseCode::Rejected, "connection limit reached");
+                        continue;
+                    };
+                    let handler = Arc::clone(&handler);
+                    let setup = ConnectionSetup {
+                        peer,
+                        events: Arc::clone(&self.events),
+                        idle_timeout: self.idle_timeout,
+                        slot: Some(slot),
+                    };
+                    let _ = stream.set_nodelay(true);
+                    let (read_half, write_half) = stream.into_split();
+                    connections.spawn(serve_connection(
+                        AsyncPacketReader::new(read_half),
+                        AsyncPacketWriter::new(write_half),
+                        setup,
+                        handler,
+                        self.shutdown.subscribe(),
+                    ));
+                }
+                _ = stop.changed() => {}
+            }
+            while connections.try_join_next().is_some() {} // Reap finished connections
+        }
+
+        while connections.join_next().await.is_some() {}
+        Ok(())
+    }
+}
+
+/// Live connection counts checked against [`ConnectionLimits`].
+struct Admission {
+    limits: ConnectionLimits,
+    total: usize,
+    per_ip: HashMap<IpAddr, usize>,
+}
+
+impl Admission {
+    fn new(limits: ConnectionLimits) -> Self {
+        Self { limits, total: 0, per_ip: HashMap::new() }
+    }
+
+    /// Count a connection from `ip`, or `None` if it is over a limit.
+    fn admit(admission: &Arc<Mutex<Admission>>, ip: IpAddr) -> Option<Slot> {
+        let mut counts = admission.lock().unwrap();
+        let from_ip = counts.per_ip.get(&ip).copied().unwrap_or(0);
+        if counts.limits.max_connections.is_some_and(|max| counts.total >= max)
+            || counts.limits.max_per_ip.is_some_and(|max| from_ip >= max)
+        {
+            return None;
+        }
+        counts.total += 1;
+        counts.per_ip.insert(ip, from_ip + 1);
+        Some(Slot { admission: Arc::clone(admission), ip })
+    }
+}
+
+/// A counted connection; dropping it frees the slot, even if the task panicked.
+struct Slot {
+    admission: Arc<Mutex<Admission>>,
+    ip: IpAddr,
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        let mut counts = self.admission.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        counts.total -= 1;
+        if let Some(count) = counts.per_ip.get_mut(&self.ip) {
+            *count -= 1;
+            if *count == 0 {
+                counts.per_ip.remove(&self.ip);
+            }
+        }
+    }
+}
+
+/// Generic cell rate algorithm form of the [`AcceptRate`] token bucket.
+struct AcceptThrottle {
+    interval: Duration,
+    burst_window: Duration,
+    next_free: Option<Instant>, // When the bucket would be full again
+}
+
+impl AcceptThrottle {
+    fn new(rate: AcceptRate) -> Self {
+        let interval = Duration::from_secs(1) / rate.per_second.max(1);
+        Self {
+            interval,
+            burst_window: interval * rate.burst.max(1).saturating_sub(1),
+            next_free: None,
+        }
+    }
+
+    /// How long to wait before the next accept is allowed.
+    fn delay(&self, now: Instant) -> Duration {
+        match self.next_free {
+            Some(next_free) => next_free.saturating_duration_since(now + self.burst_window),
+            None => Duration::ZERO,
+        }
+    }
+
+    fn record(&mut self, now: Instant) {
+        let base = self.next_free.map_or(now, |next_free| next_free.max(now));
+        self.next_free = Some(base + self.interval);
+    }
+}
+
+/// Requests graceful shutdown of a [`PacketServer`].
+#[derive(Clone)]
+pub struct ShutdownHandle(Arc<watch::Sender<bool>>);
+
+impl ShutdownHandle {
+    pub fn shutdown(&self) {
+        self.0.send_replace(true);
+    }
+}
+
+struct ConnectionSetup {
+    peer: SocketAddr,
+    events: Arc<dyn ConnectionEvents>,
+    idle_timeout: Option<Duration>,
+    slot: Option<Slot>, // None for connections that bypass admission
+}
+
+async fn serve_connection<H, R, W>(
+    mut reader: AsyncPacketReader<R>,
+    mut writer: AsyncPacketWriter<W>,
+    setup: ConnectionSetup,
+    handler: Arc<H>,
+    mut stop: watch::Receiver<bool>,
+) where
+    H: PacketHandler,
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let ConnectionSetup { peer, events, idle_timeout, slot } = setup;
+    let mut ctx = ConnectionContext::new(peer);
+    events.on_connect(Some(peer));
+    events.on_handshake_complete(Some(peer));
+
+    let shutting_down = || (CloseCode::Shutdown, "server shutting down".to_string());
+    let (code, reason) = loop {
+        if *stop.borrow() {
+            break shutting_down();
+        }
+        let idle = async {
+            match idle_timeout {
+                Some(timeout) => tokio::time::sleep(timeout).await,
+                None => std::future::pending().await,
+            }
+        };
+        let packet = tokio::select! {
+            result = reader.read_packet() => match result {
+                Ok(packet) => packet,
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                    break (CloseCode::PeerClosed, err.to_string());
+                }
+                Err(err) => {
+                    events.on_error(Some(peer), &err);
+                    break (CloseCode::Error, err.to_string());
+                }
+            },
+            _ = stop.changed() => break shutting_down(),
+            _ = idle => {
+                events.on_idle(Some(peer), idle_timeout.unwrap_or_default());
+                continue;
+            }
+        };
+        if let Some(reply) = handler.on_packet_with(&mut ctx, packet).await {
+            let written = match writer.write_packet(&reply).await {
+                Ok(()) => writer.flush().await,
+                Err(err) => Err(err),
+            };
+            if let Err(err) = written {
+                events.on_error(Some(peer), &err);
+                break (CloseCode::Error, err.to_string());
+            }
+        }
+    };
+    let _ = writer.close().await;
+    drop(slot); // Free the slot before anyone hears about the close
+    events.on_close(Some(peer), code, &reason);
+}
+
+/// A client wired straight to a [`PacketHandler`] through an in-memory duplex.
+///
+/// Packets go through the real framing and the same per-connection loop a
+/// [`PacketServer`] runs, but without sockets or threads, so handler logic
+/// can be unit tested one packet at a time.
+///
+/// ```
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> std::io::Result<()> {
+/// use std::net::SocketAddr;
+/// use byteframe::server::{LocalClient, PacketHandler};
+/// use byteframe::Packet;
+///
+/// struct Echo;
+///
+/// impl PacketHandler for Echo {
+///     async fn on_packet(&self, _peer: SocketAddr, packet: Packet) -> Option<Packet> {
+///         Some(packet)
+///     }
+/// }
+///
+/// let mut client = LocalClient::new(Echo);
+/// assert_eq!(client.request(&Packet::Ping).await?, Packet::Ping);
+/// client.close().await
+/// # }
+/// ```
+pub struct LocalClient {
+    connection: Connection<DuplexStream>,
+    _stop: watch::Sender<bool>, // Held open; the connection loop ends on EOF instead
+    task: JoinHandle<()>,
+}
+
+impl LocalClient {
+    /// Connect to `handler`, which sees the peer address `127.0.0.1:0`.
+    pub fn new<H: PacketHandler>(handler: H) -> Self {
+        Self::with_peer(handler, SocketAddr::from(([127, 0, 0, 1], 0)))
+    }
+
+    /// Connect to `handler`, which sees `peer` as the client's address.
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub fn with_peer<H: PacketHandler>(handler: H, peer: SocketAddr) -> Self {
+        let (local, remote) = tokio::io::duplex(64 * 1024);
+        let (read_half, write_half) = tokio::io::split(remote);
+        let (stop_tx, stop) = watch::channel(false);
+        let setup = ConnectionSetup { peer, events: Arc::new(NoEvents), idle_timeout: None, slot: None };
+        let task = tokio::spawn(serve_connection(
+            AsyncPacketReader::new(read_half),
+            AsyncPacketWriter::new(write_half),
+            setup,
+            Arc::new(handler),
+            stop,
+        ));
+        Self { connection: Connection::new(local), _stop: stop_tx, task }
+    }
+
+    /// Send one packet to the handler.
+    pub async fn send(&mut self, packet: &Packet) -> io::Result<()> {
+        self.connection.write_packet(packet).await?;
+        self.connection.flush().await
+    }
+
+    /// Wait for the next packet the handler sent back.
+    pub async fn recv(&mut self) -> io::Result<Packet> {
+        self.connection.read_packet().await
+    }
+
+    /// Send one packet and wait for the reply.
+    pub async fn request(&mut self, packet: &Packet) -> io::Result<Packet> {
+        self.send(packet).await?;
+        self.recv().await
+    }
+
+    /// Close the connection and wait for the handler to finish.
+    ///
+    /// Every packet already sent is handled first; replies not yet read with
+    /// [`recv`](Self::recv) are discarded. A panic inside the handler is
+    /// propagated here.
+    pub async fn close(self) -> io::Result<()> {
+        let (mut reader, mut writer) = self.connection.split();
+        writer.close().await?;
+        while reader.read_packet().await.is_ok() {}
+        match self.task.await {
+            Ok(()) => Ok(()),
+            Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+            Err(err) => Err(io::Error::new(io::ErrorKind::Other, err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::tests::Recorder;
+    use tokio::net::TcpStream;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Counter(Arc<AtomicUsize>);
+
+    impl PacketHandler for Counter {
+        async fn on_packet(&self, _peer: SocketAddr, packet: Packet) -> Option<Packet> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            match packet {
+                Packet::Ping => Some(Packet::Pong),
+                Packet::Data(bytes) => Some(Packet::Data(bytes.into_iter().rev().collect())),
+                _ => None,
+            }
+        }
+    }
+
+    async fn client(addr: SocketAddr) -> (AsyncPacketReader<tokio::net::tcp::OwnedReadHalf>, AsyncPacketWriter<tokio::net::tcp::OwnedWriteHalf>) {
+        let (read_half, write_half) = TcpStream::connect(addr).await.unwrap().into_split();
+        (AsyncPacketReader::new(read_half), AsyncPacketWriter::new(write_half))
+    }
+
+    /// Replies to each packet with how many its connection has sent so far.
+    struct PerConnection;
+
+    impl PacketHandler for PerConnection {
+        async fn on_packet_with(&self, ctx: &mut ConnectionContext, _packet: Packet) -> Option<Packet> {
+            let seen = ctx.extensions_mut().get_or_insert_default::<u8>();
+            *seen += 1;
+            Some(Packet::Data(vec![*seen]))
+        }
+    }
+
+    #[tokio::test]
+    async fn extensions_are_scoped_to_one_connection() {
+        let mut first = LocalClient::new(PerConnection);
+        let mut second = LocalClient::new(PerConnection);
+
+        assert_eq!(first.request(&Packet::Ping).await.unwrap(), Packet::Data(vec![1]));
+        assert_eq!(first.request(&Packet::Ping).await.unwrap(), Packet::Data(vec![2]));
+        assert_eq!(second.request(&Packet::Ping).await.unwrap(), Packet::Data(vec![1]));
+        first.close().await.unwrap();
+        second.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn serves_clients_and_shuts_down_gracefully() {
+        let server = PacketServer::bind("127.0.0.1:0").await.unwrap();
+        let addr = server.local_addr().unwrap();
+        let shutdown = server.shutdown_handle();
+        let handled = Arc::new(AtomicUsize::new(0));
+        let serving = tokio::spawn(server.serve(Counter(Arc::clone(&handled))));
+
+        let (mut reader_a, mut writer_a) = client(addr).await;
+        let (mut reader_b, mut writer_b) = client(addr).await;
+
+        writer_a.write_packet(&Packet::Ping).await.unwrap();
+        writer_b.write_packet(&Packet::Data(vec![1, 2, 3])).await.unwrap();
+        assert_eq!(reader_a.read_packet().await.unwrap(), Packet::Pong);
+        assert_eq!(reader_b.read_packet().await.unwrap(), Packet::Data(vec![3, 2, 1]));
+
+        shutdown.shutdown();
+        serving.await.unwrap().unwrap();
+
+        assert_eq!(reader_a.read_packet().await.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(reader_b.read_packet().await.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(handled.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn reports_lifecycle_events() {
+        let recorder = Arc::new(Recorder::default());
+        let server = PacketServer::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .with_events(Arc::clone(&recorder))
+            .idle_timeout(Duration::from_millis(20));
+        let addr = server.local_addr().unwrap();
+        let shutdown = server.shutdown_handle();
+        let serving = tokio::spawn(server.serve(Counter(Arc::new(AtomicUsize::new(0)))));
+
+        let (mut reader, mut writer) = client(addr).await;
+        writer.write_packet(&Packet::Ping).await.unwrap();
+        assert_eq!(reader.read_packet().await.unwrap(), Packet::Pong);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        drop((reader, writer));
+        while !recorder.events().iter().any(|event| event.starts_with("close")) {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        shutdown.shutdown();
+        serving.await.unwrap().unwrap();
+
+        let events = recorder.events();
+        assert_eq!(events[..3], ["connect", "ready", "idle"]);
+        assert_eq!(events.last().unwrap(), "close PeerClosed");
+    }
+
+    #[tokio::test]
+    async fn refuses_connections_over_per_ip_limit() {
+        let recorder = Arc::new(Recorder::default());
+        let limits = ConnectionLimits { max_per_ip: Some(1), ..ConnectionLimits::default() };
+        let server = PacketServer::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .with_events(Arc::clone(&recorder))
+            .limits(limits);
+        let addr = server.local_addr().unwrap();
+        let shutdown = server.shutdown_handle();
+        let serving = tokio::spawn(server.serve(Counter(Arc::new(AtomicUsize::new(0)))));
+
+        let (mut reader_a, mut writer_a) = client(addr).await;
+        writer_a.write_packet(&Packet::Ping).await.unwrap();
+        assert_eq!(reader_a.read_packet().await.unwrap(), Packet::Pong);
+
+        let (mut reader_b, _writer_b) = client(addr).await;
+        assert_eq!(reader_b.read_packet().await.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        assert!(recorder.events().contains(&"close Rejected".to_string()));
+
+        drop((reader_a, writer_a));
+        while !recorder.events().contains(&"close PeerClosed".to_string()) {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let (mut reader_c, mut writer_c) = client(addr).await;
+        writer_c.write_packet(&Packet::Ping).await.unwrap();
+        assert_eq!(reader_c.read_packet().await.unwrap(), Packet::Pong);
+
+        shutdown.shutdown();
+        serving.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn local_client_reaches_handler_without_sockets() {
+        let handled = Arc::new(AtomicUsize::new(0));
+        let peer: SocketAddr = "10.0.0.7:4000".parse().unwrap();
+        let mut client = LocalClient::with_peer(Counter(Arc::clone(&handled)), peer);
+
+        assert_eq!(client.request(&Packet::Ping).await.unwrap(), Packet::Pong);
+        assert_eq!(client.request(&Packet::Data(vec![1, 2])).await.unwrap(), Packet::Data(vec![2, 1]));
+        client.send(&Packet::Pong).await.unwrap(); // No reply expected
+        client.close().await.unwrap();
+        assert_eq!(handled.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn accept_throttle_allows_burst_then_paces() {
+        let mut throttle = AcceptThrottle::new(AcceptRate { per_second: 10, burst: 3 });
+        let start = Instant::now();
+        for _ in 0..3 {
+            assert_eq!(throttle.delay(start), Duration::ZERO);
+            throttle.record(start);
+        }
+        assert_eq!(throttle.delay(start), Duration::from_millis(100));
+
+        let later = start + Duration::from_millis(100);
+        assert_eq!(throttle.delay(later), Duration::ZERO);
+        throttle.record(later);
+        assert_eq!(throttle.delay(later), Duration::from_millis(100));
+    }
+}
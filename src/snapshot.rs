@@ -0,0 +1,223 @@
+//! Snapshot + delta replication helper layered on `Packet::Data`.
+//!
+//! A sender replicates application state as a full snapshot followed by
+//! deltas, re-sending a snapshot every few deltas so late joiners and
+//! receivers that missed a frame can recover. Each payload carries a kind
+//! byte and a big-endian `u64` sequence number; every delta must follow
+//! the previous update's sequence exactly.
+//!
+//! ```text
+//! ┌──────┬──────────┬──────────────┐
+//! │ kind │ sequence │ body         │
+//! │  u8  │   u64    │ (app bytes)  │
+//! └──────┴──────────┴──────────────┘
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::packet::Packet;
+
+const KIND_SNAPSHOT: u8 = 0x01;
+const KIND_DELTA: u8 = 0x02;
+const PREFIX_LEN: usize = 9;
+
+/// Builds snapshot and delta packets with sequence linkage.
+#[derive(Debug, Clone)]
+pub struct SnapshotSender {
+    next_sequence: u64,
+    deltas_since_snapshot: u32,
+    resnapshot_every: u32,
+    force_snapshot: bool,
+}
+
+impl SnapshotSender {
+    /// Create a sender that asks for a fresh snapshot after every `resnapshot_every` deltas.
+    pub fn new(resnapshot_every: u32) -> Self {
+        Self {
+            next_sequence: 0,
+            deltas_since_snapshot: 0,
+            resnapshot_every: resnapshot_every.max(1),
+            force_snapshot: true,
+        }
+    }
+
+    /// `true` when the next update must be a snapshot: nothing was sent yet,
+    /// the re-snapshot interval elapsed, or one was requested.
+    pub fn needs_snapshot(&self) -> bool {
+        self.force_snapshot || self.deltas_since_snapshot >= self.resnapshot_every
+    }
+
+    /// Force the next update to be a snapshot, e.g. when a receiver reports a gap.
+    pub fn request_snapshot(&mut self) {
+        self.force_snapshot = true;
+    }
+
+    /// Encode the full `state` as a snapshot packet.
+    pub fn snapshot(&mut self, state: &[u8]) -> Packet {
+        self.force_snapshot = false;
+        self.deltas_since_snapshot = 0;
+        self.encode(KIND_SNAPSHOT, state)
+    }
+
+    /// Encode `delta` against the previous update.
+    ///
+    /// Callers should check [`needs_snapshot`](Self::needs_snapshot) first;
+    /// deltas sent before any snapshot cannot be applied by a receiver.
+    pub fn delta(&mut self, delta: &[u8]) -> Packet {
+        self.deltas_since_snapshot += 1;
+        self.encode(KIND_DELTA, delta)
+    }
+
+    /// Send a snapshot of `state()` if one is due, otherwise `delta`.
+    pub fn update(&mut self, delta: &[u8], state: impl FnOnce() -> Vec<u8>) -> Packet {
+        if self.needs_snapshot() {
+            self.snapshot(&state())
+        } else {
+            self.delta(delta)
+        }
+    }
+
+    fn encode(&mut self, kind: u8, body: &[u8]) -> Packet {
+        let mut payload = Vec::with_capacity(PREFIX_LEN + body.len());
+        payload.push(kind);
+        payload.extend_from_slice(&self.next_sequence.to_be_bytes());
+        payload.extend_from_slice(body);
+        self.next_sequence = self.next_sequence.wrapping_add(1);
+        Packet::Data(payload)
+    }
+}
+
+/// An update accepted by [`SnapshotReceiver::receive`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Update<'a> {
+    /// Replace local state with this body.
+    Snapshot(&'a [u8]),
+    /// Apply this body on top of the current state.
+    Delta(&'a [u8]),
+}
+
+/// Errors while accepting a replication payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// The payload is too short or has an unknown kind byte.
+    Malformed,
+    /// A delta arrived before any snapshot.
+    AwaitingSnapshot,
+    /// A delta does not follow the last applied update; wait for the next snapshot.
+    Gap { expected: u64, actual: u64 },
+}
+
+impl core::fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SnapshotError::Malformed => write!(f, "malformed snapshot/delta payload"),
+            SnapshotError::AwaitingSnapshot => write!(f, "delta received before a snapshot"),
+            SnapshotError::Gap { expected, actual } => {
+                write!(f, "delta sequence gap: expected {expected}, got {actual}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SnapshotError {}
+
+/// Validates sequence linkage on the receiving side.
+///
+/// After a gap the receiver drops every delta until the next snapshot.
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotReceiver {
+    last_sequence: Option<u64>,
+}
+
+impl SnapshotReceiver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `true` once a snapshot was applied and no gap has been seen since.
+    pub fn is_synced(&self) -> bool {
+        self.last_sequence.is_some()
+    }
+
+    /// Check a `Packet::Data` payload and return the update to apply.
+    pub fn receive<'a>(&mut self, payload: &'a [u8]) -> Result<Update<'a>, SnapshotError> {
+        if payload.len() < PREFIX_LEN {
+            return Err(SnapshotError::Malformed);
+        }
+        let mut sequence_bytes = [0u8; 8];
+        sequence_bytes.copy_from_slice(&payload[1..PREFIX_LEN]);
+        let sequence = u64::from_be_bytes(sequence_bytes);
+        let body = &payload[PREFIX_LEN..];
+
+        match payload[0] {
+            KIND_SNAPSHOT => {
+                self.last_sequence = Some(sequence);
+                Ok(Update::Snapshot(body))
+            }
+            KIND_DELTA => {
+                let last = self.last_sequence.ok_or(SnapshotError::AwaitingSnapshot)?;
+                let expected = last.wrapping_add(1);
+                if sequence != expected {
+                    self.last_sequence = None;
+                    return Err(SnapshotError::Gap { expected, actual: sequence });
+                }
+                self.last_sequence = Some(sequence);
+                Ok(Update::Delta(body))
+            }
+            _ => Err(SnapshotError::Malformed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(packet: &Packet) -> &[u8] {
+        match packet {
+            Packet::Data(bytes) => bytes,
+            other => panic!("expected Data, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn replicates_snapshot_then_deltas_with_periodic_resnapshot() {
+        let mut sender = SnapshotSender::new(2);
+        let mut receiver = SnapshotReceiver::new();
+
+        let packets: Vec<Packet> = (0..4u8).map(|i| sender.update(&[i], || vec![0xFF])).collect();
+        let updates: Vec<_> = packets.iter().map(|p| receiver.receive(payload(p)).unwrap()).collect();
+
+        assert_eq!(
+            updates,
+            vec![
+                Update::Snapshot(&[0xFF]),
+                Update::Delta(&[1]),
+                Update::Delta(&[2]),
+                Update::Snapshot(&[0xFF]),
+            ]
+        );
+    }
+
+    #[test]
+    fn gap_requires_new_snapshot() {
+        let mut sender = SnapshotSender::new(10);
+        let mut receiver = SnapshotReceiver::new();
+
+        receiver.receive(payload(&sender.snapshot(b"state"))).unwrap();
+        let _lost = sender.delta(b"a");
+        let err = receiver.receive(payload(&sender.delta(b"b"))).unwrap_err();
+        assert_eq!(err, SnapshotError::Gap { expected: 1, actual: 2 });
+        assert!(!receiver.is_synced());
+
+        let err = receiver.receive(payload(&sender.delta(b"c"))).unwrap_err();
+        assert_eq!(err, SnapshotError::AwaitingSnapshot);
+
+        sender.request_snapshot();
+        assert!(sender.needs_snapshot());
+        let resync = sender.snapshot(b"state2");
+        let update = receiver.receive(payload(&resync)).unwrap();
+        assert_eq!(update, Update::Snapshot(b"state2"));
+    }
+}
@@ -0,0 +1,293 @@
+//! Soak-test driver for validating long-running connections.
+//!
+//! [`run`] drives one end of a connection with randomized traffic and checks
+//! that the peer echoes it back intact. The peer is expected to behave like
+//! `examples/simple_echo.rs`: answer `Ping` with `Pong` and echo every other
+//! packet unchanged. Point it at your own server to soak your handlers.
+
+use std::io::{self, Read, Write};
+use std::time::Duration;
+
+use crate::clock::{Clock, SystemClock};
+use crate::codec::HELLO_LEN;
+use crate::packet::Packet;
+use crate::reader::PacketReader;
+use crate::rng::{Rng, SeededRng};
+use crate::writer::PacketWriter;
+
+/// Tunables for a soak run.
+#[derive(Debug, Clone)]
+pub struct SoakConfig {
+    /// Stop after this much wall-clock time.
+    pub duration: Duration,
+    /// Stop after this many packets were echoed (`None` = only `duration` applies).
+    pub max_packets: Option<u64>,
+    /// Packets sent before waiting for their echoes.
+    pub window: usize,
+    /// Largest randomized payload, in bytes.
+    pub max_payload: usize,
+    /// Run invariant checks every this many windows.
+    pub check_interval: u64,
+    /// Seed for the traffic generator, so failing runs can be replayed.
+    pub seed: u64,
+}
+
+impl Default for SoakConfig {
+    fn default() -> Self {
+        Self {
+            duration: Duration::from_secs(60 * 60),
+            max_packets: None,
+            window: 32,
+            max_payload: 1024,
+            check_interval: 16,
+            seed: 0x5EED,
+        }
+    }
+}
+
+/// Summary of a completed soak run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SoakReport {
+    pub packets_sent: u64,
+    pub packets_received: u64,
+    pub payload_bytes: u64,
+    pub invariant_checks: u64,
+    pub elapsed: Duration,
+}
+
+/// Reasons a soak run stopped early.
+#[derive(Debug)]
+pub enum SoakError {
+    Io(io::Error),
+    /// The echo for `sequence` did not match what was sent.
+    Mismatch { sequence: u64, expected: Packet, actual: Packet },
+    /// A periodic invariant check failed.
+    Invariant(String),
+}
+
+impl From<io::Error> for SoakError {
+    fn from(err: io::Error) -> Self {
+        SoakError::Io(err)
+    }
+}
+
+impl core::fmt::Display for SoakError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SoakError::Io(err) => write!(f, "soak I/O error: {err}"),
+            SoakError::Mismatch { sequence, expected, actual } => {
+                write!(f, "echo {sequence} mismatch: expected {expected:?}, got {actual:?}")
+            }
+            SoakError::Invariant(reason) => write!(f, "soak invariant violated: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for SoakError {}
+
+/// Drive randomized echo traffic over `reader`/`writer` until the configured
+/// duration or packet count is reached.
+///
+/// Every `Data` payload starts with its 8-byte big-endian sequence number,
+/// so a dropped, duplicated or reordered echo is reported as a
+/// [`SoakError::Mismatch`] with the sequence where continuity broke.
+pub fn run<R: Read, W: Write>(
+    reader: &mut PacketReader<R>,
+    writer: &mut PacketWriter<W>,
+    config: &SoakConfig,
+) -> Result<SoakReport, SoakError> {
+    run_with_clock(reader, writer, config, &SystemClock)
+}
+
+/// Like [`run`], measuring `config.duration` against `clock`.
+pub fn run_with_clock<R: Read, W: Write>(
+    reader: &mut PacketReader<R>,
+    writer: &mut PacketWriter<W>,
+    config: &SoakConfig,
+    clock: &dyn Clock,
+) -> Result<SoakReport, SoakError> {
+    let started = clock.now();
+    let mut traffic = Traffic::new(config.seed);
+    let mut report = SoakReport::default();
+    let mut expected = Vec::with_capacity(config.window);
+    let mut windows = 0u64;
+
+    while clock.now() - started < config.duration
+        && config.max_packets.map_or(true, |max| report.packets_received < max)
+    {
+        expected.clear();
+        for _ in 0..config.window.max(1) {
+            let packet = traffic.next_packet(report.packets_sent, config.max_payload);
+            writer.write_packet(&packet)?;
+            report.packets_sent += 1;
+            report.payload_bytes += payload_len(&packet) as u64;
+            expected.push(echo_of(packet));
+        }
+        writer.flush()?;
+
+        for want in expected.drain(..) {
+            let got = reader.read_packet()?;
+            if got != want {
+                let sequence = report.packets_received;
+                return Err(SoakError::Mismatch { sequence, expected: want, actual: got });
+            }
+            report.packets_received += 1;
+        }
+
+        windows += 1;
+        if windows % config.check_interval.max(1) == 0 {
+            check_invariants(reader, writer, &report)?;
+            report.invariant_checks += 1;
+        }
+    }
+
+    check_invariants(reader, writer, &report)?;
+    report.invariant_checks += 1;
+    report.elapsed = clock.now() - started;
+    Ok(report)
+}
+
+fn check_invariants<R: Read, W: Write>(
+    reader: &PacketReader<R>,
+    writer: &PacketWriter<W>,
+    report: &SoakReport,
+) -> Result<(), SoakError> {
+    if report.packets_sent != report.packets_received {
+        return Err(SoakError::Invariant(format!(
+            "{} packets sent but {} echoed",
+            report.packets_sent, report.packets_received
+        )));
+    }
+    if !reader.state().is_healthy() {
+        return Err(SoakError::Invariant(format!("reader is {}", reader.state())));
+    }
+    if !writer.state().is_healthy() || writer.has_pending() {
+        return Err(SoakError::Invariant(format!(
+            "writer is {} with pending bytes: {}",
+            writer.state(),
+            writer.has_pending()
+        )));
+    }
+    Ok(())
+}
+
+fn echo_of(packet: Packet) -> Packet {
+    match packet {
+        Packet::Ping => Packet::Pong,
+        other => other,
+    }
+}
+
+fn payload_len(packet: &Packet) -> usize {
+    match packet {
+        Packet::Ping | Packet::Pong => 0,
+        #[cfg(feature = "text")]
+        Packet::Message(text) => text.len(),
+        Packet::Data(bytes) => bytes.len(),
+        Packet::Batch(packets) => packets.iter().map(payload_len).sum(),
+        Packet::Hello { .. } => HELLO_LEN,
+    }
+}
+
+/// Randomized packet source; seeded so a failing run can be replayed.
+struct Traffic {
+    rng: SeededRng,
+}
+
+impl Traffic {
+    fn new(seed: u64) -> Self {
+        Self { rng: SeededRng::new(seed) }
+    }
+
+    fn next_packet(&mut self, sequence: u64, max_payload: usize) -> Packet {
+        let len = self.rng.below(max_payload as u64 + 1) as usize;
+        match self.rng.below(3) {
+            0 => Packet::Ping,
+            #[cfg(feature = "text")]
+            1 => Packet::Message((0..len).map(|_| (b'a' + self.rng.below(26) as u8) as char).collect()),
+            _ => {
+                let mut bytes = sequence.to_be_bytes().to_vec();
+                bytes.resize(len.max(8), 0);
+                self.rng.fill_bytes(&mut bytes[8..]);
+                Packet::Data(bytes)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+
+    fn spawn_echo_peer() -> TcpStream {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            stream.set_nodelay(true).unwrap();
+            let mut reader = PacketReader::new(stream.try_clone().unwrap());
+            let mut writer = PacketWriter::new(stream);
+            while let Ok(packet) = reader.read_packet() {
+                writer.write_packet(&echo_of(packet)).unwrap();
+            }
+        });
+        let stream = TcpStream::connect(addr).unwrap();
+        stream.set_nodelay(true).unwrap();
+        stream
+    }
+
+    #[test]
+    fn soaks_against_echo_peer() {
+        let stream = spawn_echo_peer();
+        let mut reader = PacketReader::new(stream.try_clone().unwrap());
+        let mut writer = PacketWriter::new(stream);
+        let config = SoakConfig {
+            max_packets: Some(500),
+            window: 10,
+            check_interval: 5,
+            ..SoakConfig::default()
+        };
+
+        let report = run(&mut reader, &mut writer, &config).unwrap();
+        assert_eq!(report.packets_sent, 500);
+        assert_eq!(report.packets_received, 500);
+        assert_eq!(report.invariant_checks, 11);
+    }
+
+    #[test]
+    fn stops_when_clock_reaches_duration() {
+        let stream = spawn_echo_peer();
+        let mut reader = PacketReader::new(stream.try_clone().unwrap());
+        let mut writer = PacketWriter::new(stream);
+        let clock = crate::clock::MockClock::new();
+        let config = SoakConfig { duration: Duration::ZERO, ..SoakConfig::default() };
+
+        let report = run_with_clock(&mut reader, &mut writer, &config, &clock).unwrap();
+        assert_eq!(report.packets_sent, 0);
+        assert_eq!(report.elapsed, Duration::ZERO);
+    }
+
+    #[cfg(feature = "text")]
+    #[test]
+    fn reports_mismatched_echo() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = PacketReader::new(stream.try_clone().unwrap());
+            let mut writer = PacketWriter::new(stream);
+            while reader.read_packet().is_ok() {
+                writer.write_packet(&Packet::Message("wrong".into())).unwrap();
+            }
+        });
+        let stream = TcpStream::connect(addr).unwrap();
+        let mut reader = PacketReader::new(stream.try_clone().unwrap());
+        let mut writer = PacketWriter::new(stream);
+        let config = SoakConfig { max_packets: Some(10), ..SoakConfig::default() };
+
+        let err = run(&mut reader, &mut writer, &config).unwrap_err();
+        assert!(matches!(err, SoakError::Mismatch { sequence: 0, .. }));
+    }
+}
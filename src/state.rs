@@ -0,0 +1,66 @@
+//! Health state shared by the packet reader and writer.
+
+use alloc::string::String;
+
+/// Health of a reader or writer after the errors it has seen so far.
+///
+/// A reader is poisoned for good by a hard I/O error, a missed frame
+/// deadline or a framing error that lost frame sync; a frame rejected on
+/// its own leaves it `Healthy`. A writer is poisoned only while a hard error
+/// has left a frame half-written: an error at a frame boundary leaves it
+/// `Healthy`, and completing the frame with `flush_pending` clears the
+/// poisoning. While not `Healthy`, other calls fail fast instead of touching
+/// the underlying stream again.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ConnectionState {
+    /// The stream is at a usable position.
+    #[default]
+    Healthy,
+    /// The stream is not at a usable position; the reason is kept for diagnostics.
+    Poisoned(String),
+    /// The stream reached end-of-file or was closed explicitly.
+    Closed,
+}
+
+impl ConnectionState {
+    pub fn is_healthy(&self) -> bool {
+        matches!(self, ConnectionState::Healthy)
+    }
+
+    pub fn is_poisoned(&self) -> bool {
+        matches!(self, ConnectionState::Poisoned(_))
+    }
+
+    pub fn is_closed(&self) -> bool {
+        matches!(self, ConnectionState::Closed)
+    }
+}
+
+impl core::fmt::Display for ConnectionState {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ConnectionState::Healthy => write!(f, "healthy"),
+            ConnectionState::Poisoned(reason) => write!(f, "poisoned: {reason}"),
+            ConnectionState::Closed => write!(f, "closed"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_to_healthy() {
+        let state = ConnectionState::default();
+        assert!(state.is_healthy());
+        assert!(!state.is_poisoned());
+        assert!(!state.is_closed());
+    }
+
+    #[test]
+    fn displays_poison_reason() {
+        let state = ConnectionState::Poisoned("broken pipe".into());
+        assert_eq!(state.to_string(), "poisoned: broken pipe");
+    }
+}
@@ -0,0 +1,180 @@
+//! Counters and distributions collected by decoders, readers and writers.
+
+/// Upper bound (inclusive) of the smallest histogram bucket, in bytes.
+const FIRST_BUCKET: usize = 16;
+/// Number of power-of-two buckets; the last one also catches anything larger.
+const BUCKETS: usize = 14;
+
+/// Histogram of wire frame sizes (header + payload) in power-of-two buckets.
+///
+/// Bucket `i` counts frames of at most `16 << i` bytes that did not fit in
+/// bucket `i - 1`. Histograms from many connections can be combined with
+/// [`merge`](Self::merge) for an aggregate view.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FrameSizeHistogram {
+    counts: [u64; BUCKETS],
+    total_bytes: u64,
+    max: usize,
+}
+
+impl FrameSizeHistogram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one frame of `size` bytes.
+    pub fn record(&mut self, size: usize) {
+        self.counts[bucket_index(size)] += 1;
+        self.total_bytes += size as u64;
+        self.max = self.max.max(size);
+    }
+
+    /// Add every observation from `other` into this histogram.
+    pub fn merge(&mut self, other: &FrameSizeHistogram) {
+        for (count, other_count) in self.counts.iter_mut().zip(other.counts) {
+            *count += other_count;
+        }
+        self.total_bytes += other.total_bytes;
+        self.max = self.max.max(other.max);
+    }
+
+    /// Number of frames recorded.
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.total_bytes
+    }
+
+    /// Largest frame recorded, in bytes.
+    pub fn max(&self) -> usize {
+        self.max
+    }
+
+    /// `(upper_bound, count)` for every bucket, smallest first.
+    pub fn buckets(&self) -> impl Iterator<Item = (usize, u64)> + '_ {
+        self.counts
+            .iter()
+            .enumerate()
+            .map(|(index, &count)| (bucket_bound(index), count))
+    }
+
+    /// Upper bound of the bucket containing the `quantile` (0.0..=1.0) frame,
+    /// or `None` if nothing was recorded.
+    pub fn quantile(&self, quantile: f64) -> Option<usize> {
+        let total = self.count();
+        if total == 0 {
+            return None;
+        }
+        let scaled = quantile.clamp(0.0, 1.0) * total as f64;
+        let mut rank = scaled as u64; // Round up by hand; `f64::ceil` needs std
+        if (rank as f64) < scaled {
+            rank += 1;
+        }
+        let rank = rank.max(1);
+        let mut seen = 0;
+        for (bound, count) in self.buckets() {
+            seen += count;
+            if seen >= rank {
+                return Some(bound.min(self.max));
+            }
+        }
+        Some(self.max)
+    }
+}
+
+/// How well a decoder is keeping sync with its peer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResyncStats {
+    /// Frames that decoded successfully.
+    pub frames_ok: u64,
+    /// Frames rejected because the payload checksum did not match.
+    pub checksum_failures: u64,
+    /// Frames rejected for any other codec error (bad opcode, bad UTF-8, ...).
+    pub frames_failed: u64,
+    /// Bytes discarded while scanning for the next header magic.
+    pub bytes_skipped: u64,
+    /// Corruption episodes that ended with a good frame, i.e. successful resyncs.
+    pub frames_recovered: u64,
+}
+
+impl ResyncStats {
+    /// Add every counter from `other` into these stats.
+    pub fn merge(&mut self, other: &ResyncStats) {
+        self.frames_ok += other.frames_ok;
+        self.checksum_failures += other.checksum_failures;
+        self.frames_failed += other.frames_failed;
+        self.bytes_skipped += other.bytes_skipped;
+        self.frames_recovered += other.frames_recovered;
+    }
+
+    /// Share of frames that arrived intact, from 0.0 to 1.0.
+    ///
+    /// Checksum failures and failed frames count against the score, and
+    /// every 9 skipped bytes (one header's worth of garbage) counts as one
+    /// lost frame. A link with no traffic yet scores 1.0.
+    pub fn link_quality(&self) -> f64 {
+        let lost = self.checksum_failures + self.frames_failed + (self.bytes_skipped + 8) / 9;
+        let total = self.frames_ok + lost;
+        if total == 0 {
+            return 1.0;
+        }
+        self.frames_ok as f64 / total as f64
+    }
+}
+
+fn bucket_index(size: usize) -> usize {
+    let mut index = 0;
+    while index + 1 < BUCKETS && size > bucket_bound(index) {
+        index += 1;
+    }
+    index
+}
+
+fn bucket_bound(index: usize) -> usize {
+    FIRST_BUCKET << index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_by_power_of_two() {
+        let mut histogram = FrameSizeHistogram::new();
+        histogram.record(9);
+        histogram.record(16);
+        histogram.record(17);
+        histogram.record(70_000);
+
+        let buckets: Vec<_> = histogram.buckets().collect();
+        assert_eq!(buckets[0], (16, 2));
+        assert_eq!(buckets[1], (32, 1));
+        assert_eq!(buckets[BUCKETS - 1], (16 << (BUCKETS - 1), 1));
+        assert_eq!(histogram.count(), 4);
+        assert_eq!(histogram.max(), 70_000);
+    }
+
+    #[test]
+    fn link_quality_reflects_losses() {
+        assert_eq!(ResyncStats::default().link_quality(), 1.0);
+
+        let stats = ResyncStats { frames_ok: 6, checksum_failures: 1, bytes_skipped: 10, ..Default::default() };
+        assert_eq!(stats.link_quality(), 0.666_666_666_666_666_6);
+    }
+
+    #[test]
+    fn merges_and_reports_quantiles() {
+        let mut small = FrameSizeHistogram::new();
+        (0..9).for_each(|_| small.record(9));
+        let mut large = FrameSizeHistogram::new();
+        large.record(1000);
+
+        small.merge(&large);
+        assert_eq!(small.count(), 10);
+        assert_eq!(small.quantile(0.5), Some(16));
+        assert_eq!(small.quantile(1.0), Some(1000));
+        assert_eq!(FrameSizeHistogram::new().quantile(0.5), None);
+    }
+}
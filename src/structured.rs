@@ -0,0 +1,220 @@
+//! Structured payloads encoded with postcard (`postcard` feature).
+//!
+//! postcard is a compact, `no_std`-friendly serde format, which makes it
+//! the recommended way to carry typed data in [`Packet::Data`] on small
+//! targets. Both peers must agree on the Rust type of each payload.
+//!
+//! With the `io` feature, [`TypedSender`] and [`TypedReceiver`] wrap a
+//! packet writer and reader into a channel of one Rust type.
+
+#[cfg(feature = "io")]
+use std::io::{self, Read, Write};
+#[cfg(feature = "io")]
+use std::marker::PhantomData;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::packet::Packet;
+#[cfg(feature = "io")]
+use crate::reader::PacketReader;
+#[cfg(feature = "io")]
+use crate::writer::PacketWriter;
+
+/// Serialize `value` into a `Data` packet.
+pub fn to_packet<T: Serialize + ?Sized>(value: &T) -> Result<Packet, PayloadError> {
+    postcard::to_allocvec(value).map(Packet::Data).map_err(PayloadError::Postcard)
+}
+
+/// Deserialize the payload of a `Data` packet.
+pub fn from_packet<T: DeserializeOwned>(packet: &Packet) -> Result<T, PayloadError> {
+    match packet {
+        Packet::Data(bytes) => from_payload(bytes),
+        other => Err(PayloadError::NotData(other.opcode())),
+    }
+}
+
+/// Deserialize a raw payload, borrowing strings and byte slices from it.
+pub fn from_payload<'a, T: Deserialize<'a>>(payload: &'a [u8]) -> Result<T, PayloadError> {
+    postcard::from_bytes(payload).map_err(PayloadError::Postcard)
+}
+
+/// Serialize `value` into `buf` without allocating, returning the used prefix.
+pub fn to_payload<'a, T: Serialize + ?Sized>(value: &T, buf: &'a mut [u8]) -> Result<&'a mut [u8], PayloadError> {
+    postcard::to_slice(value, buf).map_err(PayloadError::Postcard)
+}
+
+/// Errors from the structured payload helpers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PayloadError {
+    /// The packet was not `Data`; carries its opcode.
+    NotData(u8),
+    /// postcard failed to serialize or deserialize the value.
+    Postcard(postcard::Error),
+}
+
+impl core::fmt::Display for PayloadError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PayloadError::NotData(opcode) => write!(f, "expected a Data packet, got opcode {opcode:#04x}"),
+            PayloadError::Postcard(err) => write!(f, "postcard error: {err}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PayloadError {}
+
+#[cfg(feature = "io")]
+impl From<PayloadError> for io::Error {
+    fn from(err: PayloadError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// Sends values of one type as `Data` packets.
+///
+/// ```
+/// use byteframe::structured::{TypedReceiver, TypedSender};
+///
+/// let mut wire = Vec::new();
+/// TypedSender::<(u8, bool), _>::new(&mut wire).send(&(7, true)).unwrap();
+///
+/// let mut rx = TypedReceiver::<(u8, bool), _>::new(&wire[..]);
+/// assert_eq!(rx.recv().unwrap(), (7, true));
+/// ```
+#[cfg(feature = "io")]
+pub struct TypedSender<T: ?Sized, W> {
+    writer: PacketWriter<W>,
+    _type: PhantomData<fn(&T)>,
+}
+
+#[cfg(feature = "io")]
+impl<T: Serialize + ?Sized, W: Write> TypedSender<T, W> {
+    pub fn new(writer: W) -> Self {
+        Self::from_writer(PacketWriter::new(writer))
+    }
+
+    /// Wrap an already configured packet writer.
+    pub fn from_writer(writer: PacketWriter<W>) -> Self {
+        Self { writer, _type: PhantomData }
+    }
+
+    /// Serialize `value`, write it as one packet and flush.
+    pub fn send(&mut self, value: &T) -> io::Result<()> {
+        self.writer.write_packet(&to_packet(value)?)?;
+        self.writer.flush()
+    }
+
+    pub fn writer(&mut self) -> &mut PacketWriter<W> {
+        &mut self.writer
+    }
+
+    pub fn into_writer(self) -> PacketWriter<W> {
+        self.writer
+    }
+}
+
+/// Receives values of one type from `Data` packets.
+///
+/// `Ping` and `Pong` are skipped so keepalive traffic can share the stream;
+/// any other packet, or a payload that does not deserialize as `T`, fails
+/// with `InvalidData` wrapping a [`PayloadError`].
+#[cfg(feature = "io")]
+pub struct TypedReceiver<T, R> {
+    reader: PacketReader<R>,
+    _type: PhantomData<fn() -> T>,
+}
+
+#[cfg(feature = "io")]
+impl<T: DeserializeOwned, R: Read> TypedReceiver<T, R> {
+    pub fn new(reader: R) -> Self {
+        Self::from_reader(PacketReader::new(reader))
+    }
+
+    /// Wrap an already configured packet reader.
+    pub fn from_reader(reader: PacketReader<R>) -> Self {
+        Self { reader, _type: PhantomData }
+    }
+
+    /// Block until the next value arrives.
+    pub fn recv(&mut self) -> io::Result<T> {
+        loop {
+            match self.reader.read_packet()? {
+                Packet::Ping | Packet::Pong => continue,
+                packet => return Ok(from_packet(&packet)?),
+            }
+        }
+    }
+
+    pub fn reader(&mut self) -> &mut PacketReader<R> {
+        &mut self.reader
+    }
+
+    pub fn into_reader(self) -> PacketReader<R> {
+        self.reader
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Reading<'a> {
+        sensor: &'a str,
+        celsius: i16,
+        samples: Vec<u8>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Command {
+        id: u32,
+        enable: bool,
+    }
+
+    #[test]
+    fn round_trips_through_data_packet() {
+        let command = Command { id: 7, enable: true };
+        let packet = to_packet(&command).unwrap();
+
+        assert!(matches!(&packet, Packet::Data(bytes) if bytes.len() == 2));
+        assert_eq!(from_packet::<Command>(&packet).unwrap(), command);
+    }
+
+    #[test]
+    fn borrows_from_payload_without_allocating() {
+        let reading = Reading { sensor: "t0", celsius: -12, samples: vec![1, 2, 3] };
+        let mut buf = [0u8; 32];
+        let payload = to_payload(&reading, &mut buf).unwrap();
+
+        assert_eq!(from_payload::<Reading>(payload).unwrap(), reading);
+    }
+
+    #[cfg(feature = "io")]
+    #[test]
+    fn typed_channel_skips_keepalives_and_rejects_other_packets() {
+        let mut wire = Vec::new();
+        let mut writer = PacketWriter::new(&mut wire);
+        writer.write_packet(&Packet::Ping).unwrap();
+        let mut tx = TypedSender::<Command, _>::from_writer(writer);
+        tx.send(&Command { id: 1, enable: false }).unwrap();
+        tx.writer().write_packet(&Packet::Data(vec![0x80])).unwrap();
+        tx.writer().flush().unwrap();
+        drop(tx);
+
+        let mut rx = TypedReceiver::<Command, _>::new(&wire[..]);
+        assert_eq!(rx.recv().unwrap(), Command { id: 1, enable: false });
+        assert_eq!(rx.recv().unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(rx.recv().unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn rejects_other_packets_and_bad_payloads() {
+        assert_eq!(from_packet::<Command>(&Packet::Ping), Err(PayloadError::NotData(0x01)));
+        assert!(matches!(
+            from_packet::<Command>(&Packet::Data(vec![0x80])),
+            Err(PayloadError::Postcard(_))
+        ));
+    }
+}
@@ -0,0 +1,562 @@
+//! Tools for constructing deliberately broken traffic in tests and fuzzers.
+
+use crate::checksum::fnv1a32;
+use crate::codec::{self, ChecksumMode, CodecError, FrameFormat};
+use crate::framing::Framing;
+use crate::header::{
+    varint_len, Flags, Header, HEADER_LEN, HEADER_MAGIC, HEADER_MAGIC_FLAGGED, HEADER_MAGIC_V2, HEADER_MAGIC_VARINT,
+};
+use std::fmt::Write as _;
+
+use crate::packet::{Packet, OPCODE_BATCH, OPCODE_DATA, OPCODE_HELLO, OPCODE_MESSAGE, OPCODE_PING, OPCODE_PONG};
+use crate::rng::Rng;
+
+/// Builds raw frames field by field, including values a valid encoder would never produce.
+///
+/// Unless overridden, the magic is [`HEADER_MAGIC`], the length is the
+/// payload length and the checksum is computed over the payload.
+///
+/// ```
+/// use byteframe::testing::FrameBuilder;
+/// use byteframe::{codec, CodecError, Packet};
+///
+/// let frame = FrameBuilder::from_packet(&Packet::Data(b"hi".to_vec()))
+///     .unwrap()
+///     .checksum(0xDEADBEEF)
+///     .build();
+/// assert!(matches!(codec::decode(&frame), Err(CodecError::ChecksumMismatch { .. })));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameBuilder {
+    magic: u16,
+    opcode: u8,
+    flags: Flags,
+    length: Option<u32>,
+    checksum: Option<u32>,
+    payload: Vec<u8>,
+}
+
+impl FrameBuilder {
+    /// Start a frame with `opcode` and an empty payload.
+    ///
+    /// `opcode` is the whole wire byte, so values above 0x0F land in the
+    /// version nibble.
+    pub fn new(opcode: u8) -> Self {
+        Self {
+            magic: HEADER_MAGIC,
+            opcode,
+            flags: Flags::empty(),
+            length: None,
+            checksum: None,
+            payload: Vec::new(),
+        }
+    }
+
+    /// Start from the valid encoding of `packet`.
+    pub fn from_packet(packet: &Packet) -> Result<Self, CodecError> {
+        let mut frame = Vec::new();
+        codec::encode(packet, &mut frame)?;
+        Ok(Self::new(packet.opcode()).payload(&frame[HEADER_LEN..]))
+    }
+
+    pub fn magic(mut self, magic: u16) -> Self {
+        self.magic = magic;
+        self
+    }
+
+    pub fn opcode(mut self, opcode: u8) -> Self {
+        self.opcode = opcode;
+        self
+    }
+
+    /// Set the version nibble of the opcode byte.
+    pub fn version(mut self, version: u8) -> Self {
+        self.opcode = version << 4 | self.opcode & 0x0F;
+        self
+    }
+
+    /// Set header flags, switching to the flagged layout unless they are empty.
+    pub fn flags(mut self, flags: Flags) -> Self {
+        self.flags = flags;
+        self.magic = if flags.is_empty() { HEADER_MAGIC } else { HEADER_MAGIC_FLAGGED };
+        self
+    }
+
+    /// Declare a length that need not match the payload.
+    pub fn length(mut self, length: u32) -> Self {
+        self.length = Some(length);
+        self
+    }
+
+    /// Write this checksum instead of the payload's real one.
+    pub fn checksum(mut self, checksum: u32) -> Self {
+        self.checksum = Some(checksum);
+        self
+    }
+
+    pub fn payload(mut self, payload: impl Into<Vec<u8>>) -> Self {
+        self.payload = payload.into();
+        self
+    }
+
+    /// The header that [`build`](Self::build) will emit.
+    pub fn header(&self) -> Header {
+        Header {
+            magic: self.magic,
+            version: self.opcode >> 4,
+            opcode: self.opcode & 0x0F,
+            flags: self.flags,
+            length: self.length.unwrap_or(self.payload.len() as u32),
+            checksum: self.checksum.unwrap_or_else(|| fnv1a32(&self.payload)),
+        }
+    }
+
+    /// Serialize the header followed by the payload.
+    pub fn build(&self) -> Vec<u8> {
+        let mut frame = Vec::new();
+        self.header().write_to(&mut frame);
+        frame.extend_from_slice(&self.payload);
+        frame
+    }
+}
+
+/// A parameterized way of damaging a byte stream.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Corruption {
+    /// Flip each bit independently with probability `rate`.
+    BitFlips { rate: f64 },
+    /// Remove one random span of 1..=`max_len` bytes.
+    Truncate { max_len: usize },
+    /// Insert 1..=`max_len` random bytes at a random offset.
+    Insert { max_len: usize },
+    /// Cut the stream into `chunk_len`-byte chunks and shuffle them.
+    ReorderChunks { chunk_len: usize },
+}
+
+/// A corrupted stream together with a description of what was done to it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorruptionCase {
+    /// Human-readable description, e.g. `bit-flips(p=0.01): 12 bits`.
+    pub label: String,
+    pub corruption: Corruption,
+    /// Bits flipped, bytes removed, bytes inserted, or chunks moved.
+    pub affected: usize,
+    pub bytes: Vec<u8>,
+}
+
+/// Applies [`Corruption`] patterns to valid streams using a caller-supplied [`Rng`].
+///
+/// With a [`SeededRng`](crate::rng::SeededRng) every case is reproducible,
+/// so resync measurements can be compared across decoder changes.
+#[derive(Debug, Clone)]
+pub struct Corruptor<R> {
+    rng: R,
+}
+
+impl<R: Rng> Corruptor<R> {
+    pub fn new(rng: R) -> Self {
+        Self { rng }
+    }
+
+    /// Produce one labeled case from `stream`.
+    pub fn apply(&mut self, stream: &[u8], corruption: Corruption) -> CorruptionCase {
+        let mut bytes = stream.to_vec();
+        let (label, affected) = match corruption {
+            Corruption::BitFlips { rate } => {
+                let mut flipped = 0;
+                for byte in &mut bytes {
+                    for bit in 0..8 {
+                        if self.unit() < rate {
+                            *byte ^= 1 << bit;
+                            flipped += 1;
+                        }
+                    }
+                }
+                (format!("bit-flips(p={rate}): {flipped} bits"), flipped)
+            }
+            Corruption::Truncate { max_len } => {
+                let removed = self.span(bytes.len(), max_len);
+                let start = self.index(bytes.len() - removed + 1);
+                bytes.drain(start..start + removed);
+                (format!("truncate: {removed} bytes at {start}"), removed)
+            }
+            Corruption::Insert { max_len } => {
+                let count = 1 + self.index(max_len.max(1));
+                let at = self.index(bytes.len() + 1);
+                let mut garbage = vec![0u8; count];
+                self.rng.fill_bytes(&mut garbage);
+                bytes.splice(at..at, garbage);
+                (format!("insert: {count} bytes at {at}"), count)
+            }
+            Corruption::ReorderChunks { chunk_len } => {
+                let mut chunks: Vec<Vec<u8>> = bytes.chunks(chunk_len.max(1)).map(<[u8]>::to_vec).collect();
+                for i in (1..chunks.len()).rev() {
+                    let j = self.index(i + 1);
+                    chunks.swap(i, j);
+                }
+                let original: Vec<&[u8]> = stream.chunks(chunk_len.max(1)).collect();
+                let moved = chunks.iter().zip(&original).filter(|(now, was)| now.as_slice() != **was).count();
+                bytes = chunks.concat();
+                (format!("reorder({chunk_len}-byte chunks): {moved} moved"), moved)
+            }
+        };
+        CorruptionCase { label, corruption, affected, bytes }
+    }
+
+    /// Apply every pattern in `patterns` to `stream`, one case each.
+    pub fn cases(&mut self, stream: &[u8], patterns: &[Corruption]) -> Vec<CorruptionCase> {
+        patterns.iter().map(|&pattern| self.apply(stream, pattern)).collect()
+    }
+
+    /// Uniform value in `[0, 1)`.
+    fn unit(&mut self) -> f64 {
+        (self.rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn index(&mut self, bound: usize) -> usize {
+        if bound == 0 {
+            return 0;
+        }
+        self.rng.below(bound as u64) as usize
+    }
+
+    fn span(&mut self, len: usize, max_len: usize) -> usize {
+        (1 + self.index(max_len.max(1))).min(len)
+    }
+}
+
+/// One entry of [`golden_vectors`]: a packet and its exact encoding under
+/// one combination of options.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoldenVector {
+    /// Path-like label, e.g. `data/v1/checksum-off/priority-2/slip`.
+    pub name: String,
+    pub packet: Packet,
+    pub checksum: ChecksumMode,
+    pub flags: Flags,
+    pub format: FrameFormat,
+    pub framing: Framing,
+    pub bytes: Vec<u8>,
+}
+
+/// Deterministic encodings across every combination of wire options, for
+/// checking other implementations against this one.
+///
+/// Each sample packet is encoded with v1 and varint headers, with the
+/// checksum on and off, without flags, with a priority and with a header
+/// CRC, under length and SLIP framing. A 64 KiB payload covers the v2 and long varint
+/// headers. Compression and encryption flags are left
+/// out: the crate has no codec behind them, so there is no reference
+/// output to give.
+pub fn golden_vectors() -> Vec<GoldenVector> {
+    let samples = [
+        ("ping", Packet::Ping),
+        ("data", Packet::Data(vec![0x00, 0x7F, 0xC0, 0xDB, 0xFF])), // Includes both SLIP specials
+        ("batch", Packet::Batch(vec![Packet::Pong, Packet::Data(vec![1, 2])])),
+        ("hello", Packet::hello()),
+    ];
+    let mut vectors = Vec::new();
+    for (name, packet) in &samples {
+        for format in [FrameFormat::V1, FrameFormat::Varint] {
+            for checksum in [ChecksumMode::Enabled, ChecksumMode::Disabled] {
+                for flags in [Flags::empty(), Flags::empty().with_priority(2), Flags::HEADER_CRC] {
+                    for framing in [Framing::Length, Framing::Slip] {
+                        vectors.push(golden_vector(name, packet.clone(), checksum, flags, format, framing));
+                    }
+                }
+            }
+        }
+    }
+    let jumbo = Packet::Data((0..=u16::MAX as u32).map(|i| i as u8).collect());
+    for format in [FrameFormat::V2, FrameFormat::Varint] {
+        for checksum in [ChecksumMode::Enabled, ChecksumMode::Disabled] {
+            vectors.push(golden_vector("jumbo", jumbo.clone(), checksum, Flags::empty(), format, Framing::Length));
+        }
+    }
+    vectors
+}
+
+fn golden_vector(
+    name: &str,
+    packet: Packet,
+    checksum: ChecksumMode,
+    flags: Flags,
+    format: FrameFormat,
+    framing: Framing,
+) -> GoldenVector {
+    let mut bytes = Vec::new();
+    framing.encode_format(&packet, flags, format, &mut bytes, checksum).expect("sample packets are encodable");
+    let format_label = match format {
+        FrameFormat::V1 => "v1",
+        FrameFormat::V2 => "v2",
+        FrameFormat::Varint => "varint",
+    };
+    let checksum_label = match checksum {
+        ChecksumMode::Enabled => "checksum-on",
+        ChecksumMode::Disabled => "checksum-off",
+    };
+    let flags_label = match flags.priority() {
+        _ if flags.contains(Flags::HEADER_CRC) => "header-crc".to_string(),
+        0 => "plain".to_string(),
+        priority => format!("priority-{priority}"),
+    };
+    let framing_label = match framing {
+        Framing::Length => "length",
+        Framing::Slip => "slip",
+    };
+    GoldenVector {
+        name: format!("{name}/{format_label}/{checksum_label}/{flags_label}/{framing_label}"),
+        packet,
+        checksum,
+        flags,
+        format,
+        framing,
+        bytes,
+    }
+}
+
+/// One line per vector: name, length, FNV-1a of the bytes, then the bytes
+/// in hex. Frames over 64 bytes show only their first 16.
+pub fn render_golden_vectors(vectors: &[GoldenVector]) -> String {
+    let mut out = String::new();
+    for vector in vectors {
+        let bytes = &vector.bytes;
+        let shown = if bytes.len() > 64 { &bytes[..16] } else { &bytes[..] };
+        let more = if shown.len() < bytes.len() { " .." } else { "" };
+        let _ = writeln!(out, "{} {} {:08x} {}{more}", vector.name, bytes.len(), fnv1a32(bytes), hex(shown));
+    }
+    out
+}
+
+/// Render encoded frames as annotated hex for snapshot tests.
+///
+/// Each frame's header fields are labelled and its payload is dumped
+/// sixteen bytes per row with an ASCII column. Bytes that do not form a
+/// complete frame are listed as trailing. The output depends only on the
+/// input bytes, so a wire format change shows up as a reviewable diff.
+///
+/// ```
+/// use byteframe::testing::annotated_hex;
+/// use byteframe::{codec, Packet};
+///
+/// let mut wire = Vec::new();
+/// codec::encode(&Packet::Data(b"hi".to_vec()), &mut wire).unwrap();
+/// assert_eq!(
+///     annotated_hex(&wire),
+///     "frame 0 (11 bytes)
+///   magic     aa 55
+///   opcode    04 (Data)
+///   length    00 02 (2)
+///   checksum  68 3a f6 9a (ok)
+///   payload   68 69                                            |hi|
+/// "
+/// );
+/// ```
+pub fn annotated_hex(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    let mut rest = bytes;
+    let mut index = 0;
+    while let Ok(header) = Header::from_bytes(rest) {
+        let header_len = header.wire_len();
+        let Some(len) = header_len.checked_add(header.length as usize) else { break };
+        let Some(payload) = rest.get(header_len..len) else { break };
+        let raw = &rest[..header_len];
+        let length_len = match header.magic {
+            HEADER_MAGIC_V2 => 4,
+            HEADER_MAGIC_VARINT => varint_len(header.length),
+            _ => 2,
+        };
+        let crc_len = usize::from(header.magic != HEADER_MAGIC && header.flags.contains(Flags::HEADER_CRC));
+        let fields = &raw[header_len - crc_len - length_len - 4..header_len - crc_len]; // Length and checksum close every layout but the CRC
+        let _ = writeln!(out, "frame {index} ({len} bytes)");
+        let _ = writeln!(out, "  magic     {}", hex(&raw[0..2]));
+        let version = match header.version {
+            0 => String::new(),
+            version => format!(", version {version}"),
+        };
+        let _ = writeln!(out, "  opcode    {} ({}{version})", hex(&raw[2..3]), opcode_name(header.opcode));
+        if header_len > HEADER_LEN {
+            let _ = writeln!(out, "  flags     {} (priority {})", hex(&raw[3..4]), header.flags.priority());
+        }
+        let _ = writeln!(out, "  length    {} ({})", hex(&fields[..length_len]), header.length);
+        let actual = fnv1a32(payload);
+        let status = match header.checksum {
+            checksum if checksum == actual => "ok".to_string(),
+            0 => "disabled".to_string(),
+            _ => format!("mismatch, payload hashes to {actual:08x}"),
+        };
+        let _ = writeln!(out, "  checksum  {} ({status})", hex(&fields[length_len..]));
+        if crc_len > 0 {
+            let _ = writeln!(out, "  hdr crc   {} (ok)", hex(&raw[header_len - 1..])); // Parsing already verified it
+        }
+        dump_rows(&mut out, "payload", payload);
+        rest = &rest[len..];
+        index += 1;
+    }
+    if !rest.is_empty() {
+        let _ = writeln!(out, "trailing ({} bytes)", rest.len());
+        dump_rows(&mut out, "bytes", rest);
+    }
+    out
+}
+
+fn opcode_name(opcode: u8) -> &'static str {
+    match opcode {
+        OPCODE_PING => "Ping",
+        OPCODE_PONG => "Pong",
+        OPCODE_MESSAGE => "Message",
+        OPCODE_DATA => "Data",
+        OPCODE_BATCH => "Batch",
+        OPCODE_HELLO => "Hello",
+        _ => "unknown",
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect::<Vec<_>>().join(" ")
+}
+
+/// Write `bytes` as labelled rows of sixteen, each with an ASCII column.
+fn dump_rows(out: &mut String, label: &str, bytes: &[u8]) {
+    for (row, chunk) in bytes.chunks(16).enumerate() {
+        let label = if row == 0 { label } else { "" };
+        let ascii: String = chunk
+            .iter()
+            .map(|&byte| if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' })
+            .collect();
+        let _ = writeln!(out, "  {label:<9} {:<47}  |{ascii}|", hex(chunk));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framing::{FrameDecoder, FrameError};
+    #[cfg(feature = "text")]
+    use crate::packet::OPCODE_DATA;
+    use crate::rng::SeededRng;
+
+    fn sample_stream(count: usize) -> Vec<u8> {
+        let mut stream = Vec::new();
+        for i in 0..count {
+            codec::encode(&Packet::Data(vec![i as u8; 16]), &mut stream).unwrap();
+        }
+        stream
+    }
+
+    #[test]
+    fn golden_vectors_decode_and_stay_pinned() {
+        let vectors = golden_vectors();
+        assert_eq!(vectors.len(), 4 * 2 * 2 * 3 * 2 + 2 * 2);
+        for vector in &vectors {
+            let mut decoder = FrameDecoder::new();
+            decoder.set_framing(vector.framing);
+            decoder.set_checksum_mode(vector.checksum);
+            decoder.set_validation(crate::header::ValidationConfig { max_length: u32::MAX, ..Default::default() });
+            let result = decoder.decode(&vector.bytes);
+            assert!(result.errors.is_empty(), "{}: {:?}", vector.name, result.errors);
+            let expected = match &vector.packet {
+                Packet::Batch(packets) => packets.clone(),
+                packet => vec![packet.clone()],
+            };
+            assert_eq!(result.packets, expected, "{}", vector.name);
+        }
+
+        let rendered = render_golden_vectors(&vectors);
+        assert!(rendered.starts_with("ping/v1/checksum-on/plain/length 9 "));
+        assert!(rendered.contains("jumbo/v2/checksum-off/plain/length 65548 "));
+        assert!(rendered.contains("jumbo/varint/checksum-off/plain/length 65547 "));
+        assert_eq!(fnv1a32(rendered.as_bytes()), 0xc824438f, "{rendered}");
+    }
+
+    #[test]
+    fn corruption_cases_are_reproducible_and_labeled() {
+        let stream = sample_stream(10);
+        let patterns = [
+            Corruption::BitFlips { rate: 0.01 },
+            Corruption::Truncate { max_len: 8 },
+            Corruption::Insert { max_len: 4 },
+            Corruption::ReorderChunks { chunk_len: 25 },
+        ];
+
+        let first = Corruptor::new(SeededRng::new(1)).cases(&stream, &patterns);
+        let again = Corruptor::new(SeededRng::new(1)).cases(&stream, &patterns);
+        assert_eq!(first, again);
+
+        assert!(first[0].label.starts_with("bit-flips"));
+        assert_eq!(first[1].bytes.len(), stream.len() - first[1].affected);
+        assert_eq!(first[2].bytes.len(), stream.len() + first[2].affected);
+        assert_eq!(first[3].bytes.len(), stream.len());
+    }
+
+    #[test]
+    fn frame_aligned_reordering_keeps_every_frame() {
+        let stream = sample_stream(20);
+        let frame_len = HEADER_LEN + 16;
+        let case = Corruptor::new(SeededRng::new(9)).apply(&stream, Corruption::ReorderChunks { chunk_len: frame_len });
+        assert!(case.affected > 0, "{}", case.label);
+
+        let output = FrameDecoder::new().decode(&case.bytes);
+        assert_eq!(output.packets.len(), 20);
+        assert!(output.errors.is_empty());
+    }
+
+    #[cfg(feature = "text")]
+    #[test]
+    fn defaults_produce_valid_frame() {
+        let frame = FrameBuilder::new(OPCODE_DATA).payload(vec![1, 2, 3]).build();
+        assert_eq!(codec::decode(&frame).unwrap(), Packet::Data(vec![1, 2, 3]));
+
+        let original = FrameBuilder::from_packet(&Packet::Message("x".into())).unwrap().build();
+        let mut encoded = Vec::new();
+        codec::encode(&Packet::Message("x".into()), &mut encoded).unwrap();
+        assert_eq!(original, encoded);
+    }
+
+    #[test]
+    fn overrides_every_header_field() {
+        let frame = FrameBuilder::new(0xEE)
+            .magic(0x1234)
+            .length(500)
+            .checksum(7)
+            .payload(vec![9])
+            .build();
+        assert_eq!(&frame[..HEADER_LEN], &Header { magic: 0x1234, version: 0x0E, opcode: 0x0E, flags: Flags::empty(), length: 500, checksum: 7 }.to_bytes());
+        assert_eq!(frame.len(), HEADER_LEN + 1);
+    }
+
+    #[test]
+    fn malformed_frames_drive_decoder_errors() {
+        let mut stream = FrameBuilder::new(0x0E).build();
+        stream.extend(FrameBuilder::from_packet(&Packet::Ping).unwrap().build());
+
+        let output = FrameDecoder::new().decode(&stream);
+        assert_eq!(output.packets, vec![Packet::Ping]);
+        assert!(matches!(output.errors[..], [FrameError::Codec(CodecError::InvalidOpcode(0x0E))]));
+    }
+
+    #[test]
+    fn annotated_hex_labels_bad_frames_and_trailing_bytes() {
+        let mut stream = FrameBuilder::new(OPCODE_PING).build();
+        stream.extend(FrameBuilder::new(0xEE).payload(vec![0x41; 18]).checksum(1).build());
+        stream.extend_from_slice(&[0xAA, 0x55, 0x01]);
+
+        let expected = "\
+frame 0 (9 bytes)
+  magic     aa 55
+  opcode    01 (Ping)
+  length    00 00 (0)
+  checksum  81 1c 9d c5 (ok)
+frame 1 (27 bytes)
+  magic     aa 55
+  opcode    ee (unknown, version 14)
+  length    00 12 (18)
+  checksum  00 00 00 01 (mismatch, payload hashes to c7f49547)
+  payload   41 41 41 41 41 41 41 41 41 41 41 41 41 41 41 41  |AAAAAAAAAAAAAAAA|
+            41 41                                            |AA|
+trailing (3 bytes)
+  bytes     aa 55 01                                         |.U.|
+";
+        assert_eq!(annotated_hex(&stream), expected);
+    }
+}
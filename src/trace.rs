@@ -0,0 +1,174 @@
+//! Bounded record of a writer's scheduling decisions, for diagnosing stalls.
+
+use std::collections::VecDeque;
+use std::fmt::{self, Write as _};
+use std::time::{Duration, Instant};
+
+use crate::clock::{Clock, SystemClock};
+
+/// Ring buffer of the most recent scheduling events of one writer.
+///
+/// Enable it with [`PacketWriter::enable_trace`](crate::writer::PacketWriter::enable_trace)
+/// and read it back with [`dump`](Self::dump) when a packet leaves late.
+/// Each frame gets a sequence number at enqueue, and its `dequeue` event
+/// reports how long it waited behind earlier frames. Once `capacity`
+/// events are held the oldest are dropped and counted.
+pub struct SchedulingTrace {
+    clock: Box<dyn Clock>,
+    started: Instant,
+    events: VecDeque<TraceEvent>,
+    capacity: usize,
+    dropped: u64,
+    next_frame: u64,
+    in_flight: VecDeque<(u64, Instant)>, // Frames enqueued but not yet fully on the wire, oldest first
+}
+
+/// One entry of a [`SchedulingTrace`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceEvent {
+    /// Time since the trace was enabled.
+    pub at: Duration,
+    pub kind: TraceKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceKind {
+    /// A frame was accepted for sending with `queued` frames still ahead of it.
+    Enqueue { frame: u64, bytes: usize, queued: usize },
+    /// The last byte of a frame reached the sink, `waited` after its enqueue.
+    Dequeue { frame: u64, waited: Duration },
+    /// A flush was requested with `pending` bytes not yet accepted by the sink.
+    Flush { pending: usize },
+    /// The sink returned `WouldBlock`, leaving `pending` bytes buffered.
+    Backpressure { pending: usize },
+}
+
+impl SchedulingTrace {
+    /// Keep the last `capacity` events, timed by the system clock.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_clock(capacity, SystemClock)
+    }
+
+    pub fn with_clock(capacity: usize, clock: impl Clock + 'static) -> Self {
+        let started = clock.now();
+        Self {
+            clock: Box::new(clock),
+            started,
+            events: VecDeque::with_capacity(capacity),
+            capacity,
+            dropped: 0,
+            next_frame: 0,
+            in_flight: VecDeque::new(),
+        }
+    }
+
+    /// Events held, oldest first.
+    pub fn events(&self) -> impl Iterator<Item = &TraceEvent> {
+        self.events.iter()
+    }
+
+    /// Events pushed out of the buffer by newer ones.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Forget the recorded events; frames in flight are still tracked.
+    pub fn clear(&mut self) {
+        self.events.clear();
+        self.dropped = 0;
+    }
+
+    /// One line per event, preceded by a count of dropped events if any.
+    pub fn dump(&self) -> String {
+        let mut out = String::new();
+        if self.dropped > 0 {
+            let _ = writeln!(out, "({} earlier events dropped)", self.dropped);
+        }
+        for event in &self.events {
+            let _ = writeln!(out, "{event}");
+        }
+        out
+    }
+
+    pub(crate) fn enqueue(&mut self, bytes: usize) {
+        let frame = self.next_frame;
+        self.next_frame += 1;
+        let queued = self.in_flight.len();
+        self.in_flight.push_back((frame, self.clock.now()));
+        self.record(TraceKind::Enqueue { frame, bytes, queued });
+    }
+
+    /// The oldest frame in flight finished leaving.
+    pub(crate) fn dequeue(&mut self) {
+        let Some((frame, enqueued)) = self.in_flight.pop_front() else { return };
+        let waited = self.clock.now().saturating_duration_since(enqueued);
+        self.record(TraceKind::Dequeue { frame, waited });
+    }
+
+    pub(crate) fn flush(&mut self, pending: usize) {
+        self.record(TraceKind::Flush { pending });
+    }
+
+    pub(crate) fn backpressure(&mut self, pending: usize) {
+        self.record(TraceKind::Backpressure { pending });
+    }
+
+    fn record(&mut self, kind: TraceKind) {
+        if self.capacity == 0 {
+            self.dropped += 1;
+            return;
+        }
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+            self.dropped += 1;
+        }
+        let at = self.clock.now().saturating_duration_since(self.started);
+        self.events.push_back(TraceEvent { at, kind });
+    }
+}
+
+impl fmt::Debug for SchedulingTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SchedulingTrace")
+            .field("events", &self.events.len())
+            .field("capacity", &self.capacity)
+            .field("dropped", &self.dropped)
+            .field("in_flight", &self.in_flight.len())
+            .finish()
+    }
+}
+
+impl fmt::Display for TraceEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:>10.3}ms ", self.at.as_secs_f64() * 1000.0)?;
+        match self.kind {
+            TraceKind::Enqueue { frame, bytes, queued } => write!(f, "enqueue #{frame} ({bytes} bytes, {queued} ahead)"),
+            TraceKind::Dequeue { frame, waited } => write!(f, "dequeue #{frame} (waited {waited:?})"),
+            TraceKind::Flush { pending } => write!(f, "flush ({pending} bytes pending)"),
+            TraceKind::Backpressure { pending } => write!(f, "backpressure ({pending} bytes pending)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_newest_events_and_counts_the_rest() {
+        let mut trace = SchedulingTrace::new(2);
+        trace.enqueue(10);
+        trace.enqueue(20);
+        trace.dequeue();
+        trace.flush(20);
+
+        let kinds: Vec<TraceKind> = trace.events().map(|event| event.kind).collect();
+        assert!(matches!(kinds[..], [TraceKind::Dequeue { frame: 0, .. }, TraceKind::Flush { pending: 20 }]));
+        assert_eq!(trace.dropped(), 2);
+        assert!(trace.dump().starts_with("(2 earlier events dropped)\n"));
+
+        trace.clear();
+        trace.dequeue(); // Frame 1 is still tracked after a clear
+        assert!(matches!(trace.events().next().unwrap().kind, TraceKind::Dequeue { frame: 1, .. }));
+    }
+}
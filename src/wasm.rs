@@ -0,0 +1,66 @@
+//! Browser adapter feeding [`FrameDecoder`] from JavaScript byte arrays (`wasm` feature).
+//!
+//! A WebSocket with `binaryType = "arraybuffer"` delivers each binary
+//! message as an `ArrayBuffer`. Messages need not line up with frames, so
+//! pass every one to [`WasmFrameDecoder::push`] and handle whatever
+//! packets complete:
+//!
+//! ```no_run
+//! use byteframe::wasm::WasmFrameDecoder;
+//! use js_sys::{ArrayBuffer, Uint8Array};
+//!
+//! fn on_message(decoder: &mut WasmFrameDecoder, data: &ArrayBuffer) {
+//!     let result = decoder.push(&Uint8Array::new(data));
+//!     for packet in result.packets {
+//!         // Hand the packet to the application
+//!         let _ = packet;
+//!     }
+//! }
+//! ```
+//!
+//! Only the `js-sys` bindings are used, so the adapter works the same
+//! under wasm-bindgen and other glue. Calling it outside a wasm32
+//! JavaScript host panics, as all `js-sys` calls do.
+
+use alloc::vec::Vec;
+
+use js_sys::Uint8Array;
+
+use crate::framing::{DecodeResult, FrameDecoder};
+
+/// A [`FrameDecoder`] that accepts `Uint8Array` chunks.
+#[derive(Debug, Default)]
+pub struct WasmFrameDecoder {
+    decoder: FrameDecoder,
+    scratch: Vec<u8>, // Reused copy of the JavaScript bytes in linear memory
+}
+
+impl WasmFrameDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wrap an already configured decoder.
+    pub fn from_decoder(decoder: FrameDecoder) -> Self {
+        Self { decoder, scratch: Vec::new() }
+    }
+
+    /// Copy `chunk` out of JavaScript memory and decode it.
+    pub fn push(&mut self, chunk: &Uint8Array) -> DecodeResult {
+        self.scratch.resize(chunk.length() as usize, 0);
+        chunk.copy_to(&mut self.scratch);
+        self.decoder.decode(&self.scratch)
+    }
+
+    pub fn decoder(&self) -> &FrameDecoder {
+        &self.decoder
+    }
+
+    pub fn decoder_mut(&mut self) -> &mut FrameDecoder {
+        &mut self.decoder
+    }
+
+    pub fn into_decoder(self) -> FrameDecoder {
+        self.decoder
+    }
+}
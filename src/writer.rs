@@ -1,9 +1,63 @@
 //! Packet writer that wraps any `std::io::Write` sink.
 
+use std::collections::VecDeque;
 use std::io::{self, Write};
 
-use crate::codec::{self, CodecError};
+use crate::clock::{Clock, SystemClock};
+use crate::codec::{ChecksumMode, CodecError, FrameFormat};
+use crate::framing::Framing;
+use crate::header::Flags;
 use crate::packet::Packet;
+use crate::state::ConnectionState;
+use crate::stats::FrameSizeHistogram;
+use crate::trace::SchedulingTrace;
+
+/// Pending bytes above which [`PacketWriter`] refuses new frames by default.
+pub const DEFAULT_HIGH_WATER: usize = 1024 * 1024;
+
+/// Per-call options for [`PacketWriter::write_packet_with`] and its async
+/// counterpart.
+///
+/// Fields may be added in later releases, each defaulting to what
+/// `write_packet` does, so build values with the methods:
+///
+/// ```
+/// use byteframe::writer::WriteOpts;
+///
+/// let urgent = WriteOpts::new().priority(3).flush(true);
+/// assert_eq!(urgent.priority, 3);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct WriteOpts {
+    /// Priority from 0 to 3, carried in the header flags. Above 0 the frame
+    /// uses the flagged layout, which only flag-aware peers accept.
+    pub priority: u8,
+    /// Flush the sink once the frame is written.
+    pub flush: bool,
+}
+
+impl WriteOpts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the priority; values above 3 are clamped.
+    pub fn priority(mut self, priority: u8) -> Self {
+        self.priority = priority.min(3);
+        self
+    }
+
+    pub fn flush(mut self, flush: bool) -> Self {
+        self.flush = flush;
+        self
+    }
+
+    /// Header flags these options ask for.
+    pub(crate) fn flags(&self) -> Flags {
+        Flags::empty().with_priority(self.priority)
+    }
+}
 
 /// Wraps a `Write` sink and provides packet-level writing.
 ///
@@ -21,12 +75,24 @@ use crate::packet::Packet;
 /// let mut writer = PacketWriter::new(stream);
 ///
 /// writer.write_packet(&Packet::Ping).unwrap();
+/// # #[cfg(feature = "text")]
 /// writer.write_packet(&Packet::Message("hello".into())).unwrap();
 /// writer.flush().unwrap();
 /// ```
 pub struct PacketWriter<W> {
     writer: W,
     encode_buffer: Vec<u8>,
+    pending: Vec<u8>, // Encoded frame bytes the sink has not accepted yet
+    pending_frames: VecDeque<usize>, // Lengths of the frames queued in `pending`
+    high_water: usize, // Pending bytes at which new frames are refused
+    front_written: usize, // Bytes of the first queued frame already on the wire
+    state: ConnectionState, // Poisoned when a hard error left a frame half-written
+    frame_sizes: FrameSizeHistogram, // Sizes of every frame accepted for sending
+    checksum_mode: ChecksumMode,
+    framing: Framing,
+    format: FrameFormat,
+    header_crc: bool,
+    trace: Option<SchedulingTrace>, // Scheduling events, once enabled
 }
 
 impl<W: Write> PacketWriter<W> {
@@ -40,6 +106,17 @@ impl<W: Write> PacketWriter<W> {
         Self {
             writer,
             encode_buffer: Vec::with_capacity(capacity),
+            pending: Vec::new(),
+            pending_frames: VecDeque::new(),
+            high_water: DEFAULT_HIGH_WATER,
+            front_written: 0,
+            state: ConnectionState::Healthy,
+            frame_sizes: FrameSizeHistogram::new(),
+            checksum_mode: ChecksumMode::Enabled,
+            framing: Framing::Length,
+            format: FrameFormat::V1,
+            header_crc: false,
+            trace: None,
         }
     }
 
@@ -48,26 +125,287 @@ impl<W: Write> PacketWriter<W> {
     /// This method encodes the packet and writes the complete frame
     /// (header + payload) to the underlying writer.
     ///
+    /// On a non-blocking sink, any part of the frame the sink does not
+    /// accept (`WouldBlock`) is kept in an internal pending buffer and the
+    /// call still succeeds. Later frames are queued behind it, so frames are
+    /// never interleaved. Use [`has_pending`](Self::has_pending) and
+    /// [`flush_pending`](Self::flush_pending) to drain the buffer once the
+    /// sink becomes writable again. Once the sink still refuses bytes and
+    /// [`high_water`](Self::set_high_water) bytes are pending, new frames
+    /// are refused with `WouldBlock` instead of queued; the packet is not
+    /// sent and the stream stays at a frame boundary.
+    ///
+    /// # Frame boundaries
+    ///
+    /// If the sink fails before any byte of the frame was written, the packet
+    /// is not sent and the stream is still at a frame boundary. If it fails
+    /// after part of a frame reached the sink, the writer becomes poisoned:
+    /// the rest of the frame stays pending, further `write_packet` calls are
+    /// refused, and only [`flush_pending`](Self::flush_pending) may be used to
+    /// complete the frame, which clears the poisoning.
+    ///
     /// # Errors
     ///
     /// Returns `io::Error` if:
-    /// - The packet payload exceeds the maximum size (65535 bytes)
+    /// - The packet payload exceeds the maximum size of the
+    ///   [frame format](Self::set_frame_format) (65535 bytes by default)
     /// - The underlying write operation fails
+    /// - The writer is poisoned by an earlier partial frame, or closed
     pub fn write_packet(&mut self, packet: &Packet) -> io::Result<()> {
+        self.write_packet_with(packet, &WriteOpts::default())
+    }
+
+    /// [`write_packet`](Self::write_packet) with per-call options.
+    pub fn write_packet_with(&mut self, packet: &Packet, opts: &WriteOpts) -> io::Result<()> {
+        self.ensure_usable()?;
+
         self.encode_buffer.clear(); // Clear buffer and encode packet
-        codec::encode(packet, &mut self.encode_buffer).map_err(codec_to_io_error)?;
-        
-        self.writer.write_all(&self.encode_buffer)?; // Write the complete frame atomically
+        self.framing
+            .encode_format(packet, self.flags(opts), self.format, &mut self.encode_buffer, self.checksum_mode)
+            .map_err(codec_to_io_error)?;
+
+        if !self.pending.is_empty() {
+            match self.flush_pending() {
+                Ok(()) => {}
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                    if self.pending.len() >= self.high_water {
+                        return Err(err); // flush_pending traced the backpressure
+                    }
+                    // Queue behind earlier unfinished frames
+                    self.frame_sizes.record(self.encode_buffer.len());
+                    self.pending.extend_from_slice(&self.encode_buffer);
+                    self.pending_frames.push_back(self.encode_buffer.len());
+                    let len = self.encode_buffer.len();
+                    self.traced(|trace| trace.enqueue(len));
+                    return Ok(());
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        self.frame_sizes.record(self.encode_buffer.len());
+        let (written, outcome) = write_some(&mut self.writer, &self.encode_buffer);
+        let len = self.encode_buffer.len();
+        if written == len {
+            self.traced(|trace| {
+                trace.enqueue(len);
+                trace.dequeue();
+            });
+        } else if written > 0 {
+            self.pending.extend_from_slice(&self.encode_buffer[written..]);
+            self.pending_frames.push_back(len);
+            self.front_written = written;
+            self.traced(|trace| trace.enqueue(len));
+        } else if is_would_block(&outcome) {
+            self.pending.extend_from_slice(&self.encode_buffer);
+            self.pending_frames.push_back(len);
+            self.traced(|trace| trace.enqueue(len));
+        }
+        if is_would_block(&outcome) {
+            let pending = self.pending.len();
+            self.traced(|trace| trace.backpressure(pending));
+        }
+        ignore_would_block(self.check_outcome(outcome))?;
+        if opts.flush {
+            self.flush()?;
+        }
         Ok(())
     }
 
+    /// Refuse new frames with `WouldBlock` while at least `bytes` are pending
+    /// and the sink accepts no more; [`DEFAULT_HIGH_WATER`] by default.
+    ///
+    /// A frame is always accepted when nothing is pending, so the mark
+    /// bounds the buffer to about `bytes` plus the largest frame.
+    pub fn set_high_water(&mut self, bytes: usize) {
+        self.high_water = bytes;
+    }
+
+    pub fn high_water(&self) -> usize {
+        self.high_water
+    }
+
+    /// Returns `true` if encoded bytes are waiting for the sink to accept them.
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Returns `true` if a hard error left a frame partially written.
+    pub fn is_poisoned(&self) -> bool {
+        self.state.is_poisoned()
+    }
+
+    /// Choose whether outgoing frames carry a payload checksum.
+    ///
+    /// With `Disabled` the checksum field is written as zero; the peer's
+    /// reader must be configured the same way.
+    pub fn set_checksum_mode(&mut self, mode: ChecksumMode) {
+        self.checksum_mode = mode;
+    }
+
+    pub fn checksum_mode(&self) -> ChecksumMode {
+        self.checksum_mode
+    }
+
+    /// Choose how frame boundaries are marked; see [`Framing`].
+    pub fn set_framing(&mut self, framing: Framing) {
+        self.framing = framing;
+    }
+
+    /// Choose the header layouts this connection sends: [`FrameFormat::V2`]
+    /// allows payloads over 65535 bytes, [`FrameFormat::Varint`] uses LEB128
+    /// lengths throughout. The peer must understand the chosen headers.
+    pub fn set_frame_format(&mut self, format: FrameFormat) {
+        self.format = format;
+    }
+
+    pub fn frame_format(&self) -> FrameFormat {
+        self.format
+    }
+
+    /// End every header with a CRC-8 so the peer rejects a corrupted header
+    /// at once; see [`Flags::HEADER_CRC`]. Headers switch to the flagged
+    /// layout, which only flag-aware peers accept.
+    pub fn set_header_crc(&mut self, enabled: bool) {
+        self.header_crc = enabled;
+    }
+
+    pub fn header_crc(&self) -> bool {
+        self.header_crc
+    }
+
+    fn flags(&self, opts: &WriteOpts) -> Flags {
+        if self.header_crc {
+            opts.flags() | Flags::HEADER_CRC
+        } else {
+            opts.flags()
+        }
+    }
+
+    /// Sizes of the frames sent (or queued) on this connection.
+    pub fn frame_sizes(&self) -> &FrameSizeHistogram {
+        &self.frame_sizes
+    }
+
+    /// Start recording scheduling events in a ring buffer of `capacity`
+    /// entries, replacing any earlier trace; see [`SchedulingTrace`].
+    ///
+    /// Frames already pending are enqueued in the trace as it starts.
+    pub fn enable_trace(&mut self, capacity: usize) {
+        self.enable_trace_with_clock(capacity, SystemClock);
+    }
+
+    /// [`enable_trace`](Self::enable_trace), timing events with `clock`.
+    pub fn enable_trace_with_clock(&mut self, capacity: usize, clock: impl Clock + 'static) {
+        let mut trace = SchedulingTrace::with_clock(capacity, clock);
+        for &frame_len in &self.pending_frames {
+            trace.enqueue(frame_len);
+        }
+        self.trace = Some(trace);
+    }
+
+    /// The scheduling trace, if enabled. [`dump`](SchedulingTrace::dump) it
+    /// to see why a frame left late.
+    pub fn trace(&self) -> Option<&SchedulingTrace> {
+        self.trace.as_ref()
+    }
+
+    /// Stop tracing, returning what was recorded.
+    pub fn disable_trace(&mut self) -> Option<SchedulingTrace> {
+        self.trace.take()
+    }
+
+    /// Current health of the writer.
+    pub fn state(&self) -> &ConnectionState {
+        &self.state
+    }
+
+    /// Flush everything pending and mark the writer closed.
+    ///
+    /// Any later `write_packet` call fails immediately.
+    pub fn close(&mut self) -> io::Result<()> {
+        self.ensure_usable()?;
+        self.flush()?;
+        self.state = ConnectionState::Closed;
+        Ok(())
+    }
+
+    /// Try to write all pending frame bytes to the underlying writer.
+    ///
+    /// Completing a partially written frame clears the poisoned state.
+    ///
+    /// # Errors
+    ///
+    /// Returns `io::ErrorKind::WouldBlock` if the sink stopped accepting data
+    /// before the pending buffer was drained; call again once it is writable.
+    pub fn flush_pending(&mut self) -> io::Result<()> {
+        if self.state.is_closed() {
+            return Err(closed_error());
+        }
+        let (written, outcome) = write_some(&mut self.writer, &self.pending);
+        self.pending.drain(..written);
+        self.advance_frames(written);
+        if self.front_written == 0 && self.state.is_poisoned() {
+            self.state = ConnectionState::Healthy; // Back on a frame boundary
+        }
+        if is_would_block(&outcome) {
+            let pending = self.pending.len();
+            self.traced(|trace| trace.backpressure(pending));
+        }
+        self.check_outcome(outcome)
+    }
+
     /// Flush the underlying writer.
     ///
-    /// This ensures all buffered data is written to the underlying sink.
+    /// This ensures all buffered data, including pending frame bytes,
+    /// is written to the underlying sink.
     pub fn flush(&mut self) -> io::Result<()> {
+        let pending = self.pending.len();
+        self.traced(|trace| trace.flush(pending));
+        self.flush_pending()?;
         self.writer.flush()
     }
 
+    /// Fail fast if the writer is poisoned or closed.
+    fn ensure_usable(&self) -> io::Result<()> {
+        match &self.state {
+            ConnectionState::Healthy => Ok(()),
+            ConnectionState::Poisoned(reason) => Err(io::Error::new(io::ErrorKind::Other, format!("writer poisoned: {reason}"))),
+            ConnectionState::Closed => Err(closed_error()),
+        }
+    }
+
+    /// Account for `written` bytes of the pending buffer reaching the sink.
+    fn advance_frames(&mut self, mut written: usize) {
+        while let Some(&frame_len) = self.pending_frames.front() {
+            let remaining = frame_len - self.front_written;
+            if written < remaining {
+                self.front_written += written;
+                return;
+            }
+            written -= remaining;
+            self.pending_frames.pop_front();
+            self.front_written = 0;
+            self.traced(SchedulingTrace::dequeue);
+        }
+    }
+
+    fn traced(&mut self, record: impl FnOnce(&mut SchedulingTrace)) {
+        if let Some(trace) = &mut self.trace {
+            record(trace);
+        }
+    }
+
+    /// Poison the writer if a hard error struck in the middle of a frame.
+    fn check_outcome(&mut self, outcome: io::Result<()>) -> io::Result<()> {
+        if let Err(err) = &outcome {
+            if !is_would_block(&outcome) && self.front_written > 0 {
+                self.state = ConnectionState::Poisoned(err.to_string());
+            }
+        }
+        outcome
+    }
+
     /// Access the underlying writer.
     pub fn get_ref(&self) -> &W {
         &self.writer
@@ -84,12 +422,45 @@ impl<W: Write> PacketWriter<W> {
     }
 }
 
+/// Write as much of `buf` as the sink accepts, returning the byte count
+/// alongside the error (if any) that stopped the loop.
+fn write_some<W: Write>(writer: &mut W, buf: &[u8]) -> (usize, io::Result<()>) {
+    let mut written = 0;
+    while written < buf.len() {
+        match writer.write(&buf[written..]) {
+            Ok(0) => {
+                return (written, Err(io::Error::new(io::ErrorKind::WriteZero, "failed to write frame bytes")));
+            }
+            Ok(n) => written += n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return (written, Err(err)),
+        }
+    }
+    (written, Ok(()))
+}
+
+fn closed_error() -> io::Error {
+    io::Error::new(io::ErrorKind::BrokenPipe, "writer is closed")
+}
+
+fn is_would_block(result: &io::Result<()>) -> bool {
+    matches!(result, Err(err) if err.kind() == io::ErrorKind::WouldBlock)
+}
+
+/// Treat `WouldBlock` as success: the remaining bytes are already pending.
+fn ignore_would_block(result: io::Result<()>) -> io::Result<()> {
+    if is_would_block(&result) {
+        return Ok(());
+    }
+    result
+}
+
 /// Helper to convert codec errors to I/O errors.
-fn codec_to_io_error(err: CodecError) -> io::Error {
+pub(crate) fn codec_to_io_error(err: CodecError) -> io::Error {
     match err {
         CodecError::PayloadTooLarge(size) => io::Error::new(
             io::ErrorKind::InvalidInput,
-            format!("payload too large: {} bytes (max: 65535)", size),
+            format!("payload too large for the frame format: {} bytes", size),
         ),
         other => io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", other)),
     }
@@ -99,8 +470,22 @@ fn codec_to_io_error(err: CodecError) -> io::Error {
 mod tests {
     use super::*;
     use crate::codec;
+    use crate::framing::FrameError;
+    use crate::header::{HeaderError, ValidationConfig, FLAGGED_HEADER_LEN, HEADER_LEN, HEADER_MAGIC_V2, V2_HEADER_LEN};
     use crate::packet::Packet;
 
+    #[test]
+    fn write_opts_carry_priority_and_flush() {
+        let mut writer = PacketWriter::new(Vec::new());
+        writer.write_packet_with(&Packet::Ping, &WriteOpts::new().priority(9).flush(true)).unwrap();
+        writer.write_packet(&Packet::Pong).unwrap();
+
+        let wire = writer.into_writer();
+        let (packet, flags) = codec::decode_flagged(&wire[..FLAGGED_HEADER_LEN], ChecksumMode::Enabled).unwrap();
+        assert_eq!((packet, flags.priority()), (Packet::Ping, 3));
+        assert_eq!(codec::decode(&wire[FLAGGED_HEADER_LEN..]).unwrap(), Packet::Pong);
+    }
+
     #[test]
     fn writes_single_packet() {
         let mut buf = Vec::new();
@@ -114,6 +499,7 @@ mod tests {
         assert_eq!(packet, Packet::Ping);
     }
 
+    #[cfg(feature = "text")]
     #[test]
     fn writes_multiple_packets() {
         let mut buf = Vec::new();
@@ -157,6 +543,30 @@ mod tests {
         assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
     }
 
+    #[test]
+    fn v2_format_frames_large_payloads_and_decoder_detects_it() {
+        let large = Packet::Data(vec![7; 70000]);
+        let mut writer = PacketWriter::new(Vec::new());
+        writer.set_frame_format(FrameFormat::V2);
+        writer.write_packet(&large).unwrap();
+        writer.write_packet(&Packet::Ping).unwrap();
+
+        let wire = writer.into_writer();
+        assert_eq!(wire[..2], HEADER_MAGIC_V2.to_be_bytes());
+        assert_eq!(wire.len(), V2_HEADER_LEN + 70000 + 9); // The small frame keeps the v1 layout
+
+        let output = crate::framing::FrameDecoder::new().decode(&wire);
+        assert!(matches!(output.errors[..], [FrameError::Header(HeaderError::LengthExceeded { length: 70000, .. })]));
+        assert_eq!(output.packets, [Packet::Ping]); // Large frames are opt-in on the receiving side
+
+        let mut decoder = crate::framing::FrameDecoder::new();
+        decoder.set_validation(ValidationConfig { max_length: u32::MAX, ..Default::default() });
+        let output = decoder.decode(&wire);
+        assert!(output.errors.is_empty());
+        assert_eq!(output.packets, [large, Packet::Ping]);
+    }
+
+    #[cfg(feature = "text")]
     #[test]
     fn encodes_correctly() {
         let mut buf = Vec::new();
@@ -174,4 +584,170 @@ mod tests {
         // bytes[5..9] are checksum
         assert_eq!(&buf[9..13], b"test"); // Payload
     }
+
+    /// Sink that accepts a limited number of bytes before returning `WouldBlock`.
+    struct ThrottledSink {
+        data: Vec<u8>,
+        budget: usize,
+    }
+
+    impl Write for ThrottledSink {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.budget == 0 {
+                return Err(io::ErrorKind::WouldBlock.into());
+            }
+            let n = buf.len().min(self.budget);
+            self.data.extend_from_slice(&buf[..n]);
+            self.budget -= n;
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "text")]
+    #[test]
+    fn buffers_partial_frames_on_would_block() {
+        let sink = ThrottledSink { data: Vec::new(), budget: 5 };
+        let mut writer = PacketWriter::new(sink);
+
+        writer.write_packet(&Packet::Message("hello".into())).unwrap();
+        writer.write_packet(&Packet::Data(vec![9, 8, 7])).unwrap();
+        assert!(writer.has_pending());
+        assert_eq!(writer.get_ref().data.len(), 5);
+
+        let err = writer.flush_pending().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+
+        writer.get_mut().budget = usize::MAX;
+        writer.flush_pending().unwrap();
+        assert!(!writer.has_pending());
+
+        let data = &writer.get_ref().data;
+        let first = codec::decode(data).unwrap();
+        assert_eq!(first, Packet::Message("hello".into()));
+        let second = codec::decode(&data[HEADER_LEN + 5..]).unwrap();
+        assert_eq!(second, Packet::Data(vec![9, 8, 7]));
+    }
+
+    #[test]
+    fn refuses_frames_above_the_high_water_mark() {
+        use crate::trace::TraceKind;
+
+        let mut writer = PacketWriter::new(ThrottledSink { data: Vec::new(), budget: 0 });
+        writer.set_high_water(2 * HEADER_LEN);
+        writer.enable_trace(16);
+        writer.write_packet(&Packet::Ping).unwrap();
+        writer.write_packet(&Packet::Pong).unwrap();
+        let err = writer.write_packet(&Packet::Ping).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+        assert!(!writer.is_poisoned());
+        assert_eq!(writer.frame_sizes().count(), 2);
+        let last = writer.trace().unwrap().events().last().unwrap().kind;
+        assert_eq!(last, TraceKind::Backpressure { pending: 2 * HEADER_LEN });
+
+        writer.get_mut().budget = usize::MAX;
+        writer.write_packet(&Packet::Ping).unwrap();
+        assert!(!writer.has_pending());
+        assert_eq!(writer.get_ref().data.len(), 3 * HEADER_LEN);
+    }
+
+    #[test]
+    fn trace_shows_how_long_a_queued_frame_waited() {
+        use crate::clock::MockClock;
+        use crate::trace::TraceKind;
+        use std::time::Duration;
+
+        let clock = MockClock::new();
+        let mut writer = PacketWriter::new(ThrottledSink { data: Vec::new(), budget: HEADER_LEN });
+        writer.enable_trace_with_clock(16, clock.clone());
+        writer.write_packet(&Packet::Ping).unwrap(); // Fits exactly
+        writer.write_packet(&Packet::Pong).unwrap(); // Sink is full: queued
+        clock.advance(Duration::from_secs(3));
+        writer.get_mut().budget = usize::MAX;
+        writer.flush().unwrap();
+
+        let trace = writer.trace().unwrap();
+        let kinds: Vec<TraceKind> = trace.events().map(|event| event.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                TraceKind::Enqueue { frame: 0, bytes: HEADER_LEN, queued: 0 },
+                TraceKind::Dequeue { frame: 0, waited: Duration::ZERO },
+                TraceKind::Enqueue { frame: 1, bytes: HEADER_LEN, queued: 0 },
+                TraceKind::Backpressure { pending: HEADER_LEN },
+                TraceKind::Flush { pending: HEADER_LEN },
+                TraceKind::Dequeue { frame: 1, waited: Duration::from_secs(3) },
+            ]
+        );
+        assert!(trace.dump().ends_with("3000.000ms dequeue #1 (waited 3s)\n"), "{}", trace.dump());
+    }
+
+    /// Sink that accepts a limited number of bytes before failing hard.
+    struct FailingSink {
+        data: Vec<u8>,
+        budget: usize,
+    }
+
+    impl Write for FailingSink {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.budget == 0 {
+                return Err(io::ErrorKind::BrokenPipe.into());
+            }
+            let n = buf.len().min(self.budget);
+            self.data.extend_from_slice(&buf[..n]);
+            self.budget -= n;
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "text")]
+    #[test]
+    fn poisons_on_mid_frame_error_until_completed() {
+        let sink = FailingSink { data: Vec::new(), budget: 5 };
+        let mut writer = PacketWriter::new(sink);
+
+        let err = writer.write_packet(&Packet::Message("hello".into())).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+        assert!(writer.is_poisoned());
+        assert!(writer.write_packet(&Packet::Ping).is_err());
+
+        writer.get_mut().budget = usize::MAX;
+        writer.flush_pending().unwrap();
+        assert!(!writer.is_poisoned());
+        writer.write_packet(&Packet::Pong).unwrap();
+
+        let data = &writer.get_ref().data;
+        assert_eq!(codec::decode(data).unwrap(), Packet::Message("hello".into()));
+        assert_eq!(codec::decode(&data[HEADER_LEN + 5..]).unwrap(), Packet::Pong);
+    }
+
+    #[test]
+    fn error_on_frame_boundary_does_not_poison() {
+        let sink = FailingSink { data: Vec::new(), budget: 0 };
+        let mut writer = PacketWriter::new(sink);
+
+        assert!(writer.write_packet(&Packet::Ping).is_err());
+        assert!(!writer.is_poisoned());
+        assert!(!writer.has_pending());
+    }
+
+    #[test]
+    fn refuses_writes_after_close() {
+        let mut buf = Vec::new();
+        let mut writer = PacketWriter::new(&mut buf);
+
+        writer.write_packet(&Packet::Ping).unwrap();
+        writer.close().unwrap();
+        assert_eq!(writer.state(), &ConnectionState::Closed);
+
+        let err = writer.write_packet(&Packet::Pong).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+    }
 }